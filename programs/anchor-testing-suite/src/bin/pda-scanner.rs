@@ -1,59 +1,531 @@
 use anyhow::Result;              // nice error handling library
 use clap::{Parser, Subcommand};  // library for CLI tools
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::process;
 
+// Program .so loading and ELF validation.
+#[path = "pda_scanner/binaries.rs"]
+mod binaries;
 // Case generation + execution pipeline.
 #[path = "pda_scanner/cases.rs"]
 mod cases;
+// Post-execution pass/fail classification rules.
+#[path = "pda_scanner/classify.rs"]
+mod classify;
+// Per-instruction reachability for the `coverage` command.
+#[path = "pda_scanner/coverage.rs"]
+mod coverage;
+// Breaking-change detection between two IDL versions.
+#[path = "pda_scanner/compare.rs"]
+mod compare;
+// Regression diff between a baseline and a current test report.
+#[path = "pda_scanner/diff.rs"]
+mod diff;
+// Anchor and IDL error code names.
+#[path = "pda_scanner/errors.rs"]
+mod errors;
+// Exit-code contract and the failures that map to it.
+#[path = "pda_scanner/exit.rs"]
+mod exit;
+// `--fixtures` account files and `--assertions` state checks.
+#[path = "pda_scanner/fixtures.rs"]
+mod fixtures;
+// Seeded random perturbations for the `fuzz` command.
+#[path = "pda_scanner/fuzz.rs"]
+mod fuzz;
+// Self-contained HTML report (`--format html`).
+#[path = "pda_scanner/html_report.rs"]
+mod html_report;
+// Per-file IDL format detection and legacy normalization.
+#[path = "pda_scanner/idl_format.rs"]
+mod idl_format;
+// IDL type layouts and account space computation.
+#[path = "pda_scanner/layout.rs"]
+mod layout;
+// Dry-run case matrix for the `list` command.
+#[path = "pda_scanner/list.rs"]
+mod list;
 // Report writer for JSON output.
 #[path = "pda_scanner/report.rs"]
 mod report;
+// Verbose single-case reruns (`test --case-id`, `replay`).
+#[path = "pda_scanner/replay.rs"]
+mod replay;
 // Orchestration for the `test` command.
 #[path = "pda_scanner/runner.rs"]
 mod runner;
+// SARIF findings for security scanning pipelines (`--format sarif`).
+#[path = "pda_scanner/sarif.rs"]
+mod sarif;
 // PDA discovery from IDL.
 #[path = "pda_scanner/scan.rs"]
 mod scan;
+// Hand-written program specs for IDL-less runs.
+#[path = "pda_scanner/spec_file.rs"]
+mod spec_file;
 // IDL + deploy artifact parsing.
 #[path = "pda_scanner/specs.rs"]
 mod specs;
 // Shared data types across modules.
 #[path = "pda_scanner/types.rs"]
 mod types;
+// Static IDL warnings and promotion rules.
+#[path = "pda_scanner/warnings.rs"]
+mod warnings;
 
 // CLI definition for `pda-scanner`.
 #[derive(Parser)]
 #[command(name = "anchor-suite")]
 #[command(about = "Anchor testing suite CLI")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
+#[command(after_help = exit::EXIT_CODES_HELP)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
 }
 
 // Subcommands supported by the CLI.
+// Parsed once at startup, so the size gap between `test` and `scan` args doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     Scan {
         #[arg(short, long)]
         project_dir: Option<String>,
+        /// Print the PDAs as a JSON document instead of text
+        #[arg(long)]
+        json: bool,
+        /// Write the JSON document to this path instead of stdout
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// Read IDLs from this directory instead of <project-dir>/target/idl
+        #[arg(long, value_name = "DIR")]
+        idl_dir: Option<PathBuf>,
+        /// Scan this IDL file instead of the IDL directory (repeatable)
+        #[arg(long = "idl", value_name = "FILE")]
+        idl_files: Vec<PathBuf>,
+        /// Only scan the program with this IDL name, IDL file stem or program address
+        #[arg(long, value_name = "NAME_OR_ADDRESS")]
+        program: Option<String>,
     },
+    #[command(after_help = exit::EXIT_CODES_HELP)]
     Test {
         #[arg(short, long)]
         project_dir: Option<String>,
+        /// JSON rules file that overrides the built-in pass/fail classification
+        #[arg(long)]
+        rules: Option<PathBuf>,
+        /// Fail the run when any instruction arg type can't be encoded (default: skip it)
+        #[arg(long)]
+        fail_on_unsupported_types: bool,
+        /// Skip features that shell out to external commands (cargo smoke test)
+        #[arg(long)]
+        offline: bool,
+        /// Encode string args as "a" instead of "" so programs parse real content
+        #[arg(long)]
+        non_empty_strings: bool,
+        /// Create every writable account that is neither a signer nor a PDA as a rent-exempt
+        /// system account before sending, so base cases don't fail on a missing account
+        #[arg(long)]
+        fund_writable: bool,
+        /// Lamports written to u64 `balance` fields of PDAs the harness fabricates
+        #[arg(long, value_name = "LAMPORTS", default_value_t = 0)]
+        fabricated_balance: u64,
+        /// JSON file mapping account names to { lamports, owner, data_base64 }; those accounts
+        /// are seeded into LiteSVM before each case
+        #[arg(long, value_name = "PATH")]
+        fixtures: Option<PathBuf>,
+        /// JSON file of account-state assertions checked after each named instruction's base case
+        #[arg(long, value_name = "PATH")]
+        assertions: Option<PathBuf>,
+        /// Record per-case time spent in add_program/airdrop/build_accounts/send_ix
+        #[arg(long)]
+        debug_harness: bool,
+        /// Fail the run if any warning is reported
+        #[arg(long)]
+        warnings_as_errors: bool,
+        /// Fail the run on warnings with this code (repeatable)
+        #[arg(long = "error-on", value_name = "CODE")]
+        error_on: Vec<String>,
+        /// Test from a hand-written JSON spec file instead of target/idl
+        #[arg(long, value_name = "FILE")]
+        spec: Option<PathBuf>,
+        /// Expect duplicate_account cases to fail instead of accepting any outcome
+        #[arg(long)]
+        duplicate_accounts_must_fail: bool,
+        /// Expect extra_account cases to fail instead of accepting any outcome
+        #[arg(long)]
+        extra_accounts_must_fail: bool,
+        /// Require the instruction's base case to log a line containing SUBSTRING, as
+        /// <instruction>=<substring> (repeatable)
+        #[arg(long = "expect-log", value_name = "INSTRUCTION=SUBSTRING")]
+        expect_logs: Vec<String>,
+        /// Fail the run if a base case uses more compute units than this, as <units> or
+        /// <instruction>=<units> (repeatable; per-instruction values win)
+        #[arg(long = "max-cu", value_name = "[INSTRUCTION=]UNITS")]
+        max_cu: Vec<String>,
+        /// Regenerate and run only the case with this id (from report.json), printing its accounts,
+        /// instruction data, full logs and raw error
+        #[arg(long, value_name = "ID")]
+        case_id: Option<String>,
+        /// Also run N cases per instruction with every arg set to a random value
+        #[arg(long, value_name = "N")]
+        fuzz: Option<usize>,
+        /// Seed for --fuzz; the same seed generates the same cases (default: from the clock)
+        #[arg(long, value_name = "SEED", requires = "fuzz")]
+        fuzz_seed: Option<u64>,
+        /// Only run cases for this instruction (repeatable)
+        #[arg(long = "instruction", value_name = "NAME")]
+        instructions: Vec<String>,
+        /// Only run cases for instructions whose name matches this regex
+        #[arg(long, value_name = "REGEX")]
+        filter: Option<String>,
+        /// Print the instruction names from the loaded IDLs and exit
+        #[arg(long)]
+        list_instructions: bool,
+        /// Only run cases of this mutation kind, e.g. wrong_pda (repeatable)
+        #[arg(long = "mutation", value_name = "KIND")]
+        mutations: Vec<String>,
+        /// Comma-separated mutation kinds to run, e.g. wrong_pda,truncate_data
+        #[arg(long = "mutations", value_name = "LIST", value_delimiter = ',')]
+        mutation_list: Vec<String>,
+        /// After the cases, run these instructions in order on one LiteSVM that keeps its state,
+        /// e.g. initialize_vault,deposit,withdraw
+        #[arg(long, value_name = "IX1,IX2,...", value_delimiter = ',')]
+        sequence: Vec<String>,
+        /// Stop executing cases after the first unexpected result
+        #[arg(long)]
+        fail_fast: bool,
+        /// Only test the program with this IDL name, IDL file stem or program address
+        #[arg(long, value_name = "NAME_OR_ADDRESS")]
+        program: Option<String>,
+        /// Program id for an IDL without an address, as <name>:<pubkey> (repeatable; name is the
+        /// IDL file stem or program name)
+        #[arg(long = "program-id", value_name = "NAME:PUBKEY")]
+        program_ids: Vec<String>,
+        /// Pair an IDL with a .so in target/deploy, as <idl_stem>=<so_filename> (repeatable);
+        /// used before the name-matching heuristic
+        #[arg(long = "map", value_name = "IDL_STEM=SO")]
+        so_map: Vec<String>,
+        /// Read IDLs from this directory instead of <project-dir>/target/idl
+        #[arg(long, value_name = "DIR")]
+        idl_dir: Option<PathBuf>,
+        /// Read program binaries from this directory instead of <project-dir>/target/deploy
+        #[arg(long, value_name = "DIR")]
+        deploy_dir: Option<PathBuf>,
+        /// Load this IDL file instead of scanning the IDL directory (repeatable)
+        #[arg(long = "idl", value_name = "FILE")]
+        idl_files: Vec<PathBuf>,
+        /// Run every case in a fresh LiteSVM instead of reusing one per worker with accounts reset
+        /// between cases
+        #[arg(long)]
+        isolate: bool,
+        /// Number of cases to execute in parallel (defaults to the number of CPUs)
+        #[arg(long, value_name = "N")]
+        jobs: Option<NonZeroUsize>,
+        /// Print plain PASS/FAIL labels (colors are already off when stdout isn't a terminal)
+        #[arg(long)]
+        no_color: bool,
+        /// Write report.json to this path instead of target/anchor-suite/report.json; `-` prints
+        /// it to stdout and moves all other output to stderr
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// Also write a JUnit XML report to this path
+        #[arg(long, value_name = "PATH")]
+        junit: Option<PathBuf>,
+        /// After a successful run, copy report.json to this path as the new `diff` baseline
+        #[arg(long, value_name = "PATH")]
+        update_baseline: Option<PathBuf>,
+        /// Write a compact Markdown summary of the failures to this path, for a PR comment
+        #[arg(long, value_name = "PATH")]
+        summary_markdown: Option<PathBuf>,
+        /// Extra report format written next to report.json: md, html, tap, sarif (repeatable)
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            value_parser = ["md", "html", "tap", "sarif"]
+        )]
+        formats: Vec<String>,
+    },
+    /// Randomly perturb instruction data, args, account order and PDA seeds with a seeded RNG
+    Fuzz {
+        #[arg(short, long)]
+        project_dir: Option<String>,
+        /// Number of perturbed instructions to execute
+        #[arg(long, default_value_t = 100)]
+        iterations: usize,
+        /// RNG seed; the same seed draws the same perturbations (default: from the clock)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Run every instruction's base case and report which ones executed, failed or were skipped
+    Coverage {
+        #[arg(short, long)]
+        project_dir: Option<String>,
+    },
+    /// Print the cases `test` would run, with the same filters, without executing them
+    List {
+        #[arg(short, long)]
+        project_dir: Option<String>,
+        /// Print the cases as a JSON document instead of a table
+        #[arg(long)]
+        json: bool,
+        /// List cases from a hand-written JSON spec file instead of target/idl
+        #[arg(long)]
+        spec: Option<PathBuf>,
+        /// Only list cases for this instruction (repeatable)
+        #[arg(long = "instruction", value_name = "NAME")]
+        instructions: Vec<String>,
+        /// Only list cases for instructions whose name matches this regex
+        #[arg(long, value_name = "REGEX")]
+        filter: Option<String>,
+        /// Only list cases of this mutation kind, e.g. wrong_pda (repeatable)
+        #[arg(long = "mutation", value_name = "KIND")]
+        mutations: Vec<String>,
+        /// Comma-separated mutation kinds to list, e.g. wrong_pda,truncate_data
+        #[arg(long = "mutations", value_name = "LIST", value_delimiter = ',')]
+        mutation_list: Vec<String>,
+        /// Only list cases for the program with this IDL name, IDL file stem or program address
+        #[arg(long, value_name = "NAME_OR_ADDRESS")]
+        program: Option<String>,
+        /// Program id for an IDL without an address, as <name>:<pubkey> (repeatable)
+        #[arg(long = "program-id", value_name = "NAME:PUBKEY")]
+        program_ids: Vec<String>,
+        /// Pair an IDL with a .so in target/deploy, as <idl_stem>=<so_filename> (repeatable)
+        #[arg(long = "map", value_name = "IDL_STEM=SO")]
+        so_map: Vec<String>,
+        /// Read IDLs from this directory instead of <project-dir>/target/idl
+        #[arg(long, value_name = "DIR")]
+        idl_dir: Option<PathBuf>,
+        /// Read program binaries from this directory instead of <project-dir>/target/deploy
+        #[arg(long, value_name = "DIR")]
+        deploy_dir: Option<PathBuf>,
+        /// Load this IDL file instead of scanning the IDL directory (repeatable)
+        #[arg(long = "idl", value_name = "FILE")]
+        idl_files: Vec<PathBuf>,
+    },
+    /// Compare a test report against a baseline and exit non-zero on newly failing cases
+    Diff {
+        /// Known-good report.json
+        #[arg(long)]
+        baseline: PathBuf,
+        /// Report of the run to check
+        #[arg(long, default_value = "target/anchor-suite/report.json")]
+        current: PathBuf,
+        /// Print the diff as JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rebuild one case of an earlier test report from the IDL and rerun it with full logs
+    Replay {
+        #[arg(short, long)]
+        project_dir: Option<String>,
+        /// Report whose case to rerun; its project directory is used unless --project-dir is set
+        #[arg(long, default_value = "target/anchor-suite/report.json")]
+        report: PathBuf,
+        /// Case id or display name from the report
+        #[arg(long)]
+        id: String,
+    },
+    /// Diff two IDL versions and exit non-zero on breaking changes
+    CompareIdl {
+        old: PathBuf,
+        new: PathBuf,
+        /// Print the diff as JSON instead of text
+        #[arg(long)]
+        json: bool,
     },
 }
 
-// Entry point: route subcommands to the correct module.
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+// Entry point: run the subcommand and map its outcome to the documented exit code.
+fn main() {
+    // Usage errors are tool errors; clap's own exit code 2 would read as a preflight failure.
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        if !e.use_stderr() {
+            e.exit();
+        }
+        let _ = e.print();
+        process::exit(exit::TOOL_ERROR);
+    });
+    if let Err(e) = run(cli) {
+        eprintln!("Error: {:?}", e);
+        process::exit(exit::exit_code(&e));
+    }
+}
+
+// Route subcommands to the correct module.
+fn run(cli: Cli) -> Result<()> {
     match cli.command {
-        Commands::Scan { project_dir } => {
+        Commands::Scan {
+            project_dir,
+            json,
+            output,
+            idl_dir,
+            idl_files,
+            program,
+        } => {
             let dir = project_dir.unwrap_or_else(|| ".".to_string());
-            scan::scan_pdas(&dir)?;
+            scan::scan_pdas(
+                &dir,
+                idl_dir.as_deref(),
+                &idl_files,
+                program.as_deref(),
+                json,
+                output.as_deref(),
+            )?;
         }
-        Commands::Test { project_dir } => {
+        Commands::Test {
+            project_dir,
+            rules,
+            fail_on_unsupported_types,
+            offline,
+            non_empty_strings,
+            fund_writable,
+            fabricated_balance,
+            fixtures,
+            assertions,
+            debug_harness,
+            warnings_as_errors,
+            error_on,
+            spec,
+            duplicate_accounts_must_fail,
+            extra_accounts_must_fail,
+            expect_logs,
+            max_cu,
+            case_id,
+            fuzz,
+            fuzz_seed,
+            instructions,
+            filter,
+            list_instructions,
+            mut mutations,
+            mutation_list,
+            sequence,
+            fail_fast,
+            program,
+            program_ids,
+            so_map,
+            idl_dir,
+            deploy_dir,
+            idl_files,
+            isolate,
+            jobs,
+            no_color,
+            output,
+            junit,
+            update_baseline,
+            summary_markdown,
+            formats,
+        } => {
             let dir = project_dir.unwrap_or_else(|| ".".to_string());
-            runner::run_tests(&dir)?;
+            mutations.extend(mutation_list);
+            let opts = types::TestOptions {
+                rules_file: rules,
+                fail_on_unsupported_types,
+                offline,
+                non_empty_strings,
+                fund_writable,
+                fabricated_balance,
+                fixtures,
+                assertions,
+                debug_harness,
+                warnings_as_errors,
+                error_on,
+                spec_file: spec,
+                duplicate_accounts_must_fail,
+                extra_accounts_must_fail,
+                expect_logs,
+                max_cu,
+                case_id,
+                fuzz,
+                fuzz_seed,
+                instructions,
+                instruction_regex: filter,
+                list_instructions,
+                mutations,
+                sequence,
+                fail_fast,
+                program,
+                program_ids,
+                so_map,
+                idl_dir,
+                deploy_dir,
+                idl_files,
+                isolate,
+                jobs,
+                no_color,
+                output,
+                junit,
+                update_baseline,
+                markdown: formats.iter().any(|f| f == "md"),
+                html: formats.iter().any(|f| f == "html"),
+                tap: formats.iter().any(|f| f == "tap"),
+                sarif: formats.iter().any(|f| f == "sarif"),
+                summary_markdown,
+            };
+            runner::run_tests(&dir, &opts)?;
+        }
+        Commands::Fuzz {
+            project_dir,
+            iterations,
+            seed,
+        } => {
+            let dir = project_dir.unwrap_or_else(|| ".".to_string());
+            fuzz::run_fuzz(&dir, &types::FuzzOptions { iterations, seed })?;
+        }
+        Commands::Coverage { project_dir } => {
+            let dir = project_dir.unwrap_or_else(|| ".".to_string());
+            coverage::run_coverage(&dir)?;
+        }
+        Commands::List {
+            project_dir,
+            json,
+            spec,
+            instructions,
+            filter,
+            mut mutations,
+            mutation_list,
+            program,
+            program_ids,
+            so_map,
+            idl_dir,
+            deploy_dir,
+            idl_files,
+        } => {
+            let dir = project_dir.unwrap_or_else(|| ".".to_string());
+            mutations.extend(mutation_list);
+            let opts = types::TestOptions {
+                spec_file: spec,
+                instructions,
+                instruction_regex: filter,
+                mutations,
+                program,
+                program_ids,
+                so_map,
+                idl_dir,
+                deploy_dir,
+                idl_files,
+                ..Default::default()
+            };
+            list::run_list(&dir, &opts, json)?;
+        }
+        Commands::Diff {
+            baseline,
+            current,
+            json,
+        } => {
+            diff::diff_reports(&baseline, &current, json)?;
+        }
+        Commands::Replay {
+            project_dir,
+            report,
+            id,
+        } => {
+            replay::replay_from_report(&report, &id, project_dir.as_deref())?;
+        }
+        Commands::CompareIdl { old, new, json } => {
+            compare::compare_idls(&old, &new, json)?;
         }
     }
     Ok(())
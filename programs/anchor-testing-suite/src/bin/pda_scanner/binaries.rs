@@ -0,0 +1,92 @@
+use crate::types::ProgramSpec;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use solana_address::Address;
+use std::fmt::Write;
+use std::fs;
+
+// First bytes of every ELF file.
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+// Size of an ELF64 header; anything shorter can't be a loadable program.
+const MIN_ELF_LEN: usize = 64;
+
+// A program's .so, read and validated once per run.
+#[derive(Debug)]
+pub struct ProgramBinary {
+    pub program_id: Address,
+    pub idl_file: String,
+    pub bytes: Vec<u8>,
+    pub sha256: String,
+    // Why the binary can't be deployed; None when it passed validation.
+    pub invalid: Option<String>,
+}
+
+impl ProgramBinary {
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_none()
+    }
+}
+
+// Read every program's .so once. A malformed binary is recorded, not an error, so it becomes one
+// failed check instead of a failure per case.
+pub fn load_program_binaries(programs: &[ProgramSpec]) -> Result<Vec<ProgramBinary>> {
+    programs
+        .iter()
+        .map(|p| {
+            let bytes = fs::read(&p.deploy_so)
+                .with_context(|| format!("Failed to read {}", p.deploy_so.display()))?;
+            Ok(ProgramBinary {
+                program_id: p.program_id,
+                idl_file: p.idl_file.clone(),
+                sha256: sha256_hex(&bytes),
+                invalid: validate_elf(&bytes).err(),
+                bytes,
+            })
+        })
+        .collect()
+}
+
+// Cheap structural check; LiteSVM's loader does the real verification.
+fn validate_elf(bytes: &[u8]) -> std::result::Result<(), String> {
+    if bytes.len() < MIN_ELF_LEN {
+        return Err(format!(
+            "{} bytes is smaller than an ELF header ({} bytes)",
+            bytes.len(),
+            MIN_ELF_LEN
+        ));
+    }
+    if !bytes.starts_with(ELF_MAGIC) {
+        return Err("missing ELF magic bytes".to_string());
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .fold(String::with_capacity(64), |mut out, b| {
+            let _ = write!(out, "{:02x}", b);
+            out
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elf_validation_checks_size_and_magic() {
+        assert!(validate_elf(&[]).unwrap_err().contains("0 bytes"));
+        assert_eq!(
+            validate_elf(&[0u8; MIN_ELF_LEN]).unwrap_err(),
+            "missing ELF magic bytes"
+        );
+        let mut elf = vec![0u8; MIN_ELF_LEN];
+        elf[..4].copy_from_slice(ELF_MAGIC);
+        assert!(validate_elf(&elf).is_ok());
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
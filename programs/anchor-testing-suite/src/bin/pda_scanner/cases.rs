@@ -1,17 +1,36 @@
+use crate::binaries::ProgramBinary;
+use crate::classify::{custom_error_code, CaseOutcome, Classifier};
+use crate::errors::error_name;
+use crate::fuzz::hex;
+use crate::layout::{account_space, account_type_for, defined_name, field_range};
 use crate::types::{
-    EdgeCase, ExecutedCase, Expectation, InstructionSpec, Mutation, ProgramSpec, SeedSpec,
+    AccountAssertion, AccountFixtures, AccountSpec, AccountTypeSpec, ArgSpec, AuthoritySwap,
+    Boundary, BumpSwap, EdgeCase, ExecutedCase, Expectation, FabricatedField, ForeignPda,
+    FuzzPerturbation, HarnessTimings, InstructionSpec, Mutation, ProgramSpec, SeedSpec,
+    SequenceStep, SetupStep, SkipReason, StateCheck, TestOptions, UnresolvedSeed, UnsupportedArg,
 };
-use anyhow::{Context, Result};
+use anyhow::Result;
+use litesvm::types::TransactionMetadata;
 use litesvm::LiteSVM;
 use serde_json::Value;
-use solana_address::Address;
+use sha2::{Digest, Sha256};
+use solana_account::Account;
+use solana_address::{address, Address};
 use solana_instruction::{account_meta::AccountMeta, Instruction};
 use solana_keypair::Keypair;
 use solana_message::Message;
 use solana_signer::Signer;
 use solana_transaction::Transaction;
-use std::collections::HashMap;
-use std::fs;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Hash bytes in a case id (8 hex chars).
+const CASE_ID_HASH_BYTES: usize = 4;
 
 // Build a deterministic list of base + negative cases from IDL instructions.
 pub fn generate_edge_cases(programs: &[ProgramSpec]) -> Vec<EdgeCase> {
@@ -19,278 +38,3923 @@ pub fn generate_edge_cases(programs: &[ProgramSpec]) -> Vec<EdgeCase> {
 
     for p in programs {
         for ix in &p.instructions {
-            cases.push(EdgeCase {
-                id: format!("{}_{}_base", p.idl_file, ix.name),
-                idl_file: p.idl_file.clone(),
-                program_id: p.program_id,
-                instruction: ix.clone(),
-                mutation: Mutation::None,
-                expectation: Expectation::Any,
-            });
+            // Base cases are happy-path checks only when nothing about them is guessed.
+            let base = if fully_resolvable(p, ix) {
+                Expectation::MustSucceed
+            } else {
+                Expectation::Any
+            };
+            cases.push(new_case(p, ix, "base", Mutation::None, base));
+            cases.push(new_case(
+                p,
+                ix,
+                "wrong_program",
+                Mutation::WrongProgramId,
+                Expectation::MustFail,
+            ));
+            // Truncation targets arg deserialization; without args it would only clip the
+            // discriminator, which corrupt_discriminator/empty_data already cover.
+            if !ix.args.is_empty() {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    "truncate_data",
+                    Mutation::TruncateData,
+                    must_fail_with_code(INSTRUCTION_DID_NOT_DESERIALIZE),
+                ));
+            }
+            // Anchor rejects unknown discriminators (InstructionFallthrough, 101) and
+            // payloads too short to hold one (InstructionMissing, 100).
+            cases.push(new_case(
+                p,
+                ix,
+                "corrupt_discriminator",
+                Mutation::CorruptDiscriminator,
+                Expectation::MustFail,
+            ));
+            cases.push(new_case(
+                p,
+                ix,
+                "empty_data",
+                Mutation::EmptyData,
+                Expectation::MustFail,
+            ));
 
-            cases.push(EdgeCase {
-                id: format!("{}_{}_wrong_program", p.idl_file, ix.name),
-                idl_file: p.idl_file.clone(),
-                program_id: p.program_id,
-                instruction: ix.clone(),
-                mutation: Mutation::WrongProgramId,
-                expectation: Expectation::MustFail,
-            });
+            for acc in &ix.accounts {
+                if !acc.pda_seeds.is_empty() {
+                    cases.push(new_case(
+                        p,
+                        ix,
+                        &format!("wrong_pda_{}", acc.name),
+                        Mutation::WrongPda {
+                            account: acc.name.clone(),
+                        },
+                        must_fail_with_code(CONSTRAINT_SEEDS),
+                    ));
+                }
+            }
 
-            cases.push(EdgeCase {
-                id: format!("{}_{}_truncate_data", p.idl_file, ix.name),
-                idl_file: p.idl_file.clone(),
-                program_id: p.program_id,
-                instruction: ix.clone(),
-                mutation: Mutation::TruncateData,
-                expectation: Expectation::MustFail,
-            });
+            // One case per seed, so a program that checks only some of a PDA's seeds shows up
+            // against the exact seed it ignores.
+            for acc in &ix.accounts {
+                for (seed_index, seed) in acc.pda_seeds.iter().enumerate() {
+                    cases.push(new_case(
+                        p,
+                        ix,
+                        &format!("corrupt_seed_{}_{}_{}", acc.name, seed_index, seed.kind()),
+                        Mutation::CorruptSeed {
+                            account: acc.name.clone(),
+                            seed_index,
+                        },
+                        must_fail_with_code(CONSTRAINT_SEEDS),
+                    ));
+                }
+            }
+
+            // The right seeds with a lower bump still give a valid address, one only a program
+            // that takes the bump from its caller (instead of the canonical one) would accept.
+            for acc in ix.accounts.iter().filter(|a| !a.pda_seeds.is_empty()) {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("non_canonical_bump_{}", acc.name),
+                    Mutation::NonCanonicalBump {
+                        account: acc.name.clone(),
+                    },
+                    must_fail_with_code(CONSTRAINT_SEEDS),
+                ));
+            }
+
+            // Account confusion: a real PDA of the program, derived for another user. The
+            // signers stay the same, so only the seeds (or a has_one check) can reject it.
+            for acc in ix.accounts.iter().filter(|a| signer_seeded(ix, a)) {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("foreign_pda_{}", acc.name),
+                    Mutation::ForeignPda {
+                        account: acc.name.clone(),
+                    },
+                    Expectation::MustFail,
+                ));
+            }
+
+            // Anchor's `init` creates the PDA through the system program, which refuses an address
+            // already in use, so running an initializer twice must fail instead of resetting
+            // the account.
+            if is_initializer(&ix.name)
+                && ix.accounts.iter().any(|a| a.signer && a.writable)
+                && ix.accounts.iter().any(|a| a.writable && !a.pda_seeds.is_empty())
+            {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    "double_init",
+                    Mutation::DoubleInit,
+                    Expectation::MustFailWith {
+                        anchor_code: None,
+                        contains: Some(ACCOUNT_ALREADY_IN_USE.to_string()),
+                    },
+                ));
+            }
+
+            // A PDA an initializer creates must exist before anything else operates on it: the
+            // right address with nothing behind it has to be rejected, not treated as empty state.
+            for acc in ix.accounts.iter().filter(|a| initialized_pda(p, ix, a)) {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("uninitialized_{}", acc.name),
+                    Mutation::Uninitialized {
+                        account: acc.name.clone(),
+                    },
+                    Expectation::MustFail,
+                ));
+            }
+
+            // Undeclared trailing account (readonly, non-signer). Anchor ignores accounts past
+            // the declared ones unless the program reads `remaining_accounts`, so any outcome
+            // passes; the outcome is compared against the base case instead
+            // (`--extra-accounts-must-fail` makes it strict).
+            cases.push(new_case(
+                p,
+                ix,
+                "extra_account",
+                Mutation::ExtraAccount,
+                Expectation::Any,
+            ));
+
+            // Base encodes options as None; this case flips them all to Some.
+            if ix.args.iter().any(|a| has_option(&a.ty)) {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    "option_some",
+                    Mutation::OptionSome,
+                    Expectation::Any,
+                ));
+            }
+
+            // Integer args at their maximum push unchecked arithmetic (`balance += amount`) past
+            // overflow. Release builds wrap silently, so either outcome is informative.
+            if ix.args.iter().any(|a| {
+                let max = encode_arg_max(&a.ty, &p.types);
+                max.is_ok() && max != encode_arg_zero(&a.ty, &p.types)
+            }) {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    "max_args",
+                    Mutation::MaxArgs,
+                    Expectation::Any,
+                ));
+            }
+
+            // One integer arg at a time at 1, its maximum and (signed) minimum, the rest at zero,
+            // so e.g. `deposit(u64::MAX)` reaches unchecked `balance += amount` without the
+            // combinations multiplying.
+            for arg in ix.args.iter().filter(|a| int_layout(&a.ty).is_some()) {
+                let signed = int_layout(&arg.ty).is_some_and(|(_, signed)| signed);
+                let values: &[Boundary] = if signed {
+                    &[Boundary::One, Boundary::Max, Boundary::Min]
+                } else {
+                    &[Boundary::One, Boundary::Max]
+                };
+                for &value in values {
+                    cases.push(new_case(
+                        p,
+                        ix,
+                        &format!("boundary_{}_{}", arg.name, value.label()),
+                        Mutation::Boundary {
+                            arg: arg.name.clone(),
+                            value,
+                        },
+                        Expectation::Any,
+                    ));
+                }
+            }
+
+            // Programs that declare their own errors usually guard amounts with them
+            // (InsufficientFunds, LimitExceeded, ...); run after the setup chain, a maximal
+            // amount is the cheapest way to reach those. Whether it must fail is
+            // program-specific, so the declared-error coverage in the report is the signal,
+            // except for withdrawals, which must hit the program's insufficient-funds error.
+            if !p.errors.is_empty() && !is_initializer(&ix.name) && ix.args.iter().any(is_amount_arg) {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    "huge_amount",
+                    Mutation::HugeAmount,
+                    insufficient_funds_error(p, ix)
+                        .map_or(Expectation::Any, must_fail_with_code),
+                ));
+            }
 
+            for (index, acc) in ix.accounts.iter().enumerate() {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("remove_account_{}", acc.name),
+                    Mutation::RemoveAccount {
+                        index,
+                        account: acc.name.clone(),
+                    },
+                    Expectation::MustFail,
+                ));
+            }
+
+            // A declared `mut` account sent readonly must be rejected, by Anchor's mut constraint
+            // or by the runtime once the program writes to it. The reverse, a readonly account
+            // sent writable, is harmless for a well-behaved program, so it is only compared
+            // against the base case.
             for acc in &ix.accounts {
-                if !acc.pda_seeds.is_empty() {
-                    cases.push(EdgeCase {
-                        id: format!("{}_{}_wrong_pda_{}", p.idl_file, ix.name, acc.name),
-                        idl_file: p.idl_file.clone(),
-                        program_id: p.program_id,
-                        instruction: ix.clone(),
-                        mutation: Mutation::WrongPda {
+                let (suffix, mutation, expectation) = if acc.writable {
+                    let mutation = Mutation::NotWritable {
+                        account: acc.name.clone(),
+                    };
+                    ("not_writable", mutation, Expectation::MustFail)
+                } else {
+                    let mutation = Mutation::UnexpectedWritable {
+                        account: acc.name.clone(),
+                    };
+                    ("unexpected_writable", mutation, Expectation::Any)
+                };
+                let suffix = format!("{}_{}", suffix, acc.name);
+                cases.push(new_case(p, ix, &suffix, mutation, expectation));
+            }
+
+            // Every declared signer must actually be enforced by the program, and only the
+            // expected key may sign (e.g. a stranger withdrawing from someone else's vault).
+            for acc in ix.accounts.iter().filter(|a| a.signer) {
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("missing_signer_{}", acc.name),
+                    Mutation::MissingSigner {
+                        account: acc.name.clone(),
+                    },
+                    must_fail_with_code(ACCOUNT_NOT_SIGNER),
+                ));
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("wrong_signer_{}", acc.name),
+                    Mutation::WrongSigner {
+                        account: acc.name.clone(),
+                    },
+                    Expectation::MustFail,
+                ));
+            }
+
+            // A signer that owns existing state (it seeds a PDA, or is named like an owner) is
+            // swapped for a stranger while the PDAs keep pointing at the owner's accounts, so
+            // the program's authority checks (has_one, seeds, require!) are all that stand in
+            // the way.
+            if !is_initializer(&ix.name) {
+                for acc in ix.accounts.iter().filter(|a| authority_signer(ix, a)) {
+                    cases.push(new_case(
+                        p,
+                        ix,
+                        &format!("wrong_authority_{}", acc.name),
+                        Mutation::WrongAuthority {
                             account: acc.name.clone(),
                         },
-                        expectation: Expectation::MustFail,
-                    });
+                        Expectation::MustFail,
+                    ));
+                }
+            }
+
+            // Same key passed for two writable accounts. Only non-signers are overwritten so the
+            // transaction never needs a signature for a key we hold no keypair for. Whether this
+            // must fail is program-specific (`--duplicate-accounts-must-fail` makes it strict).
+            for acc in ix.accounts.iter().filter(|a| a.writable && !a.signer) {
+                for source in ix.accounts.iter().filter(|s| s.writable && s.name != acc.name) {
+                    cases.push(new_case(
+                        p,
+                        ix,
+                        &format!("duplicate_{}_as_{}", acc.name, source.name),
+                        Mutation::DuplicateAccount {
+                            account: acc.name.clone(),
+                            source: source.name.clone(),
+                        },
+                        Expectation::Any,
+                    ));
                 }
             }
+
+            // An account of a known IDL type is deserialized by Anchor, which must reject a
+            // foreign owner. Any other non-signer may be a raw AccountInfo the program never
+            // reads (e.g. one that only seeds a PDA), so its owner is the program's business and
+            // any outcome passes. Programs and sysvars at fixed addresses are left alone:
+            // replacing the real account there tests nothing.
+            for acc in ix.accounts.iter().filter(|a| !a.signer && known_address(a).is_none()) {
+                let expectation = if account_type_for(p, &acc.name).is_some() {
+                    Expectation::MustFail
+                } else {
+                    Expectation::Any
+                };
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("wrong_owner_{}", acc.name),
+                    Mutation::WrongOwner {
+                        account: acc.name.clone(),
+                    },
+                    expectation,
+                ));
+            }
         }
     }
 
     cases
 }
 
-// Execute all cases in LiteSVM and return the per-case outcomes.
-pub fn execute_edge_cases(programs: &[ProgramSpec], cases: &[EdgeCase]) -> Result<Vec<ExecutedCase>> {
-    // Load program bytes once to avoid repeated I/O per case.
-    let mut program_bytes = HashMap::new();
-    for p in programs {
-        let bytes = fs::read(&p.deploy_so)
-            .with_context(|| format!("Failed to read {}", p.deploy_so.display()))?;
-        program_bytes.insert(p.program_id, bytes);
+// Anchor error codes that mutations are expected to trip.
+const INSTRUCTION_DID_NOT_DESERIALIZE: u32 = 102;
+const CONSTRAINT_SEEDS: u32 = 2006;
+const ACCOUNT_NOT_SIGNER: u32 = 3010;
+// Logged by the system program when `init` targets an account that already exists.
+const ACCOUNT_ALREADY_IN_USE: &str = "already in use";
+
+// A MustFail that only passes when the program rejected the case with `code`, so failures
+// unrelated to the mutation (e.g. a missing account) don't count.
+fn must_fail_with_code(code: u32) -> Expectation {
+    Expectation::MustFailWith {
+        anchor_code: Some(code),
+        contains: None,
     }
+}
 
-    let mut out = Vec::with_capacity(cases.len());
-    for case in cases {
-        let bytes = match program_bytes.get(&case.program_id) {
-            Some(v) => v,
-            None => continue,
-        };
-        let run = run_case(bytes, case);
-        let (actual_success, error) = match run {
-            Ok(()) => (true, None),
-            Err(e) => (false, Some(e)),
-        };
-
-        let (expected_success, passed) = match case.expectation {
-            Expectation::Any => (None, true),
-            Expectation::MustFail => (Some(false), !actual_success),
-        };
-
-        out.push(ExecutedCase {
-            id: case.id.clone(),
-            idl_file: case.idl_file.clone(),
-            instruction: case.instruction.name.clone(),
-            mutation: match &case.mutation {
-                Mutation::None => "none".to_string(),
-                Mutation::WrongProgramId => "wrong_program_id".to_string(),
-                Mutation::TruncateData => "truncate_data".to_string(),
-                Mutation::WrongPda { account } => format!("wrong_pda:{}", account),
-            },
-            expected_success,
-            actual_success,
-            passed,
-            error,
-        });
+// Instruction name fragments for handlers that move funds out of program accounts.
+const DEBIT_IX_HINTS: &[&str] = &["withdraw", "redeem", "claim"];
+
+// Code of the program's insufficient-funds error when `ix` debits program funds; a maximal
+// amount can't be covered by a freshly initialized account, so that error must be raised.
+fn insufficient_funds_error(p: &ProgramSpec, ix: &InstructionSpec) -> Option<u32> {
+    if !DEBIT_IX_HINTS.iter().any(|h| ix.name.contains(h)) {
+        return None;
     }
+    p.errors
+        .iter()
+        .find(|e| e.name.to_ascii_lowercase().contains("insufficient"))
+        .map(|e| e.code)
+}
 
-    Ok(out)
+// Whether one of `acc`'s seeds is the key of a signer of `ix` (`[b"vault", user]`).
+fn signer_seeded(ix: &InstructionSpec, acc: &AccountSpec) -> bool {
+    acc.pda_seeds.iter().any(|seed| {
+        matches!(seed, SeedSpec::Account(path)
+            if ix.accounts.iter().any(|a| a.signer && a.name == *path))
+    })
 }
 
-// Run a single case: deploy program, construct instruction, and submit a transaction.
-fn run_case(program_bytes: &[u8], case: &EdgeCase) -> std::result::Result<(), String> {
-    let mut svm = LiteSVM::new();
-    svm.add_program(case.program_id, program_bytes)
-        .map_err(|e| format!("add_program failed: {e:?}"))?;
+// Whether `acc` is a signer of `ix` that owns state: some PDA of `ix` is seeded by it, or its
+// name is owner-like (see OWNER_FIELDS).
+fn authority_signer(ix: &InstructionSpec, acc: &AccountSpec) -> bool {
+    acc.signer
+        && (OWNER_FIELDS.contains(&acc.name.as_str())
+            || ix.accounts.iter().any(|a| {
+                a.pda_seeds
+                    .iter()
+                    .any(|seed| matches!(seed, SeedSpec::Account(path) if *path == acc.name))
+            }))
+}
 
-    let payer = Keypair::new();
-    svm.airdrop(&payer.pubkey(), 10_000_000_000)
-        .map_err(|e| format!("airdrop failed: {e:?}"))?;
+// Case for one instruction; ids are `<instruction>_<suffix>_<hash>` (see `case_id`), and the IDL
+// file only appears in `display_name` (see `EdgeCase`).
+fn new_case(
+    p: &ProgramSpec,
+    ix: &InstructionSpec,
+    suffix: &str,
+    mutation: Mutation,
+    expectation: Expectation,
+) -> EdgeCase {
+    EdgeCase {
+        id: case_id(&p.program_id, &ix.name, suffix),
+        display_name: format!("{}_{}_{}", p.idl_file, ix.name, suffix),
+        idl_file: p.idl_file.clone(),
+        program_id: p.program_id,
+        instruction: ix.clone(),
+        mutation,
+        expectation,
+        setup_chain: setup_chain_for(p, ix),
+    }
+}
 
-    let (account_metas, signer_keys) = build_accounts(case, &payer)?;
-    let mut data = encode_instruction_data(&case.instruction)?;
+// Case suffixes name the mutation kind and its target (account, arg) rather than a position, so
+// hashing them with the program id and instruction gives an id that survives renaming the IDL
+// file and reordering instructions or accounts.
+pub fn case_id(program_id: &Address, instruction: &str, suffix: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(program_id.as_ref());
+    hasher.update([0]);
+    hasher.update(instruction.as_bytes());
+    hasher.update([0]);
+    hasher.update(suffix.as_bytes());
+    let digest = hasher.finalize();
+    format!("{}_{}_{}", instruction, suffix, hex(&digest[..CASE_ID_HASH_BYTES]))
+}
 
-    // Mutations adjust the base case to force failure scenarios.
-    if matches!(case.mutation, Mutation::TruncateData) && !data.is_empty() {
-        data.pop();
+// Initializers can't rely on prior state; every other instruction first runs the program's
+// initializers. IDLs don't mark `init` accounts, so initializers are recognized by name.
+fn setup_chain_for(p: &ProgramSpec, ix: &InstructionSpec) -> Vec<String> {
+    if is_initializer(&ix.name) {
+        return Vec::new();
     }
+    p.instructions
+        .iter()
+        .filter(|i| is_initializer(&i.name))
+        .map(|i| i.name.clone())
+        .collect()
+}
 
-    let program_id = match case.mutation {
-        Mutation::WrongProgramId => Address::from(Keypair::new().pubkey().to_bytes()),
-        _ => case.program_id,
-    };
+fn is_initializer(name: &str) -> bool {
+    name == "init" || name.starts_with("init_") || name.starts_with("initialize")
+}
 
-    let ix = Instruction {
-        program_id,
-        accounts: account_metas,
-        data,
-    };
+// Whether `acc` of the non-initializer `ix` is a PDA that one of the program's initializers
+// declares too, i.e. state the setup chain creates before the case runs.
+fn initialized_pda(p: &ProgramSpec, ix: &InstructionSpec, acc: &AccountSpec) -> bool {
+    !is_initializer(&ix.name)
+        && !acc.pda_seeds.is_empty()
+        && p.instructions
+            .iter()
+            .filter(|i| is_initializer(&i.name))
+            .any(|i| i.accounts.iter().any(|a| a.name == acc.name && !a.pda_seeds.is_empty()))
+}
 
-    send_ix(&mut svm, &payer, &signer_keys, ix)
+// Whether an unmutated run of `ix` uses only real inputs; see `unresolved_input`.
+fn fully_resolvable(p: &ProgramSpec, ix: &InstructionSpec) -> bool {
+    unresolved_input(p, ix).is_none()
 }
 
-// Resolve account metas and signer keypairs, including PDA derivation when possible.
-fn build_accounts(
-    case: &EdgeCase,
-    payer: &Keypair,
-) -> std::result::Result<(Vec<AccountMeta>, Vec<Keypair>), String> {
-    let mut signer_by_name: HashMap<String, Keypair> = HashMap::new();
-    let mut pubkey_by_name: HashMap<String, Address> = HashMap::new();
+// First input an unmutated run of `ix` would have to fabricate, or `None` when it uses only
+// real ones: encodable args, and every account a signer, a known address, or a PDA derivable
+// before execution. Non-initializers touching PDAs also need an initializer chain that is
+// itself fully resolvable.
+pub fn unresolved_input(p: &ProgramSpec, ix: &InstructionSpec) -> Option<String> {
+    if let Some(arg) = ix
+        .args
+        .iter()
+        .find(|a| encode_arg_zero(&a.ty, &p.types).is_err())
+    {
+        return Some(format!("arg `{}` is not encodable", arg.name));
+    }
+    let seed_ok = |seed: &SeedSpec| match seed {
+        SeedSpec::Const(_) => true,
+        // Dotted paths read on-chain data, which may not exist yet.
+        SeedSpec::Account(path) => ix.accounts.iter().any(|a| a.name == *path),
+        SeedSpec::Arg(path) => ix.args.iter().any(|a| a.name == *path),
+    };
+    if let Some(acc) = ix.accounts.iter().find(|acc| {
+        !(acc.signer
+            || known_address(acc).is_some()
+            || (!acc.pda_seeds.is_empty() && acc.pda_seeds.iter().all(seed_ok)))
+    }) {
+        return Some(format!("account `{}` is not derivable", acc.name));
+    }
 
-    // First pass: assign pubkeys for signer/non-signer accounts.
-    for acc in &case.instruction.accounts {
-        if acc.signer {
-            if pubkey_by_name.is_empty() {
-                pubkey_by_name.insert(acc.name.clone(), payer.pubkey());
-            } else {
-                let kp = Keypair::new();
-                pubkey_by_name.insert(acc.name.clone(), kp.pubkey());
-                signer_by_name.insert(acc.name.clone(), kp);
+    let chain = setup_chain_for(p, ix);
+    let needs_state = ix.accounts.iter().any(|a| !a.pda_seeds.is_empty());
+    if needs_state && !is_initializer(&ix.name) && chain.is_empty() {
+        return Some("no initializer creates its PDAs".to_string());
+    }
+    chain.iter().find_map(|name| {
+        let resolvable = p
+            .instructions
+            .iter()
+            .find(|i| i.name == *name)
+            .is_some_and(|i| fully_resolvable(p, i));
+        (!resolvable).then(|| format!("setup `{}` is not resolvable", name))
+    })
+}
+
+// List every instruction argument whose IDL type the encoder cannot handle.
+pub fn find_unsupported_args(programs: &[ProgramSpec]) -> Vec<UnsupportedArg> {
+    let mut out = Vec::new();
+    for p in programs {
+        for ix in &p.instructions {
+            for arg in &ix.args {
+                if let Err(reason) = encode_arg_zero(&arg.ty, &p.types) {
+                    out.push(UnsupportedArg {
+                        idl_file: p.idl_file.clone(),
+                        instruction: ix.name.clone(),
+                        field: arg.name.clone(),
+                        ty: arg.ty.to_string(),
+                        reason: reason.to_string(),
+                    });
+                }
             }
-        } else {
-            pubkey_by_name.insert(acc.name.clone(), Keypair::new().pubkey());
         }
     }
+    out
+}
 
-    // Second pass: derive PDA pubkeys for accounts that define seed recipes.
-    for acc in &case.instruction.accounts {
-        if acc.pda_seeds.is_empty() {
-            continue;
-        }
+// Programs paired with their .so bytes. Binaries were read and validated once upfront; invalid
+// ones are never deployed.
+pub fn deployed_programs<'a>(
+    programs: &'a [ProgramSpec],
+    binaries: &'a [ProgramBinary],
+) -> Vec<(&'a ProgramSpec, &'a [u8])> {
+    programs
+        .iter()
+        .filter_map(|p| {
+            let bin = binaries
+                .iter()
+                .find(|b| b.program_id == p.program_id && b.is_valid())?;
+            Some((p, bin.bytes.as_slice()))
+        })
+        .collect()
+}
 
-        let mut seeds: Vec<Vec<u8>> = Vec::new();
-        let mut resolvable = true;
-        for seed in &acc.pda_seeds {
-            match seed {
-                SeedSpec::Const(bytes) => seeds.push(bytes.clone()),
-                SeedSpec::Account(path) => match pubkey_by_name.get(path) {
-                    Some(pk) => seeds.push(pk.to_bytes().to_vec()),
-                    None => {
-                        resolvable = false;
+// Execute all cases in LiteSVM and return the per-case outcomes, plus why each case without
+// an outcome was skipped, keyed by case id.
+pub fn execute_edge_cases(
+    programs: &[ProgramSpec],
+    binaries: &[ProgramBinary],
+    cases: &[EdgeCase],
+    classifier: &Classifier,
+    opts: &TestOptions,
+    harness: Harness,
+) -> (Vec<ExecutedCase>, HashMap<String, SkipReason>) {
+    let deployed = deployed_programs(programs, binaries);
+    let workers = opts
+        .jobs
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+        .min(cases.len().max(1));
+
+    // Workers claim case indices from a shared counter; results land in their case's slot so
+    // report order always matches generation order.
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let slots: Vec<Mutex<Option<std::result::Result<ExecutedCase, SkipReason>>>> =
+        cases.iter().map(|_| Mutex::new(None)).collect();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                // Without --isolate each worker keeps its LiteSVM instances between cases.
+                let mut pool = (!opts.isolate).then(SvmPool::default);
+                loop {
+                    // A claimed index always runs, so every case before a fail-fast stop has a
+                    // result.
+                    if stop.load(Ordering::Relaxed) {
                         break;
                     }
-                },
-            }
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= cases.len() {
+                        break;
+                    }
+                    let executed =
+                        execute_case(&deployed, &cases[i], classifier, opts, harness, pool.as_mut());
+                    if opts.fail_fast && executed.as_ref().is_ok_and(|e| !e.passed) {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    *slots[i].lock().unwrap() = Some(executed);
+                }
+            });
         }
+    });
 
-        if !resolvable {
-            continue;
+    let mut out = Vec::with_capacity(cases.len());
+    let mut skipped = HashMap::new();
+    let mut stopped = false;
+    for (case, slot) in cases.iter().zip(slots) {
+        // Cases after the first unexpected result count as skipped even if a worker already
+        // ran them, so fail-fast output doesn't depend on scheduling. A case no worker claimed
+        // was cut off by the same stop.
+        let result = match slot.into_inner().unwrap() {
+            Some(result) if !stopped => result,
+            _ => Err(SkipReason::FailFast),
+        };
+        match result {
+            Ok(executed) => {
+                stopped = opts.fail_fast && !executed.passed;
+                out.push(executed);
+            }
+            Err(reason) => {
+                skipped.insert(case.id.clone(), reason);
+            }
         }
+    }
+
+    pair_with_base_cases(cases, &mut out);
+    (out, skipped)
+}
 
-        let seed_slices = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
-        let program_pubkey =
-            anchor_lang::prelude::Pubkey::new_from_array(case.program_id.to_bytes());
-        let (pda, _) =
-            anchor_lang::prelude::Pubkey::find_program_address(&seed_slices, &program_pubkey);
-        pubkey_by_name.insert(acc.name.clone(), Address::from(pda.to_bytes()));
+// Run and classify one case, or say why it was skipped.
+fn execute_case(
+    deployed: &[(&ProgramSpec, &[u8])],
+    case: &EdgeCase,
+    classifier: &Classifier,
+    opts: &TestOptions,
+    harness: Harness,
+    pool: Option<&mut SvmPool>,
+) -> std::result::Result<ExecutedCase, SkipReason> {
+    let (program, _) = deployed
+        .iter()
+        .find(|(p, _)| p.program_id == case.program_id)
+        .ok_or(SkipReason::ProgramBinaryInvalid)?;
+    // Phase timings are only collected (and `Instant` only touched) under --debug-harness.
+    let mut timings = opts.debug_harness.then(HarnessTimings::default);
+    let mut trace = CaseTrace::default();
+    let run = match pool {
+        Some(pool) => pool.run(deployed, program, case, harness, timings.as_mut(), &mut trace),
+        None => run_isolated(deployed, program, case, harness, timings.as_mut(), &mut trace),
+    };
+    // Nothing to test without a non-canonical address; the report lists the case as skipped.
+    if trace.bump_exhausted {
+        return Err(SkipReason::NoNonCanonicalBump);
     }
+    let (actual_success, error) = match run {
+        Ok(()) => (true, None),
+        Err(e) => (false, Some(e)),
+    };
+    let error_code = error.as_deref().and_then(custom_error_code);
 
-    // For wrong-PDA mutations, override the derived PDA with a random pubkey.
-    if let Mutation::WrongPda { account } = &case.mutation {
-        if pubkey_by_name.contains_key(account) {
-            pubkey_by_name.insert(account.clone(), Keypair::new().pubkey());
+    let (expected_error_code, expected_error_contains) = match &case.expectation {
+        Expectation::MustFailWith {
+            anchor_code,
+            contains,
+        } => (*anchor_code, contains.clone()),
+        _ => (None, None),
+    };
+    // Checked against the full transaction logs, not the capped copy kept for the report.
+    let expected_log = match &case.expectation {
+        Expectation::LogContains { substring } => Some(substring.clone()),
+        _ => None,
+    };
+    let log_matched = expected_log.as_deref().map(|needle| {
+        trace
+            .tx
+            .as_ref()
+            .is_some_and(|m| m.logs.iter().any(|line| line.contains(needle)))
+    });
+    let state_matched = matches!(case.expectation, Expectation::AccountState { .. })
+        .then(|| trace.state.iter().all(StateCheck::matched));
+
+    // Verdict comes from the classifier so user rules can override built-in expectations.
+    let mutation = case.mutation.label();
+    let (passed, rule) = classifier.classify(&CaseOutcome {
+        case,
+        mutation: &mutation,
+        actual_success,
+        error: error.as_deref(),
+        log_matched,
+        state_matched,
+    });
+
+    Ok(ExecutedCase {
+        id: case.id.clone(),
+        display_name: case.display_name.clone(),
+        idl_file: case.idl_file.clone(),
+        instruction: case.instruction.name.clone(),
+        mutation,
+        expected_success: case.expectation.expected_success(),
+        actual_success,
+        passed,
+        rule,
+        error,
+        timings,
+        base_case_id: None,
+        same_as_base: None,
+        // The second call only runs after a successful first one, so success means reinit.
+        reinitialization_allowed: matches!(case.mutation, Mutation::DoubleInit)
+            .then_some(actual_success),
+        unresolved_seeds: trace.unresolved_seeds,
+        loaded_programs: trace.loaded_programs,
+        setup: trace.setup,
+        logs: trace.tx.as_ref().map(|m| cap_logs(&m.logs)).unwrap_or_default(),
+        compute_units: trace.tx.as_ref().map(|m| m.compute_units_consumed),
+        expected_error_code,
+        expected_error_contains,
+        error_code,
+        error_name: error_code.and_then(|code| error_name(code, program)),
+        expected_log,
+        log_matched,
+        state_checks: trace.state,
+        state_matched,
+        fabricated_accounts: trace.fabricated,
+        fabricated_fields: trace.fabricated_fields,
+        foreign_pda: trace.foreign_pda,
+        non_canonical_bump: trace.non_canonical_bump,
+        wrong_authority: trace.wrong_authority,
+        arg_values: integer_arg_values(case),
+    })
+}
+
+// Link extra-account and unexpected-writable results to the base case of the same instruction
+// and note whether the outcome (success + custom error code) changed.
+fn pair_with_base_cases(cases: &[EdgeCase], executed: &mut [ExecutedCase]) {
+    let base_ids: HashMap<(String, String), String> = cases
+        .iter()
+        .filter(|c| matches!(c.mutation, Mutation::None))
+        .map(|c| ((c.idl_file.clone(), c.instruction.name.clone()), c.id.clone()))
+        .collect();
+    let outcome_by_id: HashMap<String, (bool, Option<u32>)> = executed
+        .iter()
+        .map(|e| {
+            let code = e.error.as_deref().and_then(custom_error_code);
+            (e.id.clone(), (e.actual_success, code))
+        })
+        .collect();
+
+    let case_by_id: HashMap<&str, &EdgeCase> = cases.iter().map(|c| (c.id.as_str(), c)).collect();
+    for exec in executed.iter_mut() {
+        let case = match case_by_id.get(exec.id.as_str()) {
+            Some(c)
+                if matches!(
+                    c.mutation,
+                    Mutation::ExtraAccount | Mutation::UnexpectedWritable { .. }
+                ) =>
+            {
+                *c
+            }
+            _ => continue,
+        };
+        let key = (case.idl_file.clone(), case.instruction.name.clone());
+        if let Some(base_id) = base_ids.get(&key) {
+            let mine = outcome_by_id.get(&exec.id);
+            exec.same_as_base = outcome_by_id.get(base_id).map(|base| Some(base) == mine);
+            exec.base_case_id = Some(base_id.clone());
         }
     }
+}
 
-    let mut metas = Vec::new();
-    let mut extra_signers = Vec::new();
+// What a case did besides its verdict, recorded even when it fails early.
+#[derive(Default)]
+struct CaseTrace {
+    unresolved_seeds: Vec<UnresolvedSeed>,
+    loaded_programs: Vec<String>,
+    setup: Vec<SetupStep>,
+    // Metadata of the case's own transaction, if it was sent.
+    tx: Option<TransactionMetadata>,
+    // The case's own instruction as sent, with IDL account names.
+    sent: Option<SentInstruction>,
+    // Every account key the setup chain and the case used; reset when the SVM is reused.
+    touched: Vec<Address>,
+    // AccountState assertions with what the case left behind.
+    state: Vec<StateCheck>,
+    // PDAs given a fabricated account because nothing had created them before the case.
+    fabricated: Vec<String>,
+    // Fields of those PDAs filled from the IDL layout.
+    fabricated_fields: Vec<FabricatedField>,
+    foreign_pda: Option<ForeignPda>,
+    non_canonical_bump: Option<BumpSwap>,
+    // A non_canonical_bump case whose seeds have no valid bump below the canonical one; it is
+    // skipped instead of reported.
+    bump_exhausted: bool,
+    wrong_authority: Option<AuthoritySwap>,
+}
 
-    for acc in &case.instruction.accounts {
-        let key = pubkey_by_name
-            .get(&acc.name)
-            .copied()
-            .unwrap_or_else(|| Keypair::new().pubkey());
+// Exact instruction a case sent, enough to replay it.
+#[derive(Debug, Clone)]
+pub struct SentInstruction {
+    pub program_id: Address,
+    pub data: Vec<u8>,
+    pub accounts: Vec<(String, AccountMeta)>,
+}
 
-        let meta = if acc.writable {
-            AccountMeta::new(key, acc.signer)
-        } else {
-            AccountMeta::new_readonly(key, acc.signer)
-        };
-        metas.push(meta);
+// Outcome of one case run outside the parallel executor (`fuzz` iterations, `--case-id`).
+#[derive(Debug)]
+pub struct CaseRun {
+    pub error: Option<String>,
+    // Full, uncapped program logs of the case's transaction.
+    pub logs: Vec<String>,
+    pub compute_units: Option<u64>,
+    pub setup: Vec<SetupStep>,
+    pub sent: Option<SentInstruction>,
+    pub state_checks: Vec<StateCheck>,
+    pub fabricated: Vec<String>,
+    pub fabricated_fields: Vec<FabricatedField>,
+}
 
-        if let Some(kp) = signer_by_name.remove(&acc.name) {
-            extra_signers.push(kp);
+// `test --fuzz`: `per_instruction` random-args cases for every instruction with args. Each case
+// draws from its own seed (taken from `seed`), recorded in the mutation so it can be replayed.
+// Instructions with an arg type the random encoder can't produce get none.
+pub fn random_arg_cases(programs: &[ProgramSpec], per_instruction: usize, seed: u64) -> Vec<EdgeCase> {
+    let mut master = Rng(seed);
+    let mut cases = Vec::new();
+    for p in programs {
+        for ix in p.instructions.iter().filter(|ix| !ix.args.is_empty()) {
+            for i in 0..per_instruction {
+                let case_seed = master.next_u64();
+                let Ok(args) = random_args(ix, &p.types, &mut Rng(case_seed)) else {
+                    break;
+                };
+                cases.push(new_case(
+                    p,
+                    ix,
+                    &format!("random_args_{}", i),
+                    Mutation::RandomArgs {
+                        seed: case_seed,
+                        args,
+                    },
+                    Expectation::Any,
+                ));
+            }
         }
     }
+    cases
+}
+
+// Build a fuzz case for `ix`; ids are `<instruction>_fuzz_<iteration>_<hash>` (see `new_case`).
+pub fn fuzz_case(
+    p: &ProgramSpec,
+    ix: &InstructionSpec,
+    iteration: usize,
+    perturbation: FuzzPerturbation,
+    expectation: Expectation,
+) -> EdgeCase {
+    let suffix = format!("fuzz_{}", iteration);
+    new_case(p, ix, &suffix, Mutation::Fuzz(perturbation), expectation)
+}
 
-    Ok((metas, extra_signers))
+// Discriminator + zero-valued args, the payload fuzz perturbations start from.
+pub fn base_instruction_data(
+    p: &ProgramSpec,
+    ix: &InstructionSpec,
+) -> std::result::Result<Vec<u8>, String> {
+    let args = encode_args(ix, &p.types, ArgStyle::default())?;
+    Ok([ix.discriminator.clone(), args.concat()].concat())
 }
 
-// Encode discriminator + zero-value args to produce a minimal valid payload shape.
-fn encode_instruction_data(ix: &InstructionSpec) -> std::result::Result<Vec<u8>, String> {
-    let mut out = ix.discriminator.clone();
-    for arg in &ix.args {
-        let bytes = encode_arg_zero(&arg.ty)
-            .map_err(|e| format!("arg {} type not supported: {}", arg.name, e))?;
-        out.extend(bytes);
+// Run one fuzz case through the same pipeline as `test` cases.
+pub fn run_single_case(
+    deployed: &[(&ProgramSpec, &[u8])],
+    case: &EdgeCase,
+    harness: Harness,
+) -> CaseRun {
+    let mut trace = CaseTrace::default();
+    let result = match deployed.iter().find(|(p, _)| p.program_id == case.program_id) {
+        Some((program, _)) => run_isolated(deployed, program, case, harness, None, &mut trace),
+        None => Err("program under test has no deployed binary".to_string()),
+    };
+    CaseRun {
+        error: result.err(),
+        compute_units: trace.tx.as_ref().map(|m| m.compute_units_consumed),
+        logs: trace.tx.map(|m| m.logs).unwrap_or_default(),
+        setup: trace.setup,
+        sent: trace.sent,
+        state_checks: trace.state,
+        fabricated: trace.fabricated,
+        fabricated_fields: trace.fabricated_fields,
     }
-    Ok(out)
 }
 
-// Produce a zero-value encoding for supported IDL primitive/array types.
-fn encode_arg_zero(ty: &Value) -> std::result::Result<Vec<u8>, &'static str> {
-    if let Some(s) = ty.as_str() {
-        return match s {
-            "bool" => Ok(vec![0]),
-            "u8" | "i8" => Ok(vec![0]),
-            "u16" | "i16" => Ok(vec![0; 2]),
-            "u32" | "i32" => Ok(vec![0; 4]),
-            "u64" | "i64" => Ok(vec![0; 8]),
-            "u128" | "i128" => Ok(vec![0; 16]),
-            "pubkey" => Ok(vec![0; 32]),
-            _ => Err("primitive not supported"),
+// `--sequence`: run the named instructions unmutated, in order, on one LiteSVM with one payer
+// and keyring, so every step sees the accounts earlier steps created (PDAs seeded by a signer
+// derive to the same address). Stops after the first failed step; Err when the sequence can't
+// start or names an instruction without a deployed program.
+pub fn run_sequence(
+    deployed: &[(&ProgramSpec, &[u8])],
+    names: &[String],
+    harness: Harness,
+) -> std::result::Result<Vec<SequenceStep>, String> {
+    let resolve = |name: &str| {
+        deployed.iter().find_map(|(p, _)| {
+            let ix = p.instructions.iter().find(|i| i.name == name)?;
+            Some((*p, ix))
+        })
+    };
+    let Some((first, _)) = names.first().and_then(|n| resolve(n)) else {
+        return Err("sequence starts with an instruction that has no deployed program".to_string());
+    };
+    let mut svm = LiteSVM::new();
+    load_programs(&mut svm, deployed, first.program_id, &mut Vec::new())?;
+    let payer = Keypair::new();
+    fund(&mut svm, &payer.pubkey())?;
+
+    let mut keyring = HashMap::new();
+    let mut steps = Vec::new();
+    for name in names {
+        let (program, ix) = resolve(name)
+            .ok_or_else(|| format!("sequence step `{}` has no deployed program", name))?;
+        let step = new_case(program, ix, "sequence", Mutation::None, Expectation::MustSucceed);
+        let mut trace = CaseTrace::default();
+        let style = harness.style();
+        let result =
+            send_setup_ix(&mut svm, program, &step, &payer, &mut keyring, style, &mut trace);
+        let success = result.is_ok();
+        steps.push(SequenceStep {
+            idl_file: program.idl_file.clone(),
+            instruction: name.clone(),
+            success,
+            error: result.err(),
+            logs: trace.tx.as_ref().map(|m| cap_logs(&m.logs)).unwrap_or_default(),
+            compute_units: trace.tx.as_ref().map(|m| m.compute_units_consumed),
+        });
+        if !success {
+            break;
+        }
+    }
+    Ok(steps)
+}
+
+// Log lines kept per case; longer logs end with a truncation marker.
+const MAX_LOG_LINES: usize = 50;
+
+fn cap_logs(logs: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = logs.iter().take(MAX_LOG_LINES).cloned().collect();
+    if logs.len() > MAX_LOG_LINES {
+        out.push(format!("... {} more log lines truncated", logs.len() - MAX_LOG_LINES));
+    }
+    out
+}
+
+// Run a case in its own LiteSVM with freshly deployed programs (`--isolate`, replay, fuzz).
+fn run_isolated(
+    deployed: &[(&ProgramSpec, &[u8])],
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    harness: Harness,
+    mut timings: Option<&mut HarnessTimings>,
+    trace: &mut CaseTrace,
+) -> std::result::Result<(), String> {
+    let mut svm = LiteSVM::new();
+    timed(slot(&mut timings, |t| &mut t.add_program), || {
+        load_programs(&mut svm, deployed, case.program_id, &mut trace.loaded_programs)
+    })?;
+    run_case(&mut svm, program, case, harness, timings, trace)
+}
+
+// LiteSVM instances one worker reuses across cases, one per program under test, so programs
+// are deployed once per worker instead of once per case.
+#[derive(Default)]
+struct SvmPool {
+    instances: HashMap<Address, (LiteSVM, Vec<String>)>,
+}
+
+impl SvmPool {
+    // Run `case` on the instance for its program, then wipe every account the case touched so
+    // the next case sees the same state a fresh instance would.
+    fn run(
+        &mut self,
+        deployed: &[(&ProgramSpec, &[u8])],
+        program: &ProgramSpec,
+        case: &EdgeCase,
+        harness: Harness,
+        mut timings: Option<&mut HarnessTimings>,
+        trace: &mut CaseTrace,
+    ) -> std::result::Result<(), String> {
+        let (svm, loaded) = match self.instances.entry(case.program_id) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                // Every case airdrops to fresh keys, more than the default 1M SOL covers.
+                let mut svm = LiteSVM::new().with_lamports(u64::MAX / 2);
+                let mut loaded = Vec::new();
+                timed(slot(&mut timings, |t| &mut t.add_program), || {
+                    load_programs(&mut svm, deployed, case.program_id, &mut loaded)
+                })?;
+                e.insert((svm, loaded))
+            }
         };
+        trace.loaded_programs.clone_from(loaded);
+        let result = run_case(svm, program, case, harness, timings, trace);
+        reset_accounts(svm, &trace.touched);
+        result
     }
+}
 
-    if let Some(obj) = ty.as_object() {
-        if let Some(arr_ty) = obj.get("array") {
-            let inner = arr_ty
-                .as_array()
-                .and_then(|a| if a.len() == 2 { Some((&a[0], &a[1])) } else { None })
-                .ok_or("invalid array type")?;
-            let inner_bytes = encode_arg_zero(inner.0)?;
-            let len = inner.1.as_u64().ok_or("invalid array len")? as usize;
-            return Ok(inner_bytes.into_iter().cycle().take(len).collect());
+// Delete the accounts a case created or modified; programs and sysvars are left alone.
+fn reset_accounts(svm: &mut LiteSVM, keys: &[Address]) {
+    for key in keys {
+        let Some(account) = svm.get_account(key) else {
+            continue;
+        };
+        if account.executable || account.owner == SYSVAR_OWNER {
+            continue;
         }
+        let _ = svm.set_account(*key, Account::default());
     }
-
-    Err("complex arg type not supported")
 }
 
-// Submit the transaction to LiteSVM with the payer + any extra signers.
-fn send_ix(
+// Owner of every sysvar account.
+const SYSVAR_OWNER: Address = address!("Sysvar1111111111111111111111111111111111111");
+
+// Owner of accounts created by `--fund-writable`.
+const SYSTEM_PROGRAM: Address = address!("11111111111111111111111111111111");
+
+// Run a single case on `svm` (programs already deployed): run the setup chain, construct the
+// instruction, and submit a transaction.
+fn run_case(
     svm: &mut LiteSVM,
-    payer: &Keypair,
-    extra_signers: &[Keypair],
-    ix: Instruction,
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    harness: Harness,
+    mut timings: Option<&mut HarnessTimings>,
+    trace: &mut CaseTrace,
 ) -> std::result::Result<(), String> {
-    let blockhash = svm.latest_blockhash();
-    let msg = Message::new_with_blockhash(&[ix], Some(&payer.pubkey()), &blockhash);
+    let style = harness.style();
+    let payer = Keypair::new();
+    trace.touched.push(payer.pubkey());
+    timed(slot(&mut timings, |t| &mut t.airdrop), || {
+        fund(svm, &payer.pubkey())
+    })?;
 
-    let mut signers: Vec<&Keypair> = Vec::with_capacity(1 + extra_signers.len());
-    signers.push(payer);
-    for s in extra_signers {
-        signers.push(s);
+    // Signers keep the same keypair across the setup chain and the case, so PDAs seeded by
+    // them (e.g. `[b"vault", user]`) point at the state the initializer created.
+    let mut keyring = HashMap::new();
+    for name in &case.setup_chain {
+        let result = match program.instructions.iter().find(|i| &i.name == name) {
+            Some(ix) => {
+                let step = new_case(program, ix, "setup", Mutation::None, Expectation::Any);
+                send_setup_ix(svm, program, &step, &payer, &mut keyring, style, trace)
+            }
+            None => Err(format!("setup instruction `{}` not found", name)),
+        };
+        trace.setup.push(SetupStep {
+            instruction: name.clone(),
+            success: result.is_ok(),
+            error: result.err(),
+        });
+    }
+    // double_init: the first attempt runs like a setup step, with the keys the case reuses, so
+    // both attempts target the same PDA. It has to succeed, or the second call proves nothing.
+    // A new blockhash keeps the second transaction from being rejected as a duplicate.
+    if matches!(case.mutation, Mutation::DoubleInit) {
+        let first = new_case(program, &case.instruction, "setup", Mutation::None, Expectation::Any);
+        let result = send_setup_ix(svm, program, &first, &payer, &mut keyring, style, trace);
+        trace.setup.push(SetupStep {
+            instruction: format!("{} (first attempt)", case.instruction.name),
+            success: result.is_ok(),
+            error: result.clone().err(),
+        });
+        result.map_err(|e| format!("first attempt failed: {}", e))?;
+        svm.expire_blockhash();
     }
+    // Only the case's own transaction is reported.
+    trace.tx = None;
 
-    let tx = Transaction::new(&signers, msg, blockhash);
-    svm.send_transaction(tx)
-        .map(|_| ())
-        .map_err(|e| format!("transaction failed: {e:?}"))
+    let style = ArgStyle {
+        some_options: matches!(case.mutation, Mutation::OptionSome),
+        max_ints: matches!(case.mutation, Mutation::MaxArgs),
+        max_amounts: matches!(case.mutation, Mutation::HugeAmount),
+        ..style
+    };
+    // Encoded once so `arg` seeds and the instruction data always agree.
+    let mut args = encode_args(&case.instruction, &program.types, style)?;
+    if let Mutation::Boundary { arg, value } = &case.mutation {
+        set_boundary_arg(&case.instruction, &mut args, arg, *value);
+    }
+    if let Mutation::RandomArgs { args: random, .. } = &case.mutation {
+        if random.len() == args.len() {
+            args.clone_from(random);
+        }
+    }
+    if let Mutation::Fuzz(FuzzPerturbation::ArgValues { values }) = &case.mutation {
+        for (name, bytes) in values {
+            if let Some(i) = case.instruction.args.iter().position(|a| &a.name == name) {
+                args[i] = bytes.clone();
+            }
+        }
+    }
+
+    let mut accounts = timed(slot(&mut timings, |t| &mut t.build_accounts), || {
+        build_accounts(svm, program, case, &payer, &mut keyring, &args)
+    })?;
+    trace.unresolved_seeds.append(&mut accounts.unresolved);
+    trace.foreign_pda.clone_from(&accounts.foreign_pda);
+    trace.non_canonical_bump.clone_from(&accounts.non_canonical_bump);
+    trace.wrong_authority.clone_from(&accounts.wrong_authority);
+    if let Mutation::NonCanonicalBump { account } = &case.mutation {
+        let derived = !trace.unresolved_seeds.iter().any(|u| u.account == *account);
+        if derived && trace.non_canonical_bump.is_none() {
+            trace.bump_exhausted = true;
+            return Err(format!(
+                "no bump below the canonical one gives `{}` a valid address",
+                account
+            ));
+        }
+    }
+    trace.touched.extend(accounts.pubkey_by_name.values());
+    trace.touched.extend(accounts.metas.iter().map(|m| m.pubkey));
+    // Signers other than the payer may need to pay rent or transfer lamports themselves.
+    timed(slot(&mut timings, |t| &mut t.airdrop), || {
+        accounts
+            .signers
+            .iter()
+            .try_for_each(|kp| fund(svm, &kp.pubkey()))
+    })?;
+    fabricate_accounts(svm, program, case, &accounts, style, harness.fabricated_balance, trace)?;
+    prepare_accounts(svm, program, case, &accounts, style, harness.fabricated_balance)?;
+    if let Some(fixtures) = harness.fixtures {
+        seed_fixtures(svm, case, &accounts, fixtures)?;
+    }
+    if harness.fund_writable {
+        fund_writable_accounts(svm, case, &accounts)?;
+    }
+    if let Mutation::Fuzz(FuzzPerturbation::AccountOrder { order }) = &case.mutation {
+        if order.len() == accounts.metas.len() {
+            accounts.metas = order.iter().map(|&i| accounts.metas[i].clone()).collect();
+        }
+    }
+    let mut data = [case.instruction.discriminator.clone(), args.concat()].concat();
+
+    mutate_data(&mut data, case.instruction.discriminator.len(), &case.mutation);
+
+    let program_id = match case.mutation {
+        Mutation::WrongProgramId => Address::from(Keypair::new().pubkey().to_bytes()),
+        _ => case.program_id,
+    };
+
+    let ix = Instruction {
+        program_id,
+        accounts: accounts.metas,
+        data,
+    };
+    trace.sent = Some(SentInstruction {
+        program_id,
+        data: ix.data.clone(),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| (account_name(&accounts.pubkey_by_name, &meta.pubkey), meta.clone()))
+            .collect(),
+    });
+
+    let result = timed(slot(&mut timings, |t| &mut t.send_ix), || {
+        send_ix(svm, &payer, &accounts.signers, ix, &mut trace.tx)
+    });
+    if let Expectation::AccountState { assertions } = &case.expectation {
+        trace.state = assertions
+            .iter()
+            .map(|a| StateCheck {
+                assertion: a.clone(),
+                actual: result
+                    .is_ok()
+                    .then(|| asserted_slice(svm, &accounts.pubkey_by_name, a))
+                    .flatten(),
+            })
+            .collect();
+    }
+    result
+}
+
+// Bytes of `assertion.account` at the asserted range after the case, read before the SVM is
+// reset.
+fn asserted_slice(
+    svm: &LiteSVM,
+    pubkey_by_name: &HashMap<String, Address>,
+    assertion: &AccountAssertion,
+) -> Option<Vec<u8>> {
+    let account = svm.get_account(pubkey_by_name.get(&assertion.account)?)?;
+    let end = assertion.offset.checked_add(assertion.expected.len())?;
+    account.data.get(assertion.offset..end).map(<[u8]>::to_vec)
+}
+
+// IDL name of the account holding `key` (the first, for duplicated keys).
+fn account_name(pubkey_by_name: &HashMap<String, Address>, key: &Address) -> String {
+    let mut names: Vec<&String> = pubkey_by_name
+        .iter()
+        .filter(|(_, k)| *k == key)
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names.first().map_or_else(|| "<unknown>".to_string(), |n| n.to_string())
+}
+
+// Send one unmutated setup-chain or `--sequence` instruction; a setup step's outcome is
+// recorded but never aborts the case.
+fn send_setup_ix(
+    svm: &mut LiteSVM,
+    program: &ProgramSpec,
+    step: &EdgeCase,
+    payer: &Keypair,
+    keyring: &mut HashMap<String, Keypair>,
+    style: ArgStyle,
+    trace: &mut CaseTrace,
+) -> std::result::Result<(), String> {
+    let args = encode_args(&step.instruction, &program.types, style)?;
+    let accounts = build_accounts(svm, program, step, payer, keyring, &args)?;
+    trace.touched.extend(accounts.pubkey_by_name.values());
+    accounts
+        .signers
+        .iter()
+        .try_for_each(|kp| fund(svm, &kp.pubkey()))?;
+    let ix = Instruction {
+        program_id: step.program_id,
+        accounts: accounts.metas,
+        data: [step.instruction.discriminator.clone(), args.concat()].concat(),
+    };
+    send_ix(svm, payer, &accounts.signers, ix, &mut trace.tx)
+}
+
+// Deploy the program under test, then every sibling workspace program so CPIs between them
+// resolve. A sibling that fails to load is left out of `loaded` rather than failing the case.
+fn load_programs(
+    svm: &mut LiteSVM,
+    deployed: &[(&ProgramSpec, &[u8])],
+    under_test: Address,
+    loaded: &mut Vec<String>,
+) -> std::result::Result<(), String> {
+    let target = deployed
+        .iter()
+        .find(|(p, _)| p.program_id == under_test)
+        .ok_or("program under test has no deployed binary")?;
+    svm.add_program(under_test, target.1)
+        .map_err(|e| format!("add_program failed: {e:?}"))?;
+    loaded.push(target.0.idl_file.clone());
+
+    let mut seen = HashSet::from([under_test]);
+    for (p, bytes) in deployed {
+        if seen.insert(p.program_id) && svm.add_program(p.program_id, bytes).is_ok() {
+            loaded.push(p.idl_file.clone());
+        }
+    }
+    Ok(())
+}
+
+// Data-level mutations adjust the encoded base payload to force failure scenarios.
+fn mutate_data(data: &mut Vec<u8>, discriminator_len: usize, mutation: &Mutation) {
+    match mutation {
+        // Drop the last arg byte; the discriminator is never touched.
+        Mutation::TruncateData if data.len() > discriminator_len => {
+            data.pop();
+        }
+        // Flip the first discriminator byte; arg bytes stay intact.
+        Mutation::CorruptDiscriminator => {
+            if let Some(b) = data.first_mut() {
+                *b ^= 0xFF;
+            }
+        }
+        Mutation::EmptyData => data.clear(),
+        Mutation::Fuzz(FuzzPerturbation::DataBytes { writes }) => {
+            for &(offset, value) in writes {
+                if let Some(b) = data.get_mut(offset) {
+                    *b = value;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// Lamports airdropped to the payer and every extra signer.
+const FUNDING_LAMPORTS: u64 = 10_000_000_000;
+
+fn fund(svm: &mut LiteSVM, key: &Address) -> std::result::Result<(), String> {
+    svm.airdrop(key, FUNDING_LAMPORTS)
+        .map(|_| ())
+        .map_err(|e| format!("airdrop failed: {e:?}"))
+}
+
+// Accounts resolved for one case: ordered metas, extra signers, and the name -> pubkey table.
+struct ResolvedAccounts {
+    metas: Vec<AccountMeta>,
+    signers: Vec<Keypair>,
+    pubkey_by_name: HashMap<String, Address>,
+    unresolved: Vec<UnresolvedSeed>,
+    foreign_pda: Option<ForeignPda>,
+    non_canonical_bump: Option<BumpSwap>,
+    wrong_authority: Option<AuthoritySwap>,
+}
+
+// Give each PDA of a non-initializer that is still missing after the setup chain a
+// program-owned, rent-exempt account of its IDL type (discriminator, zeroed fields), so the case
+// reaches the program's logic instead of stopping at AccountNotInitialized. Initializers create
+// their own PDAs, PDAs without a known IDL type are left alone, and mutation targets are
+// prepared by `prepare_accounts` instead.
+fn fabricate_accounts(
+    svm: &mut LiteSVM,
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    accounts: &ResolvedAccounts,
+    style: ArgStyle,
+    balance: u64,
+    trace: &mut CaseTrace,
+) -> std::result::Result<(), String> {
+    if is_initializer(&case.instruction.name) {
+        return Ok(());
+    }
+    // A wrong_authority case fabricates state for the legitimate authority, not the stranger.
+    let mut keys = accounts.pubkey_by_name.clone();
+    if let Some(swap) = &accounts.wrong_authority {
+        keys.insert(swap.account.clone(), swap.authority);
+    }
+    for acc in case.instruction.accounts.iter().filter(|a| !a.pda_seeds.is_empty()) {
+        let Some(acct) = account_type_for(program, &acc.name) else {
+            continue;
+        };
+        if matches!(
+                &case.mutation,
+                Mutation::WrongPda { account }
+                    | Mutation::CorruptSeed { account, .. }
+                    | Mutation::NonCanonicalBump { account }
+                    | Mutation::ForeignPda { account }
+                    | Mutation::WrongOwner { account }
+                    | Mutation::Uninitialized { account }
+                    if *account == acc.name
+            )
+        {
+            continue;
+        }
+        let Some(key) = accounts.pubkey_by_name.get(&acc.name) else {
+            continue;
+        };
+        if svm.get_account(key).is_some_and(|a| a.lamports > 0) {
+            continue;
+        }
+        let mut data = plausible_account_data(program, case, &acc.name, style);
+        let filled = fill_fabricated_fields(program, case, &keys, acct, &mut data, balance);
+        let account = Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: case.program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(*key, account)
+            .map_err(|e| format!("set_account failed for {}: {e:?}", acc.name))?;
+        trace.fabricated.push(acc.name.clone());
+        trace.fabricated_fields.extend(filled.into_iter().map(|(field, value)| FabricatedField {
+            account: acc.name.clone(),
+            field,
+            value,
+        }));
+    }
+    Ok(())
+}
+
+// Account fields naming who owns the account; without a same-named signer they get the
+// instruction's first signer.
+const OWNER_FIELDS: &[&str] = &["authority", "owner", "user"];
+
+// Fill the fixed-offset fields of a fabricated account that programs check before doing any
+// work: pubkey fields named after a signer (or owner-like, see OWNER_FIELDS) get that signer's
+// key, u64 fields named like a balance get `balance`. A wrong_signer target is left out so the
+// account still belongs to the original signer. Returns (field, value) for each field written.
+fn fill_fabricated_fields(
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    keys: &HashMap<String, Address>,
+    acct: &AccountTypeSpec,
+    data: &mut [u8],
+    balance: u64,
+) -> Vec<(String, String)> {
+    let substituted = |a: &&AccountSpec| {
+        matches!(&case.mutation, Mutation::WrongSigner { account } if *account == a.name)
+    };
+    let signers: Vec<&AccountSpec> = case
+        .instruction
+        .accounts
+        .iter()
+        .filter(|a| a.signer)
+        .filter(|a| !substituted(a))
+        .collect();
+    let mut filled = Vec::new();
+    for field in &acct.fields {
+        // Fields after a dynamically sized one have no fixed offset.
+        let Ok((offset, size)) = field_range(acct, &field.name, &program.types) else {
+            break;
+        };
+        let (bytes, value) = match field.ty.as_str() {
+            Some("pubkey") => {
+                let signer = signers.iter().find(|s| s.name == field.name).or_else(|| {
+                    OWNER_FIELDS
+                        .contains(&field.name.as_str())
+                        .then(|| signers.first())
+                        .flatten()
+                });
+                let Some(key) = signer.and_then(|s| keys.get(&s.name)) else {
+                    continue;
+                };
+                (key.to_bytes().to_vec(), key.to_string())
+            }
+            Some("u64") if field.name.contains("balance") => {
+                (balance.to_le_bytes().to_vec(), balance.to_string())
+            }
+            _ => continue,
+        };
+        if let Some(slot) = data.get_mut(offset..offset + size) {
+            slot.copy_from_slice(&bytes);
+            filled.push((field.name.clone(), value));
+        }
+    }
+    filled
+}
+
+// Per-mutation hook that seeds on-chain state before the transaction is sent.
+fn prepare_accounts(
+    svm: &mut LiteSVM,
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    accounts: &ResolvedAccounts,
+    style: ArgStyle,
+    balance: u64,
+) -> std::result::Result<(), String> {
+    if let Mutation::WrongOwner { account } = &case.mutation {
+        let key = match accounts.pubkey_by_name.get(account) {
+            Some(k) => *k,
+            None => return Ok(()),
+        };
+        // Funded, non-empty account owned by an unrelated program, sized like the real one.
+        // The real discriminator is kept so only the owner check can reject it.
+        let data = plausible_account_data(program, case, account, style);
+        let fake = Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: Keypair::new().pubkey(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(key, fake)
+            .map_err(|e| format!("set_account failed for {}: {e:?}", account))?;
+    }
+    // The random key (or corrupted-seed or non-canonical address) standing in for the PDA holds a
+    // program-owned, rent-exempt account with the real discriminator, so the address is the only
+    // thing wrong and the program must reject it on the seeds constraint rather than on a missing
+    // account.
+    if let Mutation::WrongPda { account }
+    | Mutation::CorruptSeed { account, .. }
+    | Mutation::NonCanonicalBump { account } = &case.mutation
+    {
+        if let Some(key) = accounts.pubkey_by_name.get(account) {
+            let data = plausible_account_data(program, case, account, style);
+            let substitute = Account {
+                lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: case.program_id,
+                executable: false,
+                rent_epoch: 0,
+            };
+            svm.set_account(*key, substitute)
+                .map_err(|e| format!("set_account failed for {}: {e:?}", account))?;
+        }
+    }
+    // The other user's PDA exists and is filled in as theirs (authority = that user), so the
+    // program has real state to confuse with the signer's own.
+    if let Some(foreign) = &accounts.foreign_pda {
+        let mut data = plausible_account_data(program, case, &foreign.account, style);
+        if let Some(acct) = account_type_for(program, &foreign.account) {
+            let mut keys = accounts.pubkey_by_name.clone();
+            for signer in case.instruction.accounts.iter().filter(|a| a.signer) {
+                keys.insert(signer.name.clone(), foreign.owner);
+            }
+            fill_fabricated_fields(program, case, &keys, acct, &mut data, balance);
+        }
+        let theirs = Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: case.program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(foreign.substituted, theirs)
+            .map_err(|e| format!("set_account failed for {}: {e:?}", foreign.account))?;
+    }
+    // Erase what the setup chain created at the PDA; the key stays in the case's accounts, so
+    // the pooled SVM resets it like any other.
+    if let Mutation::Uninitialized { account } = &case.mutation {
+        if let Some(key) = accounts.pubkey_by_name.get(account) {
+            svm.set_account(*key, Account::default())
+                .map_err(|e| format!("set_account failed for {}: {e:?}", account))?;
+        }
+    }
+    Ok(())
+}
+
+// Zeroed data sized like `account`'s IDL type, starting with its discriminator.
+fn plausible_account_data(
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    account: &str,
+    style: ArgStyle,
+) -> Vec<u8> {
+    let space = account_space_for(program, &case.instruction, account, style)
+        .and_then(Result::ok)
+        .unwrap_or(FALLBACK_ACCOUNT_SPACE);
+    let mut data = vec![0u8; space];
+    if let Some(acct) = account_type_for(program, account) {
+        let n = acct.discriminator.len().min(data.len());
+        data[..n].copy_from_slice(&acct.discriminator[..n]);
+    }
+    data
+}
+
+// `--fixtures`: give each named account of the case its fixture, after the setup chain so a
+// fixture wins over what an initializer created. Fixed addresses (programs, sysvars) and the
+// wrong_owner and uninitialized targets keep their state; every other key is reset with the
+// case's accounts.
+fn seed_fixtures(
+    svm: &mut LiteSVM,
+    case: &EdgeCase,
+    accounts: &ResolvedAccounts,
+    fixtures: &AccountFixtures,
+) -> std::result::Result<(), String> {
+    for acc in &case.instruction.accounts {
+        let Some(fixture) = fixtures.get(&acc.name) else {
+            continue;
+        };
+        if known_address(acc).is_some()
+            || matches!(
+                &case.mutation,
+                Mutation::WrongOwner { account }
+                    | Mutation::Uninitialized { account }
+                    | Mutation::ForeignPda { account }
+                    if *account == acc.name
+            )
+        {
+            continue;
+        }
+        let Some(key) = accounts.pubkey_by_name.get(&acc.name) else {
+            continue;
+        };
+        let account = Account {
+            lamports: fixture.lamports,
+            data: fixture.data.clone(),
+            owner: fixture.owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(*key, account)
+            .map_err(|e| format!("set_account failed for fixture {}: {e:?}", acc.name))?;
+    }
+    Ok(())
+}
+
+// `--fund-writable`: create each writable account that is neither a signer nor a PDA as an
+// empty, rent-exempt system account. Accounts that already exist (setup-chain state, wrong_owner
+// fakes), fixed addresses and a duplicate_account alias are left alone.
+fn fund_writable_accounts(
+    svm: &mut LiteSVM,
+    case: &EdgeCase,
+    accounts: &ResolvedAccounts,
+) -> std::result::Result<(), String> {
+    let lamports = svm.minimum_balance_for_rent_exemption(0);
+    for acc in &case.instruction.accounts {
+        if !acc.writable || acc.signer || !acc.pda_seeds.is_empty() || known_address(acc).is_some()
+        {
+            continue;
+        }
+        if matches!(&case.mutation, Mutation::DuplicateAccount { account, .. } if *account == acc.name)
+        {
+            continue;
+        }
+        let Some(key) = accounts.pubkey_by_name.get(&acc.name) else {
+            continue;
+        };
+        if svm.get_account(key).is_some_and(|a| a.lamports > 0) {
+            continue;
+        }
+        let account = Account {
+            lamports,
+            data: Vec::new(),
+            owner: SYSTEM_PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(*key, account)
+            .map_err(|e| format!("set_account failed for {}: {e:?}", acc.name))?;
+    }
+    Ok(())
+}
+
+// Data length used when an account's IDL type (or its size) is unknown.
+const FALLBACK_ACCOUNT_SPACE: usize = 64;
+
+// Space the program would allocate for `account`, sizing dynamic fields from this case's args.
+// None when the account doesn't map to an IDL account type.
+pub fn account_space_for(
+    program: &ProgramSpec,
+    ix: &InstructionSpec,
+    account: &str,
+    style: ArgStyle,
+) -> Option<std::result::Result<usize, String>> {
+    let acct = account_type_for(program, account)?;
+    Some(account_space(acct, ix, &program.types, |name| {
+        let arg = ix.args.iter().find(|a| a.name == name)?;
+        encode_arg(&arg.ty, &program.types, style).ok().map(|b| b.len())
+    }))
+}
+
+// Pick one phase counter out of the optional per-case timings.
+fn slot<'a>(
+    timings: &'a mut Option<&mut HarnessTimings>,
+    phase: fn(&mut HarnessTimings) -> &mut Duration,
+) -> Option<&'a mut Duration> {
+    timings.as_deref_mut().map(phase)
+}
+
+// Run `f`, adding its wall time to `slot` when timing is enabled.
+fn timed<T>(slot: Option<&mut Duration>, f: impl FnOnce() -> T) -> T {
+    match slot {
+        Some(d) => {
+            let start = Instant::now();
+            let out = f();
+            *d += start.elapsed();
+            out
+        }
+        None => f(),
+    }
+}
+
+// Resolve account metas and signer keypairs, including PDA derivation when possible.
+fn build_accounts(
+    svm: &LiteSVM,
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    payer: &Keypair,
+    keyring: &mut HashMap<String, Keypair>,
+    args: &[Vec<u8>],
+) -> std::result::Result<ResolvedAccounts, String> {
+    let mut signer_by_name: HashMap<String, Keypair> = HashMap::new();
+    let mut pubkey_by_name: HashMap<String, Address> = HashMap::new();
+
+    // First pass: assign pubkeys for signer/non-signer accounts.
+    let payer_account = payer_account(case);
+    for acc in &case.instruction.accounts {
+        if is_unsigned(case, acc) {
+            // Not the payer: the fee payer always signs, which would mask the mutation.
+            pubkey_by_name.insert(acc.name.clone(), Keypair::new().pubkey());
+        } else if acc.signer {
+            if payer_account == Some(acc.name.as_str()) {
+                pubkey_by_name.insert(acc.name.clone(), payer.pubkey());
+            } else {
+                let kp = keyring
+                    .entry(acc.name.clone())
+                    .or_insert_with(Keypair::new)
+                    .insecure_clone();
+                pubkey_by_name.insert(acc.name.clone(), kp.pubkey());
+                signer_by_name.insert(acc.name.clone(), kp);
+            }
+        } else {
+            let key = known_address(acc).unwrap_or_else(|| Keypair::new().pubkey());
+            pubkey_by_name.insert(acc.name.clone(), key);
+        }
+    }
+
+    // Second pass: derive PDA pubkeys for accounts that define seed recipes.
+    let mut unresolved = Vec::new();
+    let mut non_canonical_bump = None;
+    for acc in &case.instruction.accounts {
+        if acc.pda_seeds.is_empty() {
+            continue;
+        }
+
+        // Unresolvable PDAs keep their random first-pass key and are reported, never guessed.
+        let mut seeds: Vec<Vec<u8>> = Vec::new();
+        let mut failed = None;
+        for seed in &acc.pda_seeds {
+            match resolve_seed(svm, program, case, seed, &pubkey_by_name, args) {
+                Ok(bytes) => seeds.push(bytes),
+                Err(reason) => {
+                    failed = Some(UnresolvedSeed {
+                        account: acc.name.clone(),
+                        seed: seed.label(),
+                        reason,
+                    });
+                    break;
+                }
+            }
+        }
+        if let Some(u) = failed {
+            unresolved.push(u);
+            continue;
+        }
+        if let Mutation::Fuzz(FuzzPerturbation::PdaSeed {
+            account,
+            seed_index,
+            xor,
+        }) = &case.mutation
+        {
+            if *account == acc.name {
+                let n = seeds.len();
+                if let Some(b) = seeds.get_mut(seed_index % n).and_then(|s| s.first_mut()) {
+                    *b ^= xor;
+                }
+            }
+        }
+
+        if let Mutation::CorruptSeed {
+            account,
+            seed_index,
+        } = &case.mutation
+        {
+            if *account == acc.name {
+                if let Some(bytes) = seeds.get_mut(*seed_index) {
+                    corrupt_seed(&acc.pda_seeds[*seed_index], bytes);
+                }
+            }
+        }
+
+        let (pda, canonical_bump) = find_pda(&seeds, &case.program_id);
+        pubkey_by_name.insert(acc.name.clone(), pda);
+
+        let bump_target =
+            matches!(&case.mutation, Mutation::NonCanonicalBump { account } if *account == acc.name);
+        if bump_target {
+            if let Some((substituted, bump)) =
+                non_canonical_pda(&seeds, &case.program_id, canonical_bump)
+            {
+                pubkey_by_name.insert(acc.name.clone(), substituted);
+                non_canonical_bump = Some(BumpSwap {
+                    account: acc.name.clone(),
+                    expected: pda,
+                    canonical_bump,
+                    substituted,
+                    bump,
+                });
+            }
+        }
+    }
+
+    // For wrong-PDA mutations, override the derived PDA with a random pubkey.
+    if let Mutation::WrongPda { account } = &case.mutation {
+        if pubkey_by_name.contains_key(account) {
+            pubkey_by_name.insert(account.clone(), Keypair::new().pubkey());
+        }
+    }
+
+    // Derive the target again with every signer seed swapped for an unrelated user's key: a valid
+    // PDA of the program, just not the signers' own.
+    let mut foreign_pda = None;
+    if let Mutation::ForeignPda { account } = &case.mutation {
+        let target = case.instruction.accounts.iter().find(|a| a.name == *account);
+        let derived = !unresolved.iter().any(|u| u.account == *account);
+        if let (Some(acc), Some(&expected), true) = (target, pubkey_by_name.get(account), derived) {
+            let owner = Keypair::new().pubkey();
+            let mut keys = pubkey_by_name.clone();
+            for signer in case.instruction.accounts.iter().filter(|a| a.signer) {
+                keys.insert(signer.name.clone(), owner);
+            }
+            let seeds: std::result::Result<Vec<_>, _> = acc
+                .pda_seeds
+                .iter()
+                .map(|seed| resolve_seed(svm, program, case, seed, &keys, args))
+                .collect();
+            if let Ok(seeds) = seeds {
+                let (substituted, _) = find_pda(&seeds, &case.program_id);
+                pubkey_by_name.insert(account.clone(), substituted);
+                foreign_pda = Some(ForeignPda {
+                    account: account.clone(),
+                    expected,
+                    substituted,
+                    owner,
+                });
+            }
+        }
+    }
+
+    // Swap in an unrelated keypair that still signs. Runs after PDA derivation so seeds keep
+    // pointing at the original owner's accounts.
+    if let Mutation::WrongSigner { account } = &case.mutation {
+        if pubkey_by_name.contains_key(account) {
+            let kp = Keypair::new();
+            pubkey_by_name.insert(account.clone(), kp.pubkey());
+            signer_by_name.insert(account.clone(), kp);
+        }
+    }
+
+    // Same swap for wrong_authority, remembering whose state the PDAs still point at.
+    let mut wrong_authority = None;
+    if let Mutation::WrongAuthority { account } = &case.mutation {
+        if let Some(&authority) = pubkey_by_name.get(account) {
+            let kp = Keypair::new();
+            pubkey_by_name.insert(account.clone(), kp.pubkey());
+            wrong_authority = Some(AuthoritySwap {
+                account: account.clone(),
+                authority,
+                signer: kp.pubkey(),
+            });
+            signer_by_name.insert(account.clone(), kp);
+        }
+    }
+
+    if let Mutation::DuplicateAccount { account, source } = &case.mutation {
+        if let Some(key) = pubkey_by_name.get(source).copied() {
+            pubkey_by_name.insert(account.clone(), key);
+        }
+    }
+
+    let mut metas = Vec::new();
+    let mut extra_signers = Vec::new();
+
+    for (i, acc) in case.instruction.accounts.iter().enumerate() {
+        // Dropped accounts get neither a meta nor a signature; the payer still pays the fee.
+        if matches!(&case.mutation, Mutation::RemoveAccount { index, .. } if *index == i) {
+            continue;
+        }
+
+        let key = pubkey_by_name
+            .get(&acc.name)
+            .copied()
+            .unwrap_or_else(|| Keypair::new().pubkey());
+
+        let signer = acc.signer && !is_unsigned(case, acc);
+        let writable = match &case.mutation {
+            Mutation::NotWritable { account } if *account == acc.name => false,
+            Mutation::UnexpectedWritable { account } if *account == acc.name => true,
+            _ => acc.writable,
+        };
+        let meta = if writable {
+            AccountMeta::new(key, signer)
+        } else {
+            AccountMeta::new_readonly(key, signer)
+        };
+        metas.push(meta);
+
+        if let Some(kp) = signer_by_name.remove(&acc.name) {
+            extra_signers.push(kp);
+        }
+    }
+
+    // Trailing account the IDL doesn't declare (lands in remaining_accounts).
+    if matches!(case.mutation, Mutation::ExtraAccount) {
+        metas.push(AccountMeta::new_readonly(Keypair::new().pubkey(), false));
+    }
+
+    Ok(ResolvedAccounts {
+        metas,
+        signers: extra_signers,
+        pubkey_by_name,
+        unresolved,
+        foreign_pda,
+        non_canonical_bump,
+        wrong_authority,
+    })
+}
+
+// corrupt_seed: account seeds become an unrelated pubkey, const and arg seeds get their first
+// byte flipped (an empty one gains a byte).
+fn corrupt_seed(seed: &SeedSpec, bytes: &mut Vec<u8>) {
+    match (seed, bytes.first_mut()) {
+        (SeedSpec::Account(_), _) => *bytes = Keypair::new().pubkey().to_bytes().to_vec(),
+        (_, Some(b)) => *b ^= 0xff,
+        (_, None) => bytes.push(0xff),
+    }
+}
+
+// Bytes for one seed, or why it can't be produced for this case.
+fn resolve_seed(
+    svm: &LiteSVM,
+    program: &ProgramSpec,
+    case: &EdgeCase,
+    seed: &SeedSpec,
+    pubkey_by_name: &HashMap<String, Address>,
+    args: &[Vec<u8>],
+) -> std::result::Result<Vec<u8>, String> {
+    let key_of = |name: &str| {
+        pubkey_by_name
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("no account `{}` in this instruction", name))
+    };
+    match seed {
+        SeedSpec::Const(bytes) => Ok(bytes.clone()),
+        SeedSpec::Account(path) => match path.split_once('.') {
+            None => key_of(path).map(|k| k.to_bytes().to_vec()),
+            // `vault.authority`: read the field out of `vault`'s on-chain data via the IDL layout.
+            Some((base, field)) => {
+                let key = key_of(base)?;
+                let acct = account_type_for(program, base)
+                    .ok_or_else(|| format!("no IDL account type for `{}`", base))?;
+                let (offset, len) = field_range(acct, field, &program.types)?;
+                let data = svm
+                    .get_account(&key)
+                    .map(|a| a.data)
+                    .filter(|d| !d.is_empty())
+                    .ok_or_else(|| format!("`{}` has no on-chain data for `{}`", base, field))?;
+                data.get(offset..offset + len)
+                    .map(<[u8]>::to_vec)
+                    .ok_or_else(|| format!("`{}` data is too short for `{}`", base, field))
+            }
+        },
+        SeedSpec::Arg(path) => arg_seed(&case.instruction, args, path)
+            .ok_or_else(|| format!("arg `{}` is missing or not encodable", path)),
+    }
+}
+
+// Programs and sysvars LiteSVM ships with, keyed by the account names Anchor programs use.
+const KNOWN_ACCOUNTS: &[(&str, Address)] = &[
+    ("system_program", SYSTEM_PROGRAM),
+    ("token_program", address!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")),
+    ("associated_token_program", address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")),
+    ("rent", address!("SysvarRent111111111111111111111111111111111")),
+    ("clock", address!("SysvarC1ock11111111111111111111111111111111")),
+];
+
+// Real address for a well-known account: the IDL's fixed `address` first, then the name table.
+fn known_address(acc: &AccountSpec) -> Option<Address> {
+    acc.address.or_else(|| known_account_address(&acc.name))
+}
+
+pub fn known_account_address(name: &str) -> Option<Address> {
+    KNOWN_ACCOUNTS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, addr)| *addr)
+}
+
+// Canonical PDA and bump for `seeds` under `program_id`.
+pub fn find_pda(seeds: &[Vec<u8>], program_id: &Address) -> (Address, u8) {
+    let seed_slices = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let program_pubkey = anchor_lang::prelude::Pubkey::new_from_array(program_id.to_bytes());
+    let (pda, bump) =
+        anchor_lang::prelude::Pubkey::find_program_address(&seed_slices, &program_pubkey);
+    (Address::from(pda.to_bytes()), bump)
+}
+
+// The highest bump below `canonical` that still gives `seeds` a valid (off-curve) address, one
+// `find_program_address` never returns. Every bump above `canonical` is on the curve by
+// construction, so this covers 254 down to 0 minus the canonical bump. None in the rare case
+// all of them are on the curve.
+fn non_canonical_pda(
+    seeds: &[Vec<u8>],
+    program_id: &Address,
+    canonical: u8,
+) -> Option<(Address, u8)> {
+    let program_pubkey = anchor_lang::prelude::Pubkey::new_from_array(program_id.to_bytes());
+    (0..canonical).rev().find_map(|bump| {
+        let bump_seed = [bump];
+        let mut seed_slices = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        seed_slices.push(&bump_seed);
+        anchor_lang::prelude::Pubkey::create_program_address(&seed_slices, &program_pubkey)
+            .ok()
+            .map(|pda| (Address::from(pda.to_bytes()), bump))
+    })
+}
+
+// Account names Anchor programs conventionally use for whoever pays, in preference order.
+const PAYER_NAMES: &[&str] = &["payer", "user", "authority"];
+
+// Signer account that receives the funded fee-payer key: a conventionally named signer if there
+// is one, otherwise the first signer in IDL order. Stripped signers are never the payer, and
+// neither is a not_writable target, since the fee payer is always writable.
+fn payer_account(case: &EdgeCase) -> Option<&str> {
+    let signers: Vec<&AccountSpec> = case
+        .instruction
+        .accounts
+        .iter()
+        .filter(|a| a.signer && !is_unsigned(case, a))
+        .filter(|a| {
+            !matches!(&case.mutation, Mutation::NotWritable { account } if *account == a.name)
+        })
+        .collect();
+    PAYER_NAMES
+        .iter()
+        .find_map(|name| signers.iter().find(|a| a.name == *name))
+        .or_else(|| signers.first())
+        .map(|a| a.name.as_str())
+}
+
+// Whether this case strips the signature from `acc` (MissingSigner).
+fn is_unsigned(case: &EdgeCase, acc: &AccountSpec) -> bool {
+    matches!(&case.mutation, Mutation::MissingSigner { account } if *account == acc.name)
+}
+
+// Per-run choices for how every case of a `test` run is built.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Harness<'a> {
+    // `--non-empty-strings`.
+    pub non_empty_strings: bool,
+    // `--fund-writable`: pre-create writable non-signer, non-PDA accounts (see
+    // `fund_writable_accounts`).
+    pub fund_writable: bool,
+    // `--fabricated-balance` (see `fill_fabricated_fields`).
+    pub fabricated_balance: u64,
+    // `--fixtures`, loaded from `opts.fixtures` (see `seed_fixtures`).
+    pub fixtures: Option<&'a AccountFixtures>,
+}
+
+impl<'a> Harness<'a> {
+    pub fn from_options(opts: &TestOptions, fixtures: Option<&'a AccountFixtures>) -> Self {
+        Self {
+            non_empty_strings: opts.non_empty_strings,
+            fund_writable: opts.fund_writable,
+            fabricated_balance: opts.fabricated_balance,
+            fixtures,
+        }
+    }
+
+    fn style(self) -> ArgStyle {
+        ArgStyle {
+            non_empty_strings: self.non_empty_strings,
+            ..ArgStyle::default()
+        }
+    }
+}
+
+// Value choices for generated args beyond the all-zero default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArgStyle {
+    // Emit "a" instead of "" for strings so programs actually parse string content.
+    non_empty_strings: bool,
+    // Encode options as Some(<default inner>) instead of None.
+    some_options: bool,
+    // Variant index used for enum args (0 = first variant).
+    enum_variant: usize,
+    // Encode integers at their type's maximum instead of zero.
+    max_ints: bool,
+    // Like `max_ints`, but only for amount-like args (see `is_amount_arg`).
+    max_amounts: bool,
+    // Smallest non-default value everywhere: integers 1, bools true, one-element vecs/bytes,
+    // non-empty strings and Some options.
+    non_zero: bool,
+}
+
+// Arg name fragments that mark an integer arg as an amount.
+const AMOUNT_ARG_HINTS: &[&str] = &["amount", "lamports", "quantity", "qty"];
+
+// Integer arg whose name reads like an amount (`amount`, `withdraw_lamports`, ...).
+fn is_amount_arg(arg: &ArgSpec) -> bool {
+    int_layout(&arg.ty).is_some() && AMOUNT_ARG_HINTS.iter().any(|h| arg.name.contains(h))
+}
+
+// Byte width and signedness of an integer IDL type.
+pub fn int_layout(ty: &Value) -> Option<(usize, bool)> {
+    Some(match ty.as_str()? {
+        "u8" => (1, false),
+        "i8" => (1, true),
+        "u16" => (2, false),
+        "i16" => (2, true),
+        "u32" => (4, false),
+        "i32" => (4, true),
+        "u64" => (8, false),
+        "i64" => (8, true),
+        "u128" => (16, false),
+        "i128" => (16, true),
+        _ => return None,
+    })
+}
+
+// Little-endian bytes of an integer at `value`.
+fn encode_boundary(len: usize, signed: bool, value: Boundary) -> Vec<u8> {
+    let mut out = vec![0; len];
+    match value {
+        Boundary::One => out[0] = 1,
+        Boundary::Max => {
+            out = encode_int(
+                len,
+                signed,
+                ArgStyle {
+                    max_ints: true,
+                    ..ArgStyle::default()
+                },
+            )
+        }
+        Boundary::Min if signed => out[len - 1] = 0x80,
+        Boundary::Min => {}
+    }
+    out
+}
+
+// Replace the encoded bytes of integer arg `name` with `value`.
+fn set_boundary_arg(ix: &InstructionSpec, args: &mut [Vec<u8>], name: &str, value: Boundary) {
+    let Some(i) = ix.args.iter().position(|a| a.name == name) else {
+        return;
+    };
+    if let (Some((len, signed)), Some(slot)) = (int_layout(&ix.args[i].ty), args.get_mut(i)) {
+        *slot = encode_boundary(len, signed, value);
+    }
+}
+
+// Decimal form of an integer at `value` (zero for None).
+fn int_decimal(len: usize, signed: bool, value: Option<Boundary>) -> String {
+    let bits = (len * 8) as u32;
+    match (value, signed) {
+        (None, _) => "0".to_string(),
+        (Some(Boundary::One), _) => "1".to_string(),
+        (Some(Boundary::Max), false) => (u128::MAX >> (128 - bits)).to_string(),
+        (Some(Boundary::Max), true) => (i128::MAX >> (128 - bits)).to_string(),
+        (Some(Boundary::Min), false) => "0".to_string(),
+        (Some(Boundary::Min), true) => (i128::MIN >> (128 - bits)).to_string(),
+    }
+}
+
+// Decimal form of a little-endian integer of at most 16 bytes.
+fn le_decimal(bytes: &[u8], signed: bool) -> String {
+    let negative = signed && bytes.last().is_some_and(|b| b & 0x80 != 0);
+    let mut buf = [if negative { 0xFF } else { 0 }; 16];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    if signed {
+        i128::from_le_bytes(buf).to_string()
+    } else {
+        u128::from_le_bytes(buf).to_string()
+    }
+}
+
+// Decimal value of every integer arg as `run_case` encodes it for this case, so reports can
+// correlate outcomes with concrete inputs.
+pub fn integer_arg_values(case: &EdgeCase) -> Vec<(String, String)> {
+    case.instruction
+        .args
+        .iter()
+        .enumerate()
+        .filter_map(|(i, arg)| {
+            let (len, signed) = int_layout(&arg.ty)?;
+            if let Mutation::RandomArgs { args, .. } = &case.mutation {
+                let bytes = args.get(i)?;
+                return Some((arg.name.clone(), le_decimal(bytes, signed)));
+            }
+            let value = match &case.mutation {
+                Mutation::Boundary { arg: name, value } if *name == arg.name => Some(*value),
+                Mutation::MaxArgs => Some(Boundary::Max),
+                Mutation::HugeAmount if is_amount_arg(arg) => Some(Boundary::Max),
+                _ => None,
+            };
+            Some((arg.name.clone(), int_decimal(len, signed, value)))
+        })
+        .collect()
+}
+
+// Sample string used when non-empty strings are requested.
+const SAMPLE_STRING: &str = "a";
+
+// Encode each default-value arg in declaration order; prefixed with the discriminator this is
+// a minimal valid payload shape.
+fn encode_args(
+    ix: &InstructionSpec,
+    types: &HashMap<String, Value>,
+    style: ArgStyle,
+) -> std::result::Result<Vec<Vec<u8>>, String> {
+    ix.args
+        .iter()
+        .map(|arg| {
+            let style = if style.max_amounts && is_amount_arg(arg) {
+                ArgStyle {
+                    max_ints: true,
+                    ..style
+                }
+            } else {
+                style
+            };
+            encode_arg(&arg.ty, types, style)
+                .map_err(|e| format!("arg {} type not supported: {}", arg.name, e))
+        })
+        .collect()
+}
+
+// Seed bytes for an `arg` seed: the arg's Borsh encoding, minus the length prefix for
+// strings/bytes since Anchor seeds those with `value.as_bytes()`.
+fn arg_seed(ix: &InstructionSpec, args: &[Vec<u8>], path: &str) -> Option<Vec<u8>> {
+    let i = ix.args.iter().position(|a| a.name == path)?;
+    let bytes = args.get(i)?;
+    match ix.args[i].ty.as_str() {
+        Some("string") | Some("bytes") => bytes.get(4..).map(<[u8]>::to_vec),
+        _ => Some(bytes.clone()),
+    }
+}
+
+// Produce a zero-value encoding for supported IDL types.
+pub fn encode_arg_zero(
+    ty: &Value,
+    types: &HashMap<String, Value>,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    encode_arg(ty, types, ArgStyle::default())
+}
+
+// Produce the smallest non-default encoding (see `ArgStyle::non_zero`), for value strategies
+// that need every field populated. Case generation doesn't use it yet.
+#[allow(dead_code)]
+pub fn encode_arg_nonzero(
+    ty: &Value,
+    types: &HashMap<String, Value>,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    encode_arg(
+        ty,
+        types,
+        ArgStyle {
+            non_zero: true,
+            ..ArgStyle::default()
+        },
+    )
+}
+
+// Produce a max-value encoding: unsigned integers all 0xFF, signed integers at `iN::MAX`.
+pub fn encode_arg_max(
+    ty: &Value,
+    types: &HashMap<String, Value>,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    encode_arg(
+        ty,
+        types,
+        ArgStyle {
+            max_ints: true,
+            ..ArgStyle::default()
+        },
+    )
+}
+
+// Little-endian integer of `len` bytes: zero, one under `non_zero`, or the unsigned/signed
+// maximum under `max_ints`.
+fn encode_int(len: usize, signed: bool, style: ArgStyle) -> Vec<u8> {
+    if !style.max_ints {
+        let mut out = vec![0; len];
+        out[0] = u8::from(style.non_zero);
+        return out;
+    }
+    let mut out = vec![0xFF; len];
+    if signed {
+        // Clear the sign bit of the most significant (last) byte.
+        out[len - 1] = 0x7F;
+    }
+    out
+}
+
+// Whether an IDL type contains an `option` anywhere (top-level or nested).
+fn has_option(ty: &Value) -> bool {
+    match ty {
+        Value::Object(obj) => obj.contains_key("option") || obj.values().any(has_option),
+        Value::Array(items) => items.iter().any(has_option),
+        _ => false,
+    }
+}
+
+// Borsh string: u32 LE byte length followed by the UTF-8 bytes.
+fn encode_string(s: &str) -> Vec<u8> {
+    let mut out = (s.len() as u32).to_le_bytes().to_vec();
+    out.extend_from_slice(s.as_bytes());
+    out
+}
+
+// Borsh vec: u32 LE element count from `src` followed by that many elements. Base cases use
+// len 0; non-empty generation modes can request a single element.
+fn encode_vec(
+    inner: &Value,
+    types: &HashMap<String, Value>,
+    src: &mut ValueSource,
+    depth: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    let len = src.len();
+    let mut out = (len as u32).to_le_bytes().to_vec();
+    out.extend(encode_elements(inner, len, types, src, depth)?);
+    Ok(out)
+}
+
+// `len` elements of `inner`, each drawn from `src`.
+fn encode_elements(
+    inner: &Value,
+    len: usize,
+    types: &HashMap<String, Value>,
+    src: &mut ValueSource,
+    depth: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    // Encode the element even for empty vecs so unsupported inner types are still reported.
+    if len == 0 {
+        check_encodable(inner, types, depth + 1)?;
+    }
+    let mut out = Vec::new();
+    for _ in 0..len {
+        out.extend(encode_type(inner, types, src, depth + 1)?);
+    }
+    Ok(out)
+}
+
+// Encode `ty` with throwaway zero values, for spots that emit no value of it (empty vecs and
+// arrays, None options) but must still reject unsupported types.
+fn check_encodable(
+    ty: &Value,
+    types: &HashMap<String, Value>,
+    depth: usize,
+) -> std::result::Result<(), &'static str> {
+    encode_type(ty, types, &mut ValueSource::Style(ArgStyle::default()), depth).map(drop)
+}
+
+// Nesting limit for arg types; only a self-referential `defined` type gets this deep.
+pub const MAX_TYPE_DEPTH: usize = 32;
+
+// Encode supported IDL primitive/array/vec/defined types, using `style` for non-zero choices.
+fn encode_arg(
+    ty: &Value,
+    types: &HashMap<String, Value>,
+    style: ArgStyle,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    encode_type(ty, types, &mut ValueSource::Style(style), 0)
+}
+
+// Where `encode_type` takes its values from: the fixed zero/one/max choices of an `ArgStyle`,
+// or random draws for fuzzing.
+enum ValueSource<'a> {
+    Style(ArgStyle),
+    Random(&'a mut Rng),
+}
+
+impl ValueSource<'_> {
+    // Little-endian integer of `len` bytes.
+    fn int(&mut self, len: usize, signed: bool) -> Vec<u8> {
+        match self {
+            ValueSource::Style(style) => encode_int(len, signed, *style),
+            ValueSource::Random(rng) => (0..len).map(|_| rng.byte()).collect(),
+        }
+    }
+
+    fn bool(&mut self) -> u8 {
+        match self {
+            ValueSource::Style(style) => u8::from(style.non_zero),
+            ValueSource::Random(rng) => rng.below(2) as u8,
+        }
+    }
+
+    fn pubkey(&mut self) -> Vec<u8> {
+        match self {
+            ValueSource::Style(style) => vec![u8::from(style.non_zero); 32],
+            ValueSource::Random(rng) => (0..32).map(|_| rng.byte()).collect(),
+        }
+    }
+
+    fn string(&mut self) -> String {
+        match self {
+            ValueSource::Style(style) if style.non_empty_strings || style.non_zero => {
+                SAMPLE_STRING.to_string()
+            }
+            ValueSource::Style(_) => String::new(),
+            ValueSource::Random(rng) => (0..rng.below(MAX_RANDOM_LEN + 1))
+                .map(|_| char::from(b'a' + rng.below(26) as u8))
+                .collect(),
+        }
+    }
+
+    // Element count of a vec or `bytes`.
+    fn len(&mut self) -> usize {
+        match self {
+            ValueSource::Style(style) => usize::from(style.non_zero),
+            ValueSource::Random(rng) => rng.below(MAX_RANDOM_LEN + 1),
+        }
+    }
+
+    // Whether an option is Some.
+    fn some(&mut self) -> bool {
+        match self {
+            ValueSource::Style(style) => style.some_options || style.non_zero,
+            ValueSource::Random(rng) => rng.below(2) == 1,
+        }
+    }
+
+    // Variant index for an enum with `count` variants.
+    fn variant(&mut self, count: usize) -> std::result::Result<usize, &'static str> {
+        match self {
+            ValueSource::Style(style) if style.enum_variant < count => Ok(style.enum_variant),
+            ValueSource::Style(_) => Err("enum variant index out of range"),
+            ValueSource::Random(_) if count == 0 => Err("enum has no variants"),
+            ValueSource::Random(rng) => Ok(rng.below(count)),
+        }
+    }
+}
+
+fn encode_type(
+    ty: &Value,
+    types: &HashMap<String, Value>,
+    src: &mut ValueSource,
+    depth: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    if depth > MAX_TYPE_DEPTH {
+        return Err("type nesting too deep (recursive defined type?)");
+    }
+    if let Some(s) = ty.as_str() {
+        if let Some((len, signed)) = int_layout(ty) {
+            return Ok(src.int(len, signed));
+        }
+        return match s {
+            "bool" => Ok(vec![src.bool()]),
+            "pubkey" => Ok(src.pubkey()),
+            "string" => Ok(encode_string(&src.string())),
+            // Borsh `Vec<u8>`: same length prefix as a string.
+            "bytes" => encode_vec(&Value::from("u8"), types, src, depth),
+            _ => Err("primitive not supported"),
+        };
+    }
+
+    if let Some(obj) = ty.as_object() {
+        if let Some(arr_ty) = obj.get("array") {
+            let (inner, len) = arr_ty
+                .as_array()
+                .and_then(|a| if a.len() == 2 { Some((&a[0], a[1].as_u64())) } else { None })
+                .ok_or("invalid array type")?;
+            let len = len.ok_or("invalid array len")? as usize;
+            return encode_elements(inner, len, types, src, depth);
+        }
+        if let Some(inner) = obj.get("vec") {
+            return encode_vec(inner, types, src, depth);
+        }
+        if let Some(inner) = obj.get("option") {
+            // Borsh option: 0 for None, 1 followed by the value for Some.
+            if !src.some() {
+                check_encodable(inner, types, depth + 1)?;
+                return Ok(vec![0]);
+            }
+            let mut out = vec![1];
+            out.extend(encode_type(inner, types, src, depth + 1)?);
+            return Ok(out);
+        }
+        if let Some(name) = defined_name(ty) {
+            let def = types.get(name).ok_or("defined type not found in IDL types")?;
+            return encode_defined(def, types, src, depth);
+        }
+    }
+
+    Err("complex arg type not supported")
+}
+
+// SplitMix64: tiny, seedable and plenty for drawing fuzz inputs; avoids a `rand` dependency.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform-enough index in `0..n`; `n` must be non-zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn byte(&mut self) -> u8 {
+        self.next_u64() as u8
+    }
+}
+
+// Longest string / vec / bytes value `encode_arg_random` draws.
+const MAX_RANDOM_LEN: usize = 8;
+
+// Encode a random value of `ty`: integers and pubkeys get random bytes of their full width,
+// strings random lowercase ASCII, vecs/options/enums a random length/presence/variant.
+pub fn encode_arg_random(
+    ty: &Value,
+    types: &HashMap<String, Value>,
+    rng: &mut Rng,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    encode_type(ty, types, &mut ValueSource::Random(rng), 0)
+}
+
+// Random value for every arg of `ix`, in declaration order.
+pub fn random_args(
+    ix: &InstructionSpec,
+    types: &HashMap<String, Value>,
+    rng: &mut Rng,
+) -> std::result::Result<Vec<Vec<u8>>, &'static str> {
+    ix.args
+        .iter()
+        .map(|a| encode_arg_random(&a.ty, types, rng))
+        .collect()
+}
+
+// Borsh struct: each field encoded in declaration order, concatenated.
+// Borsh enum: u8 variant index followed by that variant's fields.
+fn encode_defined(
+    def: &Value,
+    types: &HashMap<String, Value>,
+    src: &mut ValueSource,
+    depth: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    match def["type"]["kind"].as_str() {
+        Some("struct") => {
+            let mut out = Vec::new();
+            for f in def["type"]["fields"].as_array().into_iter().flatten() {
+                out.extend(encode_field(f, types, src, depth)?);
+            }
+            Ok(out)
+        }
+        Some("enum") => {
+            let variants = def["type"]["variants"].as_array().map_or(&[][..], Vec::as_slice);
+            let index = src.variant(variants.len())?;
+            // Borsh caps enums at 256 variants, so the index always fits a u8.
+            let mut out = vec![index as u8];
+            for f in variants[index]["fields"].as_array().into_iter().flatten() {
+                out.extend(encode_field(f, types, src, depth)?);
+            }
+            Ok(out)
+        }
+        _ => Err("defined type kind not supported"),
+    }
+}
+
+// Named fields carry `type`; tuple fields are bare types.
+fn encode_field(
+    f: &Value,
+    types: &HashMap<String, Value>,
+    src: &mut ValueSource,
+    depth: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    let fty = if f["type"].is_null() { f } else { &f["type"] };
+    encode_type(fty, types, src, depth + 1)
+}
+
+// Submit the transaction to LiteSVM with the payer + any extra signers; its metadata (logs,
+// compute units) is written to `tx_meta` whether or not it succeeds.
+fn send_ix(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    extra_signers: &[Keypair],
+    ix: Instruction,
+    tx_meta: &mut Option<TransactionMetadata>,
+) -> std::result::Result<(), String> {
+    let blockhash = svm.latest_blockhash();
+    let msg = Message::new_with_blockhash(&[ix], Some(&payer.pubkey()), &blockhash);
+
+    let mut signers: Vec<&Keypair> = Vec::with_capacity(1 + extra_signers.len());
+    signers.push(payer);
+    for s in extra_signers {
+        signers.push(s);
+    }
+
+    let tx = Transaction::new(&signers, msg, blockhash);
+    match svm.send_transaction(tx) {
+        Ok(meta) => {
+            *tx_meta = Some(meta);
+            Ok(())
+        }
+        Err(e) => {
+            let err = format!("transaction failed: {e:?}");
+            *tx_meta = Some(e.meta);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn program_with(instructions: Vec<InstructionSpec>) -> ProgramSpec {
+        ProgramSpec {
+            idl_file: "vault.json".to_string(),
+            name: "vault".to_string(),
+            idl_format: crate::types::IdlFormat::Modern,
+            idl_version: "0.1.0".to_string(),
+            program_id: Address::new_from_array([7; 32]),
+            program_id_source: crate::types::ProgramIdSource::Idl,
+            deploy_so: std::path::PathBuf::from("vault.so"),
+            instructions,
+            account_types: Vec::new(),
+            types: HashMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    // build_accounts with a fresh keyring, as for a case without a setup chain.
+    fn resolve(
+        svm: &LiteSVM,
+        program: &ProgramSpec,
+        case: &EdgeCase,
+        payer: &Keypair,
+        args: &[Vec<u8>],
+    ) -> ResolvedAccounts {
+        build_accounts(svm, program, case, payer, &mut HashMap::new(), args).unwrap()
+    }
+
+    fn ix(name: &str, args: Vec<crate::types::ArgSpec>) -> InstructionSpec {
+        InstructionSpec {
+            name: name.to_string(),
+            discriminator: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            accounts: Vec::new(),
+            args,
+        }
+    }
+
+    #[test]
+    fn well_known_accounts_resolve_to_real_addresses() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "user", "writable": true, "signer": true },
+                { "name": "system_program" },
+                { "name": "oracle", "address": "SysvarC1ock11111111111111111111111111111111" },
+                { "name": "other" }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let case = new_case(&program_with(vec![]), &ix, "base", Mutation::None, Expectation::Any);
+        let svm = LiteSVM::new();
+        let metas = resolve(&svm, &program_with(vec![]), &case, &Keypair::new(), &[]).metas;
+        assert_eq!(metas[1].pubkey.to_string(), "11111111111111111111111111111111");
+        assert_eq!(metas[2].pubkey.to_string(), "SysvarC1ock11111111111111111111111111111111");
+        assert!(KNOWN_ACCOUNTS.iter().all(|(_, a)| *a != metas[3].pubkey));
+    }
+
+    fn signer_case(accounts: &[(&str, bool)]) -> EdgeCase {
+        let accounts = accounts
+            .iter()
+            .map(|(name, signer)| AccountSpec {
+                name: name.to_string(),
+                signer: *signer,
+                writable: true,
+                pda_seeds: Vec::new(),
+                address: None,
+            })
+            .collect();
+        let ix = InstructionSpec {
+            accounts,
+            ..ix("run", vec![])
+        };
+        new_case(&program_with(vec![]), &ix, "base", Mutation::None, Expectation::Any)
+    }
+
+    // Runs the case through `run_case` (no program is deployed, so the transaction itself fails)
+    // and returns the account that got the payer key; asserts every signer it sent was funded.
+    fn assign_payer(accounts: &[(&str, bool)]) -> String {
+        let case = signer_case(accounts);
+        let mut svm = LiteSVM::new();
+        let mut trace = CaseTrace::default();
+        let program = program_with(vec![]);
+        let _ = run_case(&mut svm, &program, &case, Harness::default(), None, &mut trace);
+
+        let sent = trace.sent.expect("instruction built and sent");
+        for (name, meta) in sent.accounts.iter().filter(|(_, m)| m.is_signer) {
+            assert!(svm.get_balance(&meta.pubkey).unwrap_or(0) > 0, "{} not funded", name);
+        }
+        // `run_case` registers its payer key first.
+        let payer = trace.touched[0];
+        let (name, _) = sent
+            .accounts
+            .iter()
+            .find(|(_, m)| m.pubkey == payer)
+            .expect("payer key assigned");
+        name.clone()
+    }
+
+    #[test]
+    fn payer_is_first_signer_wherever_it_appears() {
+        assert_eq!(assign_payer(&[("signer_a", true), ("vault", false)]), "signer_a");
+        assert_eq!(assign_payer(&[("vault", false), ("signer_a", true)]), "signer_a");
+        assert_eq!(
+            assign_payer(&[("vault", false), ("signer_a", true), ("signer_b", true)]),
+            "signer_a"
+        );
+    }
+
+    #[test]
+    fn payer_can_be_the_last_signer() {
+        assert_eq!(
+            assign_payer(&[("vault", false), ("mint", false), ("signer_a", true)]),
+            "signer_a"
+        );
+        // The other signers still sign with their own funded keys.
+        let accounts = [("signer_a", true), ("vault", false), ("signer_b", true), ("payer", true)];
+        assert_eq!(assign_payer(&accounts), "payer");
+    }
+
+    #[test]
+    fn payer_signer_after_a_pda_gets_the_payer_key_and_seeds_the_pda() {
+        // Same shape as the vault's deposit: `vault` (PDA seeded by `owner`) before the signer.
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                    { "kind": "account", "path": "owner" }
+                ]}},
+                { "name": "owner", "writable": true, "signer": true }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![]);
+        let case = new_case(&program, &ix, "base", Mutation::None, Expectation::Any);
+        let payer = Keypair::new();
+        let resolved = resolve(&LiteSVM::new(), &program, &case, &payer, &[]);
+
+        assert_eq!(resolved.pubkey_by_name["owner"], payer.pubkey());
+        assert!(resolved.signers.is_empty(), "the payer signs; no extra keypair needed");
+        let seeds = [b"vault".to_vec(), payer.pubkey().to_bytes().to_vec()];
+        let (vault, _) = find_pda(&seeds, &case.program_id);
+        assert_eq!(resolved.metas[0].pubkey, vault);
+        assert!(resolved.metas[1].is_signer);
+    }
+
+    #[test]
+    fn writable_flags_flip_one_account_at_a_time() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "account", "path": "user" }
+                ]}},
+                { "name": "user", "writable": true, "signer": true },
+                { "name": "system_program", "address": "11111111111111111111111111111111" }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![ix]);
+        let cases = generate_edge_cases(std::slice::from_ref(&program));
+        let flipped = |kind: &str| -> Vec<&EdgeCase> {
+            cases.iter().filter(|c| c.mutation.kind() == kind).collect()
+        };
+        let labels = |cases: &[&EdgeCase]| -> Vec<String> {
+            cases.iter().map(|c| c.mutation.label()).collect()
+        };
+        let readonly = flipped("not_writable");
+        assert_eq!(labels(&readonly), ["not_writable:vault", "not_writable:user"]);
+        assert!(readonly.iter().all(|c| c.expectation == Expectation::MustFail));
+        let writable = flipped("unexpected_writable");
+        assert_eq!(labels(&writable), ["unexpected_writable:system_program"]);
+        assert_eq!(writable[0].expectation, Expectation::Any);
+
+        // The downgraded signer can't be the fee payer, which is always writable.
+        let payer = Keypair::new();
+        let resolved = resolve(&LiteSVM::new(), &program, readonly[1], &payer, &[]);
+        let user = &resolved.metas[1];
+        assert!(user.is_signer && !user.is_writable);
+        assert_ne!(user.pubkey, payer.pubkey());
+        assert!(resolved.metas[0].is_writable);
+
+        let resolved = resolve(&LiteSVM::new(), &program, writable[0], &payer, &[]);
+        assert!(resolved.metas.iter().all(|m| m.is_writable));
+    }
+
+    #[test]
+    fn wrong_owner_skips_accounts_at_fixed_addresses() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true },
+                { "name": "user", "writable": true, "signer": true },
+                { "name": "system_program" },
+                { "name": "oracle", "address": "SysvarC1ock11111111111111111111111111111111" }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let labels: Vec<String> = generate_edge_cases(&[program_with(vec![ix])])
+            .into_iter()
+            .filter(|c| c.mutation.kind() == "wrong_owner")
+            .map(|c| c.mutation.label())
+            .collect();
+        assert_eq!(labels, ["wrong_owner:vault"]);
+    }
+
+    #[test]
+    fn cases_without_a_deployed_program_are_skipped_as_binary_invalid() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [{ "name": "user", "writable": true, "signer": true }],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![ix]);
+        let cases = generate_edge_cases(std::slice::from_ref(&program));
+        let opts = TestOptions {
+            fail_fast: true,
+            ..TestOptions::default()
+        };
+        let (executed, skipped) = execute_edge_cases(
+            &[program],
+            &[],
+            &cases,
+            &Classifier::builtin(),
+            &opts,
+            Harness::default(),
+        );
+        assert!(executed.is_empty());
+        assert_eq!(skipped.len(), cases.len());
+        assert!(skipped.values().all(|r| *r == SkipReason::ProgramBinaryInvalid));
+    }
+
+    #[test]
+    fn wrong_owner_must_fail_only_for_accounts_of_a_known_type() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "transfer",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "destination_vault", "writable": true },
+                { "name": "recipient" },
+                { "name": "user", "signer": true }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let mut program = program_with(vec![ix]);
+        program.account_types.push(crate::types::AccountTypeSpec {
+            name: "Vault".to_string(),
+            discriminator: vec![0; 8],
+            fields: Vec::new(),
+        });
+        let expectations: Vec<(String, Expectation)> = generate_edge_cases(&[program])
+            .into_iter()
+            .filter(|c| c.mutation.kind() == "wrong_owner")
+            .map(|c| (c.mutation.label(), c.expectation))
+            .collect();
+        assert_eq!(
+            expectations,
+            [
+                ("wrong_owner:destination_vault".to_string(), Expectation::MustFail),
+                ("wrong_owner:recipient".to_string(), Expectation::Any),
+            ]
+        );
+    }
+
+    #[test]
+    fn payer_prefers_conventional_names() {
+        assert_eq!(
+            assign_payer(&[("mint", true), ("vault", false), ("authority", true)]),
+            "authority"
+        );
+        assert_eq!(assign_payer(&[("authority", true), ("payer", true)]), "payer");
+    }
+
+    #[test]
+    fn fund_writable_creates_only_plain_writable_accounts() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "settle",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "receiver", "writable": true },
+                { "name": "config" },
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] }
+                ]}},
+                { "name": "user", "writable": true, "signer": true },
+                { "name": "system_program" }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![ix.clone()]);
+        let case = new_case(&program, &ix, "base", Mutation::None, Expectation::Any);
+        let mut svm = LiteSVM::new();
+        let resolved = resolve(&svm, &program, &case, &Keypair::new(), &[]);
+        fund_writable_accounts(&mut svm, &case, &resolved).unwrap();
+
+        let receiver = svm.get_account(&resolved.pubkey_by_name["receiver"]).unwrap();
+        assert_eq!(receiver.owner, SYSTEM_PROGRAM);
+        assert_eq!(receiver.lamports, svm.minimum_balance_for_rent_exemption(0));
+        for name in ["config", "vault", "user"] {
+            let funded = svm.get_account(&resolved.pubkey_by_name[name]);
+            assert!(funded.is_none_or(|a| a.lamports == 0), "{} was created", name);
+        }
+    }
+
+    #[test]
+    fn missing_pdas_of_known_types_are_fabricated_with_their_discriminator() {
+        let pda = |seed: &str| json!({ "seeds": [{ "kind": "const", "value": seed.as_bytes() }] });
+        let idl = json!({
+            "instructions": [{
+                "name": "withdraw",
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [
+                    { "name": "vault", "writable": true, "pda": pda("vault") },
+                    { "name": "destination_vault", "writable": true, "pda": pda("destination") },
+                    { "name": "receipt", "writable": true, "pda": pda("receipt") },
+                    { "name": "user", "writable": true, "signer": true }
+                ],
+                "args": []
+            }],
+            "accounts": [{ "name": "Vault", "discriminator": [211, 8, 232, 43, 2, 152, 117, 119] }],
+            "types": [{ "name": "Vault", "type": { "kind": "struct", "fields": [
+                { "name": "authority", "type": "pubkey" },
+                { "name": "balance", "type": "u64" }
+            ]}}]
+        });
+        let program = crate::specs::program_from_idl(
+            &idl,
+            "vault.json".to_string(),
+            crate::types::IdlFormat::Modern,
+            "0.1.0".to_string(),
+            Address::new_from_array([7; 32]),
+            std::path::PathBuf::new(),
+        );
+        let ix = program.instructions[0].clone();
+        let mut svm = LiteSVM::new();
+        let fabricate = |svm: &mut LiteSVM, mutation: Mutation| {
+            let case = new_case(&program, &ix, "base", mutation, Expectation::Any);
+            let resolved = resolve(svm, &program, &case, &Keypair::new(), &[]);
+            let mut trace = CaseTrace::default();
+            let style = ArgStyle::default();
+            fabricate_accounts(svm, &program, &case, &resolved, style, 5_000, &mut trace).unwrap();
+            (resolved, trace.fabricated)
+        };
+
+        // `destination_vault` is a `Vault` too; `receipt` has no IDL account type.
+        let (resolved, fabricated) = fabricate(&mut svm, Mutation::None);
+        assert_eq!(fabricated, ["vault", "destination_vault"]);
+        let vault = svm.get_account(&resolved.pubkey_by_name["vault"]).unwrap();
+        assert_eq!(vault.owner, program.program_id);
+        assert_eq!(vault.data.len(), 8 + 32 + 8);
+        assert_eq!(vault.data[..8], [211, 8, 232, 43, 2, 152, 117, 119]);
+        assert_eq!(vault.lamports, svm.minimum_balance_for_rent_exemption(48));
+
+        // An existing account is kept, and a mutation's own target is left to the mutation.
+        let (_, fabricated) = fabricate(&mut svm, Mutation::None);
+        assert!(fabricated.is_empty());
+        svm.set_account(resolved.pubkey_by_name["vault"], Account::default()).unwrap();
+        let target = Mutation::Uninitialized {
+            account: "vault".to_string(),
+        };
+        assert!(fabricate(&mut svm, target).1.is_empty());
+    }
+
+    #[test]
+    fn corrupt_seed_cases_break_one_seed_at_a_time() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                    { "kind": "account", "path": "user" }
+                ]}},
+                { "name": "user", "writable": true, "signer": true }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![ix.clone()]);
+        let suffixes: Vec<String> = generate_edge_cases(std::slice::from_ref(&program))
+            .into_iter()
+            .filter(|c| c.mutation.kind() == "corrupt_seed")
+            .map(|c| c.display_name.trim_start_matches("vault.json_deposit_").to_string())
+            .collect();
+        assert_eq!(suffixes, ["corrupt_seed_vault_0_const", "corrupt_seed_vault_1_account"]);
+
+        let svm = LiteSVM::new();
+        let corrupt = |seed_index| {
+            let mutation = Mutation::CorruptSeed {
+                account: "vault".to_string(),
+                seed_index,
+            };
+            let case = new_case(&program, &ix, "corrupt", mutation, Expectation::Any);
+            resolve(&svm, &program, &case, &Keypair::new(), &[]).pubkey_by_name
+        };
+        let vault_of = |prefix: &[u8], user: Address| {
+            find_pda(&[prefix.to_vec(), user.to_bytes().to_vec()], &program.program_id).0
+        };
+
+        // `vault` with its first byte flipped, still seeded by the real user.
+        let keys = corrupt(0);
+        assert_eq!(keys["vault"], vault_of(&[118 ^ 0xff, 97, 117, 108, 116], keys["user"]));
+        // The right prefix, but some other user's key.
+        let keys = corrupt(1);
+        assert_ne!(keys["vault"], vault_of(b"vault", keys["user"]));
+    }
+
+    #[test]
+    fn non_canonical_bump_passes_a_valid_pda_off_the_canonical_bump() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                    { "kind": "account", "path": "user" }
+                ]}},
+                { "name": "user", "writable": true, "signer": true }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![ix]);
+        let cases = generate_edge_cases(std::slice::from_ref(&program));
+        let case = cases
+            .iter()
+            .find(|c| c.mutation.kind() == "non_canonical_bump")
+            .unwrap();
+        assert_eq!(case.mutation.label(), "non_canonical_bump:vault");
+
+        let resolved = resolve(&LiteSVM::new(), &program, case, &Keypair::new(), &[]);
+        let swap = resolved.non_canonical_bump.clone().unwrap();
+        let seeds = [b"vault".to_vec(), resolved.pubkey_by_name["user"].to_bytes().to_vec()];
+        assert_eq!((swap.expected, swap.canonical_bump), find_pda(&seeds, &program.program_id));
+        assert!(swap.bump < swap.canonical_bump);
+        assert_ne!(swap.substituted, swap.expected);
+        assert_eq!(resolved.pubkey_by_name["vault"], swap.substituted);
+        // The substitute really is the PDA of those seeds at that bump.
+        let program_id = anchor_lang::prelude::Pubkey::new_from_array(program.program_id.to_bytes());
+        let with_bump = [seeds[0].as_slice(), seeds[1].as_slice(), &[swap.bump]];
+        let pda = anchor_lang::prelude::Pubkey::create_program_address(&with_bump, &program_id);
+        assert_eq!(pda.unwrap().to_bytes(), swap.substituted.to_bytes());
+    }
+
+    #[test]
+    fn foreign_pda_cases_pass_another_users_vault_as_theirs() {
+        let idl = json!({
+            "instructions": [{
+                "name": "withdraw",
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [
+                    { "name": "vault", "writable": true, "pda": { "seeds": [
+                        { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                        { "kind": "account", "path": "user" }
+                    ]}},
+                    { "name": "config", "pda": { "seeds": [
+                        { "kind": "const", "value": [1] }
+                    ]}},
+                    { "name": "user", "writable": true, "signer": true }
+                ],
+                "args": []
+            }],
+            "accounts": [{ "name": "Vault", "discriminator": [211, 8, 232, 43, 2, 152, 117, 119] }],
+            "types": [{ "name": "Vault", "type": { "kind": "struct", "fields": [
+                { "name": "authority", "type": "pubkey" },
+                { "name": "balance", "type": "u64" }
+            ]}}]
+        });
+        let program = crate::specs::program_from_idl(
+            &idl,
+            "vault.json".to_string(),
+            crate::types::IdlFormat::Modern,
+            "0.1.0".to_string(),
+            Address::new_from_array([7; 32]),
+            std::path::PathBuf::new(),
+        );
+
+        // Only the PDA seeded by a signer can belong to someone else.
+        let cases = generate_edge_cases(std::slice::from_ref(&program));
+        let foreign: Vec<_> = cases.iter().filter(|c| c.mutation.kind() == "foreign_pda").collect();
+        assert_eq!(foreign.len(), 1);
+        assert_eq!(foreign[0].mutation.label(), "foreign_pda:vault");
+        assert_eq!(foreign[0].expectation, Expectation::MustFail);
+
+        let mut svm = LiteSVM::new();
+        let resolved = resolve(&svm, &program, foreign[0], &Keypair::new(), &[]);
+        let f = resolved.foreign_pda.clone().unwrap();
+        let user = resolved.pubkey_by_name["user"];
+        let program_id = program.program_id;
+        let vault_of =
+            |key: Address| find_pda(&[b"vault".to_vec(), key.to_bytes().to_vec()], &program_id).0;
+        assert_eq!(f.expected, vault_of(user));
+        assert_eq!(f.substituted, vault_of(f.owner));
+        assert_eq!(resolved.pubkey_by_name["vault"], f.substituted);
+
+        let style = ArgStyle::default();
+        prepare_accounts(&mut svm, &program, foreign[0], &resolved, style, 7).unwrap();
+        let theirs = svm.get_account(&f.substituted).unwrap();
+        assert_eq!(theirs.owner, program_id);
+        assert_eq!(theirs.data[8..40], f.owner.to_bytes());
+        assert_eq!(theirs.data[40..48], 7u64.to_le_bytes());
+    }
+
+    #[test]
+    fn wrong_authority_signs_as_a_stranger_against_the_owners_state() {
+        let idl = json!({
+            "instructions": [{
+                "name": "withdraw",
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [
+                    { "name": "vault", "writable": true, "pda": { "seeds": [
+                        { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                        { "kind": "account", "path": "user" }
+                    ]}},
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "fee_payer", "writable": true, "signer": true }
+                ],
+                "args": []
+            }],
+            "accounts": [{ "name": "Vault", "discriminator": [211, 8, 232, 43, 2, 152, 117, 119] }],
+            "types": [{ "name": "Vault", "type": { "kind": "struct", "fields": [
+                { "name": "authority", "type": "pubkey" }
+            ]}}]
+        });
+        let program = crate::specs::program_from_idl(
+            &idl,
+            "vault.json".to_string(),
+            crate::types::IdlFormat::Modern,
+            "0.1.0".to_string(),
+            Address::new_from_array([7; 32]),
+            std::path::PathBuf::new(),
+        );
+
+        // `fee_payer` neither seeds a PDA nor is named like an owner.
+        let cases = generate_edge_cases(std::slice::from_ref(&program));
+        let labels: Vec<String> = cases
+            .iter()
+            .filter(|c| c.mutation.kind() == "wrong_authority")
+            .map(|c| c.mutation.label())
+            .collect();
+        assert_eq!(labels, ["wrong_authority:user"]);
+
+        let case = cases.iter().find(|c| c.mutation.kind() == "wrong_authority").unwrap();
+        let mut svm = LiteSVM::new();
+        let resolved = resolve(&svm, &program, case, &Keypair::new(), &[]);
+        let swap = resolved.wrong_authority.clone().unwrap();
+        assert_eq!(resolved.pubkey_by_name["user"], swap.signer);
+        assert_ne!(swap.signer, swap.authority);
+        let owners_vault =
+            find_pda(&[b"vault".to_vec(), swap.authority.to_bytes().to_vec()], &program.program_id);
+        assert_eq!(resolved.pubkey_by_name["vault"], owners_vault.0);
+        assert!(resolved.signers.iter().any(|kp| kp.pubkey() == swap.signer));
+
+        // State fabricated for the case belongs to the owner, not to whoever signs.
+        let mut trace = CaseTrace::default();
+        let style = ArgStyle::default();
+        fabricate_accounts(&mut svm, &program, case, &resolved, style, 0, &mut trace).unwrap();
+        let vault = svm.get_account(&owners_vault.0).unwrap();
+        assert_eq!(vault.data[8..40], swap.authority.to_bytes());
+    }
+
+    #[test]
+    fn fabricated_accounts_belong_to_the_signer_and_carry_the_configured_balance() {
+        let idl = json!({
+            "instructions": [{
+                "name": "withdraw",
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [
+                    { "name": "vault", "writable": true,
+                      "pda": { "seeds": [{ "kind": "const", "value": [1] }] } },
+                    { "name": "user", "writable": true, "signer": true }
+                ],
+                "args": []
+            }],
+            "accounts": [{ "name": "Vault", "discriminator": [211, 8, 232, 43, 2, 152, 117, 119] }],
+            "types": [{ "name": "Vault", "type": { "kind": "struct", "fields": [
+                { "name": "authority", "type": "pubkey" },
+                { "name": "balance", "type": "u64" },
+                { "name": "bump", "type": "u8" }
+            ]}}]
+        });
+        let program = crate::specs::program_from_idl(
+            &idl,
+            "vault.json".to_string(),
+            crate::types::IdlFormat::Modern,
+            "0.1.0".to_string(),
+            Address::new_from_array([7; 32]),
+            std::path::PathBuf::new(),
+        );
+        let ix = program.instructions[0].clone();
+        let fabricate = |mutation: Mutation| {
+            let mut svm = LiteSVM::new();
+            let case = new_case(&program, &ix, "base", mutation, Expectation::Any);
+            let resolved = resolve(&svm, &program, &case, &Keypair::new(), &[]);
+            let mut trace = CaseTrace::default();
+            let style = ArgStyle::default();
+            fabricate_accounts(&mut svm, &program, &case, &resolved, style, 5_000, &mut trace)
+                .unwrap();
+            let vault = svm.get_account(&resolved.pubkey_by_name["vault"]).unwrap();
+            (resolved, vault.data, trace.fabricated_fields)
+        };
+
+        let (resolved, data, fields) = fabricate(Mutation::None);
+        let user = resolved.pubkey_by_name["user"];
+        assert_eq!(data[8..40], user.to_bytes());
+        assert_eq!(data[40..48], 5_000u64.to_le_bytes());
+        assert_eq!(data[48], 0);
+        let field = |field: &str, value: String| FabricatedField {
+            account: "vault".to_string(),
+            field: field.to_string(),
+            value,
+        };
+        assert_eq!(
+            fields,
+            vec![field("authority", user.to_string()), field("balance", "5000".to_string())]
+        );
+
+        // A substituted signer doesn't get to own the account.
+        let (_, data, fields) = fabricate(Mutation::WrongSigner {
+            account: "user".to_string(),
+        });
+        assert!(data[8..40].iter().all(|b| *b == 0));
+        assert_eq!(fields, vec![field("balance", "5000".to_string())]);
+    }
+
+    #[test]
+    fn fixtures_seed_named_accounts_under_the_case_keys() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "withdraw",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] }
+                ]}},
+                { "name": "user", "writable": true, "signer": true },
+                { "name": "system_program" }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![ix.clone()]);
+        let vault = crate::types::AccountFixture {
+            lamports: 5_000_000,
+            owner: program.program_id,
+            data: vec![1, 2, 3],
+        };
+        let system = crate::types::AccountFixture {
+            lamports: 1,
+            owner: program.program_id,
+            data: Vec::new(),
+        };
+        let fixtures = AccountFixtures::from([
+            ("vault".to_string(), vault.clone()),
+            ("system_program".to_string(), system),
+        ]);
+
+        let case = new_case(&program, &ix, "base", Mutation::None, Expectation::Any);
+        let mut svm = LiteSVM::new();
+        let resolved = resolve(&svm, &program, &case, &Keypair::new(), &[]);
+        seed_fixtures(&mut svm, &case, &resolved, &fixtures).unwrap();
+        let seeded = svm.get_account(&resolved.pubkey_by_name["vault"]).unwrap();
+        assert_eq!(
+            (seeded.lamports, seeded.owner, seeded.data),
+            (vault.lamports, vault.owner, vault.data)
+        );
+        // Fixed addresses keep their real account.
+        let system_program = svm.get_account(&SYSTEM_PROGRAM).unwrap();
+        assert!(system_program.executable);
+    }
+
+    #[test]
+    fn arg_seeds_use_the_encoded_instruction_arg() {
+        // seeds = [b"vault", index.to_le_bytes()]
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "open",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                    { "kind": "arg", "path": "index" }
+                ]}},
+                { "name": "user", "writable": true, "signer": true }
+            ],
+            "args": [{ "name": "index", "type": "u64" }]
+        }))
+        .unwrap();
+        let program = program_with(vec![ix.clone()]);
+        let case = new_case(&program, &ix, "base", Mutation::None, Expectation::Any);
+        let args = encode_args(&ix, &program.types, ArgStyle::default()).unwrap();
+        let svm = LiteSVM::new();
+        let metas = resolve(&svm, &program, &case, &Keypair::new(), &args).metas;
+
+        let program_id =
+            anchor_lang::prelude::Pubkey::new_from_array(program.program_id.to_bytes());
+        let (expected, _) = anchor_lang::prelude::Pubkey::find_program_address(
+            &[b"vault", &0u64.to_le_bytes()],
+            &program_id,
+        );
+        assert_eq!(metas[0].pubkey.to_bytes(), expected.to_bytes());
+    }
+
+    #[test]
+    fn dotted_account_seeds_read_the_field_from_on_chain_data() {
+        // seeds = [b"escrow", vault.authority]
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "settle",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true },
+                { "name": "escrow", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [101, 115, 99, 114, 111, 119] },
+                    { "kind": "account", "path": "vault.authority" }
+                ]}},
+                { "name": "user", "writable": true, "signer": true }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let mut program = program_with(vec![ix.clone()]);
+        program.account_types.push(crate::types::AccountTypeSpec {
+            name: "Vault".to_string(),
+            discriminator: vec![0; 8],
+            fields: vec![
+                crate::types::FieldSpec {
+                    name: "balance".to_string(),
+                    ty: json!("u64"),
+                },
+                crate::types::FieldSpec {
+                    name: "authority".to_string(),
+                    ty: json!("pubkey"),
+                },
+            ],
+        });
+        let case = new_case(&program, &ix, "base", Mutation::None, Expectation::Any);
+
+        // No on-chain data yet: the escrow PDA is reported instead of guessed.
+        let mut svm = LiteSVM::new();
+        let resolved = resolve(&svm, &program, &case, &Keypair::new(), &[]);
+        assert_eq!(resolved.unresolved.len(), 1);
+        assert_eq!(resolved.unresolved[0].seed, "account:vault.authority");
+
+        // Pin the vault key through its declared address and give it data.
+        let vault_key = Address::new_from_array([9; 32]);
+        let authority = [5u8; 32];
+        let mut case = case;
+        case.instruction.accounts[0].address = Some(vault_key);
+        let data = [vec![0u8; 16], authority.to_vec()].concat();
+        svm.set_account(
+            vault_key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program.program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        let resolved = resolve(&svm, &program, &case, &Keypair::new(), &[]);
+        assert!(resolved.unresolved.is_empty());
+
+        let program_id =
+            anchor_lang::prelude::Pubkey::new_from_array(program.program_id.to_bytes());
+        let (expected, _) = anchor_lang::prelude::Pubkey::find_program_address(
+            &[b"escrow", &authority],
+            &program_id,
+        );
+        assert_eq!(resolved.metas[1].pubkey.to_bytes(), expected.to_bytes());
+    }
+
+    #[test]
+    fn non_initializers_run_the_programs_initializers_first() {
+        let program = program_with(vec![
+            ix("initialize_vault", vec![]),
+            ix("deposit", vec![]),
+            ix("init_config", vec![]),
+        ]);
+        let chains: Vec<(String, Vec<String>)> = generate_edge_cases(&[program])
+            .into_iter()
+            .filter(|c| matches!(c.mutation, Mutation::None))
+            .map(|c| (c.instruction.name, c.setup_chain))
+            .collect();
+        assert_eq!(chains[0].1, Vec::<String>::new());
+        assert_eq!(chains[1].1, vec!["initialize_vault", "init_config"]);
+        assert!(chains[2].1.is_empty());
+    }
+
+    #[test]
+    fn uninitialized_cases_target_pdas_an_initializer_creates() {
+        let parse = |name: &str, pdas: &[&str]| {
+            let accounts: Vec<Value> = pdas
+                .iter()
+                .map(|n| {
+                    json!({ "name": n, "writable": true, "pda": { "seeds": [
+                        { "kind": "const", "value": n.as_bytes() }
+                    ]}})
+                })
+                .chain([json!({ "name": "user", "writable": true, "signer": true })])
+                .collect();
+            crate::specs::parse_instruction(&json!({
+                "name": name,
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": accounts,
+                "args": []
+            }))
+            .unwrap()
+        };
+        let program = program_with(vec![
+            parse("initialize_vault", &["vault"]),
+            parse("deposit", &["vault", "receipt"]),
+        ]);
+        let targets: Vec<(String, String)> = generate_edge_cases(&[program])
+            .into_iter()
+            .filter_map(|c| match c.mutation {
+                Mutation::Uninitialized { account } => {
+                    assert_eq!(c.expectation, Expectation::MustFail);
+                    Some((c.instruction.name, account))
+                }
+                _ => None,
+            })
+            .collect();
+        // The initializer's own PDA and a PDA no initializer creates are left out.
+        assert_eq!(targets, vec![("deposit".to_string(), "vault".to_string())]);
+    }
+
+    #[test]
+    fn double_init_runs_only_for_initializers_creating_a_pda() {
+        let parse = |name: &str, pda: bool| {
+            let mut vault = json!({ "name": "vault", "writable": true });
+            if pda {
+                let seed = json!({ "kind": "const", "value": [118, 97, 117, 108, 116] });
+                vault["pda"] = json!({ "seeds": [seed] });
+            }
+            crate::specs::parse_instruction(&json!({
+                "name": name,
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [vault, { "name": "user", "writable": true, "signer": true }],
+                "args": []
+            }))
+            .unwrap()
+        };
+        let program = program_with(vec![
+            parse("initialize_vault", true),
+            parse("init_keyless", false),
+            parse("deposit", true),
+        ]);
+        let cases: Vec<EdgeCase> = generate_edge_cases(&[program])
+            .into_iter()
+            .filter(|c| matches!(c.mutation, Mutation::DoubleInit))
+            .collect();
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].instruction.name, "initialize_vault");
+        assert!(cases[0].setup_chain.is_empty());
+        assert_eq!(
+            cases[0].expectation,
+            Expectation::MustFailWith {
+                anchor_code: None,
+                contains: Some("already in use".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn keyring_keeps_signer_keys_stable_across_setup_and_case() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "payer", "writable": true, "signer": true },
+                { "name": "owner", "signer": true }
+            ],
+            "args": []
+        }))
+        .unwrap();
+        let program = program_with(vec![ix.clone()]);
+        let case = new_case(&program, &ix, "base", Mutation::None, Expectation::Any);
+        let svm = LiteSVM::new();
+        let payer = Keypair::new();
+        let mut keyring = HashMap::new();
+        let first = build_accounts(&svm, &program, &case, &payer, &mut keyring, &[]).unwrap();
+        let second = build_accounts(&svm, &program, &case, &payer, &mut keyring, &[]).unwrap();
+        assert_eq!(first.pubkey_by_name["owner"], second.pubkey_by_name["owner"]);
+        assert_eq!(second.signers[0].pubkey(), second.pubkey_by_name["owner"]);
+    }
+
+    #[test]
+    fn case_ids_survive_reordering_and_renaming_the_idl() {
+        let idl: Value = serde_json::from_str(include_str!(
+            "../../../tests/fixtures/idl/test_vault.modern.json"
+        ))
+        .unwrap();
+        let ids = |idl: &Value, file: &str| {
+            let program = crate::specs::program_from_idl(
+                idl,
+                file.to_string(),
+                crate::types::IdlFormat::Modern,
+                "0.1.0".to_string(),
+                Address::new_from_array([7; 32]),
+                std::path::PathBuf::from("vault.so"),
+            );
+            let mut ids: Vec<String> =
+                generate_edge_cases(&[program]).into_iter().map(|c| c.id).collect();
+            ids.sort();
+            ids
+        };
+
+        let mut permuted = idl.clone();
+        permuted["instructions"].as_array_mut().unwrap().reverse();
+        for ix in permuted["instructions"].as_array_mut().unwrap() {
+            ix["accounts"].as_array_mut().unwrap().reverse();
+        }
+        let original = ids(&idl, "test_vault.json");
+        assert!(!original.is_empty());
+        assert_eq!(ids(&permuted, "vault_v2.json"), original);
+        // Ids are unique and keep a readable prefix.
+        assert_eq!(original.iter().collect::<HashSet<_>>().len(), original.len());
+        assert!(original.iter().any(|id| id.starts_with("withdraw_wrong_pda_vault_")));
+    }
+
+    #[test]
+    fn every_generated_mutation_kind_is_filterable() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] }
+                ]}},
+                { "name": "user", "writable": true, "signer": true },
+                { "name": "system_program", "address": "11111111111111111111111111111111" }
+            ],
+            "args": [
+                { "name": "amount", "type": { "option": "u64" } },
+                { "name": "nonce", "type": "i8" }
+            ]
+        }))
+        .unwrap();
+        for case in generate_edge_cases(&[program_with(vec![ix])]) {
+            let kind = case.mutation.kind();
+            assert!(
+                crate::types::MUTATION_KINDS.contains(&kind.as_str()),
+                "{}",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn base_cases_must_succeed_only_when_fully_resolvable() {
+        let vault_ix = |name: &str, seed: serde_json::Value| {
+            crate::specs::parse_instruction(&json!({
+                "name": name,
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [
+                    { "name": "vault", "writable": true, "pda": { "seeds": [
+                        { "kind": "const", "value": [118, 97, 117, 108, 116] }, seed
+                    ]}},
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "system_program" }
+                ],
+                "args": []
+            }))
+            .unwrap()
+        };
+        let user_seed = json!({ "kind": "account", "path": "user" });
+        let expectations = |ixs: Vec<InstructionSpec>| -> Vec<&'static str> {
+            generate_edge_cases(&[program_with(ixs)])
+                .into_iter()
+                .filter(|c| matches!(c.mutation, Mutation::None))
+                .map(|c| c.expectation.label())
+                .collect()
+        };
+
+        let vault = vec![
+            vault_ix("initialize_vault", user_seed.clone()),
+            vault_ix("deposit", user_seed.clone()),
+        ];
+        assert_eq!(expectations(vault), vec!["must_succeed", "must_succeed"]);
+
+        // No initializer to create the vault first.
+        assert_eq!(expectations(vec![vault_ix("deposit", user_seed)]), vec!["any"]);
+
+        // Dotted seeds depend on on-chain data.
+        let dotted = json!({ "kind": "account", "path": "vault.authority" });
+        assert_eq!(
+            expectations(vec![vault_ix("initialize_vault", dotted)]),
+            vec!["any"]
+        );
+    }
+
+    #[test]
+    fn long_logs_are_capped_with_a_marker() {
+        let logs: Vec<String> = (0..MAX_LOG_LINES + 3).map(|i| i.to_string()).collect();
+        let capped = cap_logs(&logs);
+        assert_eq!(capped.len(), MAX_LOG_LINES + 1);
+        assert_eq!(capped[MAX_LOG_LINES], "... 3 more log lines truncated");
+        assert_eq!(cap_logs(&logs[..2]), logs[..2].to_vec());
+    }
+
+    #[test]
+    fn truncate_data_only_generated_for_instructions_with_args() {
+        let amount = crate::types::ArgSpec {
+            name: "amount".to_string(),
+            ty: json!("u64"),
+        };
+        let program =
+            program_with(vec![ix("initialize_vault", vec![]), ix("deposit", vec![amount])]);
+        let truncated: Vec<String> = generate_edge_cases(&[program])
+            .into_iter()
+            .filter(|c| matches!(c.mutation, Mutation::TruncateData))
+            .map(|c| c.instruction.name)
+            .collect();
+        assert_eq!(truncated, vec!["deposit".to_string()]);
+    }
+
+    #[test]
+    fn truncate_data_keeps_the_discriminator_intact() {
+        let mut with_args = [vec![1, 2, 3, 4, 5, 6, 7, 8], vec![0; 8]].concat();
+        mutate_data(&mut with_args, 8, &Mutation::TruncateData);
+        assert_eq!(with_args.len(), 15);
+        assert_eq!(&with_args[..8], &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut no_args = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        mutate_data(&mut no_args, 8, &Mutation::TruncateData);
+        assert_eq!(no_args, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn option_encodes_none_by_default_and_some_on_request() {
+        let ty = json!({ "option": "u32" });
+        assert_eq!(encode_arg_zero(&ty, &HashMap::new()).unwrap(), vec![0]);
+        let some = ArgStyle {
+            some_options: true,
+            ..ArgStyle::default()
+        };
+        assert_eq!(encode_arg(&ty, &HashMap::new(), some).unwrap(), vec![1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn max_encoding_matches_integer_maximums() {
+        let types = HashMap::new();
+        assert_eq!(
+            encode_arg_max(&json!("u64"), &types).unwrap(),
+            u64::MAX.to_le_bytes().to_vec()
+        );
+        assert_eq!(
+            encode_arg_max(&json!("i64"), &types).unwrap(),
+            i64::MAX.to_le_bytes().to_vec()
+        );
+        assert_eq!(
+            encode_arg_max(&json!("i128"), &types).unwrap(),
+            i128::MAX.to_le_bytes().to_vec()
+        );
+        // Non-integers keep their default encoding.
+        assert_eq!(encode_arg_max(&json!("pubkey"), &types).unwrap(), vec![0; 32]);
+
+        let arg = |name: &str, ty: Value| crate::types::ArgSpec {
+            name: name.to_string(),
+            ty,
+        };
+        let with_int = program_with(vec![ix("deposit", vec![arg("amount", json!("u64"))])]);
+        let without = program_with(vec![ix("set_key", vec![arg("key", json!("pubkey"))])]);
+        let has_max = |p: &ProgramSpec| {
+            generate_edge_cases(std::slice::from_ref(p))
+                .iter()
+                .any(|c| matches!(c.mutation, Mutation::MaxArgs))
+        };
+        assert!(has_max(&with_int));
+        assert!(!has_max(&without));
+    }
+
+    #[test]
+    fn huge_amount_maxes_only_amount_args_of_programs_with_declared_errors() {
+        let arg = |name: &str, ty: Value| crate::types::ArgSpec {
+            name: name.to_string(),
+            ty,
+        };
+        let withdraw = ix(
+            "withdraw",
+            vec![arg("amount", json!("u64")), arg("nonce", json!("u8"))],
+        );
+        let mut program = program_with(vec![ix("initialize_vault", vec![]), withdraw.clone()]);
+        let huge = |p: &ProgramSpec| -> Vec<String> {
+            generate_edge_cases(std::slice::from_ref(p))
+                .into_iter()
+                .filter(|c| matches!(c.mutation, Mutation::HugeAmount))
+                .map(|c| c.instruction.name)
+                .collect()
+        };
+        assert!(huge(&program).is_empty());
+
+        program.errors.push(crate::types::ProgramError {
+            code: 6000,
+            name: "InsufficientFunds".to_string(),
+            msg: None,
+        });
+        assert_eq!(huge(&program), vec!["withdraw".to_string()]);
+        // Withdrawals must hit the declared insufficient-funds error, not just fail.
+        let case = generate_edge_cases(std::slice::from_ref(&program))
+            .into_iter()
+            .find(|c| matches!(c.mutation, Mutation::HugeAmount))
+            .unwrap();
+        assert_eq!(case.expectation, must_fail_with_code(6000));
+
+        let style = ArgStyle {
+            max_amounts: true,
+            ..ArgStyle::default()
+        };
+        let args = encode_args(&withdraw, &HashMap::new(), style).unwrap();
+        assert_eq!(args, vec![u64::MAX.to_le_bytes().to_vec(), vec![0]]);
+    }
+
+    #[test]
+    fn boundary_cases_vary_one_integer_arg_at_a_time() {
+        let arg = |name: &str, ty: &str| crate::types::ArgSpec {
+            name: name.to_string(),
+            ty: json!(ty),
+        };
+        let deposit = ix("deposit", vec![arg("amount", "u64"), arg("delta", "i16")]);
+        let cases: Vec<EdgeCase> = generate_edge_cases(&[program_with(vec![deposit.clone()])])
+            .into_iter()
+            .filter(|c| c.mutation.kind() == "boundary")
+            .collect();
+        let labels: Vec<String> = cases.iter().map(|c| c.mutation.label()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "boundary:amount=one",
+                "boundary:amount=max",
+                "boundary:delta=one",
+                "boundary:delta=max",
+                "boundary:delta=min",
+            ]
+        );
+
+        assert_eq!(
+            integer_arg_values(&cases[1]),
+            vec![
+                ("amount".to_string(), u64::MAX.to_string()),
+                ("delta".to_string(), "0".to_string())
+            ]
+        );
+        assert_eq!(integer_arg_values(&cases[4])[1].1, i16::MIN.to_string());
+
+        let mut args = encode_args(&deposit, &HashMap::new(), ArgStyle::default()).unwrap();
+        set_boundary_arg(&deposit, &mut args, "delta", Boundary::Min);
+        assert_eq!(args, vec![vec![0; 8], i16::MIN.to_le_bytes().to_vec()]);
+        assert_eq!(encode_boundary(16, true, Boundary::Max), i128::MAX.to_le_bytes());
+        assert_eq!(int_decimal(16, false, Some(Boundary::Max)), u128::MAX.to_string());
+    }
+
+    #[test]
+    fn vec_defaults_to_empty_length_prefix() {
+        let ty = json!({ "vec": "u64" });
+        assert_eq!(encode_arg_zero(&ty, &HashMap::new()).unwrap(), vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn vec_single_element_recurses_into_inner_type() {
+        let bytes = encode_arg_nonzero(&json!({ "vec": "u64" }), &HashMap::new()).unwrap();
+        assert_eq!(bytes, [vec![1, 0, 0, 0], 1u64.to_le_bytes().to_vec()].concat());
+    }
+
+    #[test]
+    fn zero_and_nonzero_encodings_round_trip_through_borsh() {
+        use anchor_lang::prelude::borsh;
+        let types = HashMap::new();
+        let cases: Vec<(Value, Vec<u8>, Vec<u8>)> = vec![
+            (
+                json!("string"),
+                borsh::to_vec(&String::new()).unwrap(),
+                borsh::to_vec(&SAMPLE_STRING.to_string()).unwrap(),
+            ),
+            (
+                json!("bytes"),
+                borsh::to_vec(&Vec::<u8>::new()).unwrap(),
+                borsh::to_vec(&vec![1u8]).unwrap(),
+            ),
+            (
+                json!({ "vec": "u8" }),
+                borsh::to_vec(&Vec::<u8>::new()).unwrap(),
+                borsh::to_vec(&vec![1u8]).unwrap(),
+            ),
+            (
+                json!({ "option": "u64" }),
+                borsh::to_vec(&None::<u64>).unwrap(),
+                borsh::to_vec(&Some(1u64)).unwrap(),
+            ),
+            (
+                json!("i32"),
+                borsh::to_vec(&0i32).unwrap(),
+                borsh::to_vec(&1i32).unwrap(),
+            ),
+            (
+                json!("bool"),
+                borsh::to_vec(&false).unwrap(),
+                borsh::to_vec(&true).unwrap(),
+            ),
+        ];
+        for (ty, zero, nonzero) in cases {
+            assert_eq!(encode_arg_zero(&ty, &types).unwrap(), zero, "zero {}", ty);
+            assert_eq!(encode_arg_nonzero(&ty, &types).unwrap(), nonzero, "nonzero {}", ty);
+        }
+
+        // Seeds take the raw bytes of a `bytes` arg, like strings.
+        let ix = ix(
+            "store",
+            vec![crate::types::ArgSpec {
+                name: "blob".to_string(),
+                ty: json!("bytes"),
+            }],
+        );
+        let args = vec![encode_arg_nonzero(&json!("bytes"), &types).unwrap()];
+        assert_eq!(arg_seed(&ix, &args, "blob"), Some(vec![1]));
+    }
+
+    #[test]
+    fn random_args_are_valid_borsh_and_reproducible_from_the_case_seed() {
+        use anchor_lang::prelude::borsh;
+        let args: Vec<crate::types::ArgSpec> = [
+            ("amount", json!("u64")),
+            ("delta", json!("i16")),
+            ("memo", json!("string")),
+            ("blob", json!("bytes")),
+            ("limit", json!({ "option": "u32" })),
+            ("owner", json!("pubkey")),
+            ("flag", json!("bool")),
+        ]
+        .into_iter()
+        .map(|(name, ty)| crate::types::ArgSpec {
+            name: name.to_string(),
+            ty,
+        })
+        .collect();
+        let program = program_with(vec![ix("configure", args)]);
+        let cases = random_arg_cases(&[program], 20, 7);
+        assert_eq!(cases.len(), 20);
+        assert_eq!(cases[3].display_name, "vault.json_configure_random_args_3");
+
+        for case in &cases {
+            let Mutation::RandomArgs { seed, args } = &case.mutation else {
+                panic!("{:?}", case.mutation);
+            };
+            assert_eq!(
+                &random_args(&case.instruction, &HashMap::new(), &mut Rng(*seed)).unwrap(),
+                args
+            );
+            // Every value has its type's exact Borsh framing, so the whole payload decodes.
+            type Decoded = (u64, i16, String, Vec<u8>, Option<u32>, [u8; 32], bool);
+            let (amount, delta, ..) =
+                <Decoded as borsh::BorshDeserialize>::try_from_slice(&args.concat()).unwrap();
+            let values = integer_arg_values(case);
+            assert_eq!(values[0], ("amount".to_string(), amount.to_string()));
+            assert_eq!(values[1], ("delta".to_string(), delta.to_string()));
+        }
+        let program = program_with(vec![]);
+        assert!(random_arg_cases(&[program], 20, 7).is_empty());
+    }
+
+    #[test]
+    fn defined_structs_encode_fields_in_order_including_nested_arrays() {
+        let mut types = HashMap::new();
+        types.insert(
+            "Point".to_string(),
+            json!({ "name": "Point", "type": { "kind": "struct", "fields": [
+                { "name": "x", "type": "u16" },
+                { "name": "y", "type": "u8" }
+            ] } }),
+        );
+        types.insert(
+            "Shape".to_string(),
+            json!({ "name": "Shape", "type": { "kind": "struct", "fields": [
+                { "name": "origin", "type": { "defined": { "name": "Point" } } },
+                { "name": "corners", "type": { "array": [{ "defined": { "name": "Point" } }, 2] } },
+                { "name": "label", "type": "string" }
+            ] } }),
+        );
+
+        let ty = json!({ "defined": { "name": "Shape" } });
+        // origin (3) + corners (2 * 3) + empty string prefix (4)
+        assert_eq!(encode_arg_zero(&ty, &types).unwrap(), vec![0; 13]);
+        let missing = json!({ "defined": { "name": "Nope" } });
+        assert!(encode_arg_zero(&missing, &types).is_err());
+    }
+
+    #[test]
+    fn idl_defined_args_match_borsh_and_recursive_types_are_rejected() {
+        use anchor_lang::prelude::{borsh, AnchorSerialize, Pubkey};
+
+        #[derive(AnchorSerialize)]
+        enum Kind {
+            Fixed,
+            #[allow(dead_code)]
+            Ranged { lo: u8, hi: u8 },
+        }
+        #[derive(AnchorSerialize)]
+        struct DepositParams {
+            amount: u64,
+            owner: Pubkey,
+            kind: Kind,
+        }
+
+        let idl = json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "metadata": { "name": "vault", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "deposit",
+                "discriminator": [242, 35, 198, 137, 82, 225, 242, 182],
+                "accounts": [],
+                "args": [{ "name": "params", "type": { "defined": { "name": "DepositParams" } } }]
+            }],
+            "types": [
+                { "name": "DepositParams", "type": { "kind": "struct", "fields": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "owner", "type": "pubkey" },
+                    { "name": "kind", "type": { "defined": { "name": "Kind" } } }
+                ] } },
+                { "name": "Kind", "type": { "kind": "enum", "variants": [
+                    { "name": "Fixed" },
+                    { "name": "Ranged", "fields": [
+                        { "name": "lo", "type": "u8" },
+                        { "name": "hi", "type": "u8" }
+                    ] }
+                ] } },
+                { "name": "Node", "type": { "kind": "struct", "fields": [
+                    { "name": "next", "type": { "option": { "defined": { "name": "Node" } } } }
+                ] } }
+            ]
+        });
+        let program = crate::specs::program_from_idl(
+            &idl,
+            "vault.json".to_string(),
+            crate::types::IdlFormat::Modern,
+            "0.1.0".to_string(),
+            Address::default(),
+            std::path::PathBuf::from("vault.so"),
+        );
+
+        let expected = borsh::to_vec(&DepositParams {
+            amount: 0,
+            owner: Pubkey::default(),
+            kind: Kind::Fixed,
+        })
+        .unwrap();
+        let args = encode_args(&program.instructions[0], &program.types, ArgStyle::default());
+        assert_eq!(args.unwrap(), vec![expected]);
+
+        let node = json!({ "defined": { "name": "Node" } });
+        assert!(encode_arg_zero(&node, &program.types)
+            .unwrap_err()
+            .contains("too deep"));
+    }
+
+    #[test]
+    fn enums_encode_selected_variant_index_and_fields() {
+        let mut types = HashMap::new();
+        types.insert(
+            "Action".to_string(),
+            json!({ "name": "Action", "type": { "kind": "enum", "variants": [
+                { "name": "Deposit", "fields": [{ "name": "amount", "type": "u32" }] },
+                { "name": "Pause" },
+                { "name": "Tag", "fields": ["u8"] }
+            ] } }),
+        );
+        let ty = json!({ "defined": { "name": "Action" } });
+
+        assert_eq!(encode_arg_zero(&ty, &types).unwrap(), vec![0, 0, 0, 0, 0]);
+        let pick = |enum_variant| ArgStyle {
+            enum_variant,
+            ..ArgStyle::default()
+        };
+        assert_eq!(encode_arg(&ty, &types, pick(1)).unwrap(), vec![1]);
+        assert_eq!(encode_arg(&ty, &types, pick(2)).unwrap(), vec![2, 0]);
+        assert!(encode_arg(&ty, &types, pick(3)).is_err());
+    }
 }
@@ -0,0 +1,506 @@
+use crate::types::{EdgeCase, Expectation};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+// Final verdict a rule assigns to a case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+}
+
+// One classification rule; every matcher that is set must hold for the rule to apply.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub case_id: Option<String>,
+    pub mutation: Option<String>,
+    pub expectation: Option<String>,
+    pub success: Option<bool>,
+    pub error_code: Option<u32>,
+    pub error_contains: Option<String>,
+    // Whether the error satisfies the case's MustFailWith expectation (always true otherwise).
+    pub error_matches: Option<bool>,
+    // Whether a LogContains case found its substring (never matches other cases).
+    pub log_matched: Option<bool>,
+    // Whether every assertion of an AccountState case held (never matches other cases).
+    pub state_matched: Option<bool>,
+    // Whether the transaction failed in the VM/runtime without a program error code.
+    pub vm_error: Option<bool>,
+    pub verdict: Verdict,
+}
+
+// Observed outcome of one executed case, as seen by the classifier.
+#[derive(Debug)]
+pub struct CaseOutcome<'a> {
+    pub case: &'a EdgeCase,
+    pub mutation: &'a str,
+    pub actual_success: bool,
+    pub error: Option<&'a str>,
+    // Set for LogContains cases: whether a log line contained the substring.
+    pub log_matched: Option<bool>,
+    // Set for AccountState cases: whether every asserted slice held its expected bytes.
+    pub state_matched: Option<bool>,
+}
+
+// Ordered rule set: user rules first, then built-ins. First match wins.
+#[derive(Debug)]
+pub struct Classifier {
+    rules: Vec<Rule>,
+}
+
+impl Classifier {
+    // Classifier with only the built-in expectation rules.
+    pub fn builtin() -> Self {
+        Self {
+            rules: builtin_rules(),
+        }
+    }
+
+    // Load user rules from a JSON rules file and append the built-ins after them.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed reading {}", path.display()))?;
+        let doc: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+
+        let entries = match doc["rules"].as_array() {
+            Some(v) => v,
+            None => bail!("{} must contain a top-level `rules` array", path.display()),
+        };
+
+        let mut rules = Vec::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            rules.push(
+                parse_rule(entry)
+                    .with_context(|| format!("Invalid rule #{} in {}", i, path.display()))?,
+            );
+        }
+        rules.extend(builtin_rules());
+        Ok(Self { rules })
+    }
+
+    // Number of user-supplied rules (built-ins excluded).
+    pub fn user_rule_count(&self) -> usize {
+        self.rules
+            .iter()
+            .filter(|r| !r.name.starts_with("builtin:"))
+            .count()
+    }
+
+    // Map an outcome to (passed, matched rule name).
+    pub fn classify(&self, outcome: &CaseOutcome) -> (bool, String) {
+        for rule in &self.rules {
+            if rule_matches(rule, outcome) {
+                return (rule.verdict == Verdict::Pass, rule.name.clone());
+            }
+        }
+        // Built-ins cover every expectation, so this is only reached for new variants.
+        (false, "unclassified".to_string())
+    }
+}
+
+// Default behaviour: Any always passes, MustFail passes only when the transaction failed (with
+// the expected error, for MustFailWith), MustSucceed only when it succeeded, LogContains
+// only when the substring was logged and AccountState only when it succeeded with the asserted
+// state.
+fn builtin_rules() -> Vec<Rule> {
+    let rule = |name: &str, expectation: &str, success: Option<bool>, verdict| Rule {
+        name: name.to_string(),
+        case_id: None,
+        mutation: None,
+        expectation: Some(expectation.to_string()),
+        success,
+        error_code: None,
+        error_contains: None,
+        error_matches: None,
+        log_matched: None,
+        state_matched: None,
+        vm_error: None,
+        verdict,
+    };
+    let wrong_error = Rule {
+        error_matches: Some(false),
+        ..rule("builtin:must_fail_wrong_error", "must_fail", Some(false), Verdict::Fail)
+    };
+    let log_found = Rule {
+        log_matched: Some(true),
+        ..rule("builtin:log_contains_found", "log_contains", None, Verdict::Pass)
+    };
+    let state_matched = Rule {
+        state_matched: Some(true),
+        ..rule("builtin:account_state_matched", "account_state", Some(true), Verdict::Pass)
+    };
+    // Random args may fail with program errors, but a VM-level failure (a panic, an access
+    // violation, ...) is what `--fuzz` exists to find.
+    let random_vm_error = Rule {
+        mutation: Some("random_args".to_string()),
+        vm_error: Some(true),
+        ..rule("builtin:random_args_vm_error", "any", Some(false), Verdict::Fail)
+    };
+    vec![
+        random_vm_error,
+        rule("builtin:any", "any", None, Verdict::Pass),
+        log_found,
+        rule("builtin:log_contains_missing", "log_contains", None, Verdict::Fail),
+        state_matched,
+        rule("builtin:account_state_mismatch", "account_state", None, Verdict::Fail),
+        wrong_error,
+        rule("builtin:must_fail_failed", "must_fail", Some(false), Verdict::Pass),
+        rule("builtin:must_fail_succeeded", "must_fail", Some(true), Verdict::Fail),
+        rule("builtin:must_succeed_succeeded", "must_succeed", Some(true), Verdict::Pass),
+        rule("builtin:must_succeed_failed", "must_succeed", Some(false), Verdict::Fail),
+    ]
+}
+
+// Parse one JSON rule object; unknown keys are ignored to keep the schema forward-compatible.
+fn parse_rule(v: &Value) -> Result<Rule> {
+    let name = match v["name"].as_str() {
+        Some(s) if !s.starts_with("builtin:") => s.to_string(),
+        Some(s) => bail!("rule name `{}` uses the reserved `builtin:` prefix", s),
+        None => bail!("rule is missing `name`"),
+    };
+    let verdict = match v["verdict"].as_str() {
+        Some("pass") => Verdict::Pass,
+        Some("fail") => Verdict::Fail,
+        _ => bail!("rule `{}` needs `verdict` of \"pass\" or \"fail\"", name),
+    };
+    let error_code = match &v["error_code"] {
+        Value::Null => None,
+        c => Some(
+            c.as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .with_context(|| format!("rule `{}` has a non-u32 `error_code`", name))?,
+        ),
+    };
+
+    Ok(Rule {
+        case_id: v["case_id"].as_str().map(str::to_string),
+        mutation: v["mutation"].as_str().map(str::to_string),
+        expectation: v["expectation"].as_str().map(str::to_string),
+        success: v["success"].as_bool(),
+        error_code,
+        error_contains: v["error_contains"].as_str().map(str::to_string),
+        error_matches: v["error_matches"].as_bool(),
+        log_matched: v["log_matched"].as_bool(),
+        state_matched: v["state_matched"].as_bool(),
+        vm_error: v["vm_error"].as_bool(),
+        verdict,
+        name,
+    })
+}
+
+fn rule_matches(rule: &Rule, o: &CaseOutcome) -> bool {
+    if let Some(p) = &rule.case_id {
+        if !glob_match(p, &o.case.id) && !glob_match(p, &o.case.display_name) {
+            return false;
+        }
+    }
+    if let Some(p) = &rule.mutation {
+        if !glob_match(p, o.mutation) {
+            return false;
+        }
+    }
+    if let Some(e) = &rule.expectation {
+        if e != o.case.expectation.label() {
+            return false;
+        }
+    }
+    if let Some(s) = rule.success {
+        if s != o.actual_success {
+            return false;
+        }
+    }
+    if let Some(code) = rule.error_code {
+        if o.error.and_then(custom_error_code) != Some(code) {
+            return false;
+        }
+    }
+    if let Some(needle) = &rule.error_contains {
+        if !o.error.is_some_and(|e| e.contains(needle.as_str())) {
+            return false;
+        }
+    }
+    if let Some(m) = rule.error_matches {
+        if m != expected_error_matches(&o.case.expectation, o.error) {
+            return false;
+        }
+    }
+    if let Some(m) = rule.log_matched {
+        if o.log_matched != Some(m) {
+            return false;
+        }
+    }
+    if let Some(m) = rule.state_matched {
+        if o.state_matched != Some(m) {
+            return false;
+        }
+    }
+    if let Some(v) = rule.vm_error {
+        if v != o.error.is_some_and(is_vm_error) {
+            return false;
+        }
+    }
+    true
+}
+
+// An instruction error the runtime raised without a `Custom(N)` program error, e.g.
+// `ProgramFailedToComplete`; setup and transaction-level failures don't count.
+fn is_vm_error(error: &str) -> bool {
+    error.contains("InstructionError(") && custom_error_code(error).is_none()
+}
+
+// Whether a failure's error satisfies a MustFailWith expectation; other expectations don't
+// constrain the error.
+pub fn expected_error_matches(expectation: &Expectation, error: Option<&str>) -> bool {
+    let Expectation::MustFailWith {
+        anchor_code,
+        contains,
+    } = expectation
+    else {
+        return true;
+    };
+    anchor_code.is_none_or(|code| error.and_then(custom_error_code) == Some(code))
+        && contains
+            .as_deref()
+            .is_none_or(|needle| error.is_some_and(|e| e.contains(needle)))
+}
+
+// Pull the program's custom error code out of a LiteSVM error dump (`Custom(6000)`).
+pub fn custom_error_code(error: &str) -> Option<u32> {
+    let start = error.find("Custom(")? + "Custom(".len();
+    let rest = &error[start..];
+    let end = rest.find(')')?;
+    rest[..end].trim().parse().ok()
+}
+
+// Coarse bucket for a failure: Anchor/custom error codes first, then the runtime error kind.
+pub fn error_class(error: &str) -> String {
+    if let Some(code) = custom_error_code(error) {
+        return match code {
+            2000..=2999 => format!("constraint:{}", code),
+            6000.. => format!("custom:{}", code),
+            _ => format!("anchor:{}", code),
+        };
+    }
+    // `InstructionError(0, MissingRequiredSignature)` -> `MissingRequiredSignature`.
+    if let Some(start) = error.find("InstructionError(") {
+        let rest = &error[start..];
+        if let Some(kind) = rest.find(", ").and_then(|i| leading_ident(&rest[i + 2..])) {
+            return kind.to_string();
+        }
+    }
+    // Transaction-level errors (`err: AccountNotFound`).
+    if let Some(kind) = error.find("err: ").and_then(|i| leading_ident(&error[i + 5..])) {
+        return kind.to_string();
+    }
+    // Harness-side failures (add_program, arg encoding, ...) never reached the program.
+    "harness".to_string()
+}
+
+fn leading_ident(s: &str) -> Option<&str> {
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    (end > 0).then(|| &s[..end])
+}
+
+// Minimal `*` wildcard matcher for case ids and mutation labels.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let mut rest = text;
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(idx) => rest = &rest[idx + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn must_fail_with_only_passes_on_the_expected_code() {
+        let case = crate::types::EdgeCase {
+            id: "vault.json_deposit_wrong_pda_vault".to_string(),
+            display_name: "vault.json_deposit_wrong_pda_vault".to_string(),
+            idl_file: "vault.json".to_string(),
+            program_id: solana_address::Address::default(),
+            instruction: crate::types::InstructionSpec {
+                name: "deposit".to_string(),
+                discriminator: Vec::new(),
+                accounts: Vec::new(),
+                args: Vec::new(),
+            },
+            mutation: crate::types::Mutation::WrongPda {
+                account: "vault".to_string(),
+            },
+            expectation: Expectation::MustFailWith {
+                anchor_code: Some(2006),
+                contains: None,
+            },
+            setup_chain: Vec::new(),
+        };
+        let classify = |error: &str| {
+            Classifier::builtin().classify(&CaseOutcome {
+                case: &case,
+                mutation: "wrong_pda:vault",
+                actual_success: false,
+                error: Some(error),
+                log_matched: None,
+                state_matched: None,
+            })
+        };
+
+        let seeds = "transaction failed: InstructionError(0, Custom(2006))";
+        assert_eq!(classify(seeds), (true, "builtin:must_fail_failed".to_string()));
+        let not_initialized = "transaction failed: InstructionError(0, Custom(3012))";
+        assert_eq!(
+            classify(not_initialized),
+            (false, "builtin:must_fail_wrong_error".to_string())
+        );
+
+        let insufficient = Expectation::MustFailWith {
+            anchor_code: Some(6000),
+            contains: None,
+        };
+        let custom = "transaction failed: InstructionError(0, Custom(6000))";
+        assert!(expected_error_matches(&insufficient, Some(custom)));
+        assert!(!expected_error_matches(&insufficient, Some(not_initialized)));
+        assert!(!expected_error_matches(&insufficient, None));
+    }
+
+    #[test]
+    fn log_contains_passes_only_when_the_substring_was_logged() {
+        let case = crate::types::EdgeCase {
+            id: "vault.json_deposit_base".to_string(),
+            display_name: "vault.json_deposit_base".to_string(),
+            idl_file: "vault.json".to_string(),
+            program_id: solana_address::Address::default(),
+            instruction: crate::types::InstructionSpec {
+                name: "deposit".to_string(),
+                discriminator: Vec::new(),
+                accounts: Vec::new(),
+                args: Vec::new(),
+            },
+            mutation: crate::types::Mutation::None,
+            expectation: Expectation::LogContains {
+                substring: "Deposited".to_string(),
+            },
+            setup_chain: Vec::new(),
+        };
+        let classify = |log_matched| {
+            Classifier::builtin().classify(&CaseOutcome {
+                case: &case,
+                mutation: "none",
+                actual_success: true,
+                error: None,
+                log_matched: Some(log_matched),
+                state_matched: None,
+            })
+        };
+
+        assert_eq!(classify(true), (true, "builtin:log_contains_found".to_string()));
+        assert_eq!(classify(false), (false, "builtin:log_contains_missing".to_string()));
+    }
+
+    #[test]
+    fn account_state_passes_only_when_the_state_matched() {
+        let case = crate::types::EdgeCase {
+            id: "vault.json_deposit_base".to_string(),
+            display_name: "vault.json_deposit_base".to_string(),
+            idl_file: "vault.json".to_string(),
+            program_id: solana_address::Address::default(),
+            instruction: crate::types::InstructionSpec {
+                name: "deposit".to_string(),
+                discriminator: Vec::new(),
+                accounts: Vec::new(),
+                args: Vec::new(),
+            },
+            mutation: crate::types::Mutation::None,
+            expectation: Expectation::AccountState {
+                assertions: vec![crate::types::AccountAssertion {
+                    instruction: "deposit".to_string(),
+                    account: "vault".to_string(),
+                    offset: 40,
+                    expected: vec![0; 8],
+                }],
+            },
+            setup_chain: Vec::new(),
+        };
+        let classify = |success: bool, state_matched| {
+            Classifier::builtin().classify(&CaseOutcome {
+                case: &case,
+                mutation: "none",
+                actual_success: success,
+                error: None,
+                log_matched: None,
+                state_matched: Some(state_matched),
+            })
+        };
+
+        assert_eq!(classify(true, true), (true, "builtin:account_state_matched".to_string()));
+        assert_eq!(classify(true, false), (false, "builtin:account_state_mismatch".to_string()));
+        assert_eq!(classify(false, false), (false, "builtin:account_state_mismatch".to_string()));
+    }
+
+    #[test]
+    fn random_args_cases_fail_only_on_vm_level_errors() {
+        let case = crate::types::EdgeCase {
+            id: "vault.json_deposit_random_args_0".to_string(),
+            display_name: "vault.json_deposit_random_args_0".to_string(),
+            idl_file: "vault.json".to_string(),
+            program_id: solana_address::Address::default(),
+            instruction: crate::types::InstructionSpec {
+                name: "deposit".to_string(),
+                discriminator: Vec::new(),
+                accounts: Vec::new(),
+                args: Vec::new(),
+            },
+            mutation: crate::types::Mutation::RandomArgs {
+                seed: 1,
+                args: Vec::new(),
+            },
+            expectation: Expectation::Any,
+            setup_chain: Vec::new(),
+        };
+        let classify = |error: Option<&str>| {
+            Classifier::builtin().classify(&CaseOutcome {
+                case: &case,
+                mutation: "random_args",
+                actual_success: error.is_none(),
+                error,
+                log_matched: None,
+                state_matched: None,
+            })
+        };
+
+        assert_eq!(classify(None), (true, "builtin:any".to_string()));
+        let program_error = "InstructionError(0, Custom(6000))";
+        assert_eq!(classify(Some(program_error)), (true, "builtin:any".to_string()));
+        assert_eq!(
+            classify(Some("InstructionError(0, ProgramFailedToComplete)")),
+            (false, "builtin:random_args_vm_error".to_string())
+        );
+        // Harness failures never reached the VM.
+        assert_eq!(classify(Some("setup failed")), (true, "builtin:any".to_string()));
+    }
+}
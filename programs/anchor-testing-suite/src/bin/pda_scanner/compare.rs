@@ -0,0 +1,401 @@
+use crate::cases::MAX_TYPE_DEPTH;
+use crate::exit::Failure;
+use crate::idl_format::normalize;
+use crate::layout::defined_name;
+use crate::specs::parse_instruction;
+use crate::types::{AccountSpec, ArgSpec, InstructionSpec};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// The parts of one IDL version the comparison looks at.
+struct Idl {
+    instructions: Vec<InstructionSpec>,
+    // Raw `types` entries by name, for resolving `defined` arg types.
+    types: HashMap<String, Value>,
+}
+
+// One detected difference between two IDL versions.
+#[derive(Debug)]
+struct IdlChange {
+    instruction: String,
+    kind: &'static str,
+    detail: String,
+    breaking: bool,
+}
+
+// Diff two IDLs instruction-by-instruction and fail when any change breaks existing clients.
+pub fn compare_idls(old_path: &Path, new_path: &Path, as_json: bool) -> Result<()> {
+    let old = load_idl(old_path)?;
+    let new = load_idl(new_path)?;
+    let changes = diff_instructions(&old, &new);
+    let breaking = changes.iter().filter(|c| c.breaking).count();
+
+    if as_json {
+        let changes_json: Vec<Value> = changes
+            .iter()
+            .map(|c| {
+                json!({
+                    "instruction": c.instruction,
+                    "kind": c.kind,
+                    "detail": c.detail,
+                    "breaking": c.breaking
+                })
+            })
+            .collect();
+        let doc = json!({
+            "old": old_path.display().to_string(),
+            "new": new_path.display().to_string(),
+            "breaking_changes": breaking,
+            "additive_changes": changes.len() - breaking,
+            "changes": changes_json
+        });
+        println!("{}", serde_json::to_string_pretty(&doc)?);
+    } else {
+        println!("Comparing {} -> {}", old_path.display(), new_path.display());
+        println!("{:-^60}", " IDL Changes ");
+        for c in &changes {
+            println!(
+                "{}  {} [{}] {}",
+                if c.breaking { "BREAKING" } else { "ADDITIVE" },
+                c.instruction,
+                c.kind,
+                c.detail
+            );
+        }
+        println!("{:-^60}", " Summary ");
+        println!("breaking_changes: {}", breaking);
+        println!("additive_changes: {}", changes.len() - breaking);
+    }
+
+    if breaking > 0 {
+        bail!(Failure::Cases(format!(
+            "{} breaking IDL changes detected",
+            breaking
+        )));
+    }
+    Ok(())
+}
+
+// Parse an IDL file into instruction specs and its types table, normalizing legacy layouts
+// first.
+fn load_idl(path: &Path) -> Result<Idl> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed reading {}", path.display()))?;
+    let raw: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    Ok(parse_idl(&raw))
+}
+
+fn parse_idl(raw: &Value) -> Idl {
+    let (_, _, idl) = normalize(raw);
+    let instructions = idl["instructions"]
+        .as_array()
+        .map(|ixs| ixs.iter().filter_map(parse_instruction).collect())
+        .unwrap_or_default();
+    let types = idl["types"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| Some((t["name"].as_str()?.to_string(), t.clone())))
+        .collect();
+    Idl {
+        instructions,
+        types,
+    }
+}
+
+fn diff_instructions(old_idl: &Idl, new_idl: &Idl) -> Vec<IdlChange> {
+    let (old, new) = (&old_idl.instructions, &new_idl.instructions);
+    let mut changes = Vec::new();
+
+    for o in old {
+        let n = match new.iter().find(|n| n.name == o.name) {
+            Some(n) => n,
+            None => {
+                // A same-shaped new instruction is most likely a rename.
+                let detail = match new
+                    .iter()
+                    .find(|n| {
+                        !old.iter().any(|x| x.name == n.name) && same_shape(old_idl, o, new_idl, n)
+                    })
+                {
+                    Some(n) => format!("instruction removed (possibly renamed to `{}`)", n.name),
+                    None => "instruction removed".to_string(),
+                };
+                changes.push(change(&o.name, "instruction_removed", detail, true));
+                continue;
+            }
+        };
+
+        if o.discriminator != n.discriminator {
+            changes.push(change(
+                &o.name,
+                "discriminator_changed",
+                format!("{:?} -> {:?}", o.discriminator, n.discriminator),
+                true,
+            ));
+        }
+        diff_accounts(&o.name, &o.accounts, &n.accounts, &mut changes);
+        diff_args(old_idl, o, new_idl, n, &mut changes);
+    }
+
+    for n in new {
+        if !old.iter().any(|o| o.name == n.name) {
+            changes.push(change(&n.name, "instruction_added", "new instruction".to_string(), false));
+        }
+    }
+
+    changes
+}
+
+fn diff_accounts(ix: &str, old: &[AccountSpec], new: &[AccountSpec], out: &mut Vec<IdlChange>) {
+    for o in old {
+        if !new.iter().any(|n| n.name == o.name) {
+            out.push(change(ix, "account_removed", o.name.clone(), true));
+        }
+    }
+    for n in new {
+        match old.iter().find(|o| o.name == n.name) {
+            // Clients built against the old IDL won't pass this account.
+            None => out.push(change(ix, "account_added", n.name.clone(), true)),
+            Some(o) => {
+                if o.signer != n.signer {
+                    let detail = format!("{}: signer {} -> {}", n.name, o.signer, n.signer);
+                    out.push(change(ix, "account_signer_changed", detail, n.signer));
+                }
+                if o.writable != n.writable {
+                    let detail = format!("{}: writable {} -> {}", n.name, o.writable, n.writable);
+                    out.push(change(ix, "account_writable_changed", detail, n.writable));
+                }
+            }
+        }
+    }
+
+    // Accounts are positional, so reordering the shared ones breaks callers.
+    let old_order: Vec<&str> = old
+        .iter()
+        .filter(|o| new.iter().any(|n| n.name == o.name))
+        .map(|o| o.name.as_str())
+        .collect();
+    let new_order: Vec<&str> = new
+        .iter()
+        .filter(|n| old.iter().any(|o| o.name == n.name))
+        .map(|n| n.name.as_str())
+        .collect();
+    if old_order != new_order {
+        out.push(change(
+            ix,
+            "account_order_changed",
+            format!("[{}] -> [{}]", old_order.join(", "), new_order.join(", ")),
+            true,
+        ));
+    }
+}
+
+fn diff_args(
+    old_idl: &Idl,
+    o: &InstructionSpec,
+    new_idl: &Idl,
+    n: &InstructionSpec,
+    out: &mut Vec<IdlChange>,
+) {
+    // Borsh args are positional: compare by index, names only matter for readability.
+    for i in 0..o.args.len().max(n.args.len()) {
+        match (o.args.get(i), n.args.get(i)) {
+            (Some(a), Some(b)) if a.ty != b.ty => out.push(change(
+                &o.name,
+                "arg_type_changed",
+                format!("{}: {} -> {}", b.name, a.ty, b.ty),
+                true,
+            )),
+            // Same reference, different definition: a field of the struct/enum changed.
+            (Some(a), Some(b)) if !same_type(old_idl, a, new_idl, b) => out.push(change(
+                &o.name,
+                "arg_type_changed",
+                format!("{}: definition of {} changed", b.name, b.ty),
+                true,
+            )),
+            (Some(a), Some(b)) if a.name != b.name => out.push(change(
+                &o.name,
+                "arg_renamed",
+                format!("{} -> {}", a.name, b.name),
+                false,
+            )),
+            (Some(a), None) => out.push(change(&o.name, "arg_removed", a.name.clone(), true)),
+            (None, Some(b)) => out.push(change(&o.name, "arg_added", b.name.clone(), true)),
+            _ => {}
+        }
+    }
+}
+
+fn same_shape(old_idl: &Idl, a: &InstructionSpec, new_idl: &Idl, b: &InstructionSpec) -> bool {
+    a.args.len() == b.args.len()
+        && a.args.iter().zip(&b.args).all(|(x, y)| same_type(old_idl, x, new_idl, y))
+        && a.accounts.len() == b.accounts.len()
+        && a
+            .accounts
+            .iter()
+            .zip(&b.accounts)
+            .all(|(x, y)| x.name == y.name && x.signer == y.signer && x.writable == y.writable)
+}
+
+// Whether two args have the same type once `defined` references are resolved.
+fn same_type(old_idl: &Idl, a: &ArgSpec, new_idl: &Idl, b: &ArgSpec) -> bool {
+    resolve_defined(&a.ty, &old_idl.types, 0) == resolve_defined(&b.ty, &new_idl.types, 0)
+}
+
+// `ty` with every `defined` reference replaced by the referenced type's definition (fields in
+// order, with names and types), so a struct or enum that gained, lost, reordered or retyped a
+// field no longer compares equal. Unknown and self-referential types stay as references.
+fn resolve_defined(ty: &Value, types: &HashMap<String, Value>, depth: usize) -> Value {
+    if depth > MAX_TYPE_DEPTH {
+        return ty.clone();
+    }
+    if let Some(def) = defined_name(ty).and_then(|name| types.get(name)) {
+        return resolve_defined(&def["type"], types, depth + 1);
+    }
+    match ty {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), resolve_defined(v, types, depth + 1)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| resolve_defined(v, types, depth + 1))
+            .collect(),
+        _ => ty.clone(),
+    }
+}
+
+fn change(ix: &str, kind: &'static str, detail: String, breaking: bool) -> IdlChange {
+    IdlChange {
+        instruction: ix.to_string(),
+        kind,
+        detail,
+        breaking,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ix(name: &str, accounts: &[&str], args: Value) -> Value {
+        let accounts: Vec<Value> = accounts
+            .iter()
+            .map(|a| json!({ "name": a, "writable": true, "signer": *a == "user" }))
+            .collect();
+        json!({
+            "name": name,
+            "discriminator": [name.len() as u8, 0, 0, 0, 0, 0, 0, 0],
+            "accounts": accounts,
+            "args": args
+        })
+    }
+
+    fn idl(instructions: Vec<Value>, types: Value) -> Idl {
+        parse_idl(&json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": instructions,
+            "types": types
+        }))
+    }
+
+    fn kinds(changes: &[IdlChange]) -> Vec<(&str, bool)> {
+        changes.iter().map(|c| (c.kind, c.breaking)).collect()
+    }
+
+    #[test]
+    fn removed_and_renamed_instructions_are_breaking() {
+        let amount = json!([{ "name": "amount", "type": "u64" }]);
+        let old = idl(
+            vec![
+                ix("deposit", &["vault", "user"], amount.clone()),
+                ix("withdraw", &["vault", "user"], amount.clone()),
+            ],
+            json!([]),
+        );
+        let new = idl(vec![ix("put", &["vault", "user"], amount)], json!([]));
+
+        let changes = diff_instructions(&old, &new);
+        assert_eq!(
+            kinds(&changes),
+            [
+                ("instruction_removed", true),
+                ("instruction_removed", true),
+                ("instruction_added", false)
+            ]
+        );
+        assert_eq!(changes[0].detail, "instruction removed (possibly renamed to `put`)");
+    }
+
+    #[test]
+    fn reordered_and_added_accounts_are_breaking() {
+        let old = idl(vec![ix("deposit", &["vault", "user"], json!([]))], json!([]));
+        let reordered = idl(vec![ix("deposit", &["user", "vault"], json!([]))], json!([]));
+        let added = idl(
+            vec![ix("deposit", &["vault", "user", "fee"], json!([]))],
+            json!([]),
+        );
+
+        let changes = diff_instructions(&old, &reordered);
+        assert_eq!(kinds(&changes), [("account_order_changed", true)]);
+        assert_eq!(changes[0].detail, "[vault, user] -> [user, vault]");
+        let changes = diff_instructions(&old, &added);
+        assert_eq!(kinds(&changes), [("account_added", true)]);
+        assert_eq!(changes[0].detail, "fee");
+    }
+
+    #[test]
+    fn changes_inside_defined_arg_types_are_breaking() {
+        let params = |fields: Value| {
+            json!([{ "name": "Params", "type": { "kind": "struct", "fields": fields } }])
+        };
+        let args = json!([{ "name": "params", "type": { "defined": { "name": "Params" } } }]);
+        let old = idl(
+            vec![ix("deposit", &["vault", "user"], args.clone())],
+            params(json!([
+                { "name": "amount", "type": "u64" },
+                { "name": "memo", "type": "u8" }
+            ])),
+        );
+        let variants = [
+            // Field added.
+            json!([
+                { "name": "amount", "type": "u64" },
+                { "name": "memo", "type": "u8" },
+                { "name": "fee", "type": "u64" }
+            ]),
+            // Fields reordered.
+            json!([
+                { "name": "memo", "type": "u8" },
+                { "name": "amount", "type": "u64" }
+            ]),
+            // Field retyped.
+            json!([
+                { "name": "amount", "type": "u32" },
+                { "name": "memo", "type": "u8" }
+            ]),
+        ];
+        for fields in variants {
+            let new = idl(vec![ix("deposit", &["vault", "user"], args.clone())], params(fields));
+            let changes = diff_instructions(&old, &new);
+            assert_eq!(kinds(&changes), [("arg_type_changed", true)]);
+            assert!(changes[0].detail.starts_with("params: definition of"));
+        }
+        // The same definition under an unchanged reference is no change at all.
+        let same = idl(
+            vec![ix("deposit", &["vault", "user"], args)],
+            params(json!([
+                { "name": "amount", "type": "u64" },
+                { "name": "memo", "type": "u8" }
+            ])),
+        );
+        assert!(diff_instructions(&old, &same).is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::binaries::load_program_binaries;
+use crate::cases::{
+    base_instruction_data, deployed_programs, generate_edge_cases, run_single_case, Harness,
+};
+use crate::classify::error_class;
+use crate::exit::Failure;
+use crate::specs::load_program_specs;
+use crate::types::{EdgeCase, InstructionSpec, Mutation, ProgramSpec};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+// What running an instruction's base case showed about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reach {
+    Executed,
+    Failed { error: String },
+    // The base case never ran: its args can't be encoded or its program has no valid binary.
+    Skipped { reason: String },
+}
+
+impl Reach {
+    fn label(&self) -> &'static str {
+        match self {
+            Reach::Executed => "executed",
+            Reach::Failed { .. } => "failed",
+            Reach::Skipped { .. } => "skipped",
+        }
+    }
+}
+
+// Drive `coverage`: run every instruction's base case once and report, per program, how much
+// of the IDL surface the suite can reach.
+pub fn run_coverage(project_dir: &str) -> Result<()> {
+    let project_root = Path::new(project_dir);
+    let idl_dir = project_root.join("target").join("idl");
+    let deploy_dir = project_root.join("target").join("deploy");
+    if !idl_dir.exists() || !deploy_dir.exists() {
+        bail!(Failure::Preflight(format!(
+            "No IDL/deploy artifacts under {}. Run `anchor build` first.",
+            project_root.join("target").display()
+        )));
+    }
+
+    let (programs, _) = load_program_specs(&idl_dir, &deploy_dir, &[], &[])?;
+    let binaries = load_program_binaries(&programs)?;
+    let deployed = deployed_programs(&programs, &binaries);
+    let base_cases: Vec<EdgeCase> = generate_edge_cases(&programs)
+        .into_iter()
+        .filter(|c| matches!(c.mutation, Mutation::None))
+        .collect();
+
+    println!("Running anchor-suite coverage");
+    let mut entries = Vec::new();
+    for p in &programs {
+        println!("{:-^60}", format!(" {} ", p.idl_file));
+        let is_deployed = deployed.iter().any(|(d, _)| d.program_id == p.program_id);
+        let mut reaches = Vec::new();
+        for ix in &p.instructions {
+            let reach = match base_cases
+                .iter()
+                .find(|c| c.program_id == p.program_id && c.instruction.name == ix.name)
+            {
+                Some(case) => instruction_reach(p, ix, is_deployed, || {
+                    run_single_case(&deployed, case, Harness::default()).error
+                }),
+                None => Reach::Skipped {
+                    reason: "no base case generated".to_string(),
+                },
+            };
+            match &reach {
+                Reach::Executed => println!("{:<9} {}", reach.label(), ix.name),
+                Reach::Failed { error } => {
+                    println!("{:<9} {}: {}", reach.label(), ix.name, error_class(error))
+                }
+                Reach::Skipped { reason } => {
+                    println!("{:<9} {}: {}", reach.label(), ix.name, reason)
+                }
+            }
+            reaches.push((ix.name.as_str(), reach));
+        }
+        entries.push((p, reaches));
+    }
+
+    let report_dir = project_root.join("target").join("anchor-suite");
+    fs::create_dir_all(&report_dir)
+        .with_context(|| format!("Failed to create {}", report_dir.display()))?;
+    let path = report_dir.join("coverage.json");
+    let doc = json!({
+        "tool": "anchor-suite",
+        "programs": entries
+            .iter()
+            .map(|(p, reaches)| program_json(p, reaches))
+            .collect::<Vec<_>>()
+    });
+    fs::write(&path, serde_json::to_string_pretty(&doc)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    println!("{:-^60}", " Summary ");
+    for (p, reaches) in &entries {
+        let [executed, failed, skipped] = counts(reaches);
+        println!(
+            "{}: {}/{} executed, {} failed, {} skipped",
+            p.idl_file,
+            executed,
+            reaches.len(),
+            failed,
+            skipped
+        );
+    }
+    println!("coverage: {}", path.display());
+    Ok(())
+}
+
+// Skip the instruction when its base case can't be built or deployed, otherwise run it.
+fn instruction_reach(
+    p: &ProgramSpec,
+    ix: &InstructionSpec,
+    is_deployed: bool,
+    run: impl FnOnce() -> Option<String>,
+) -> Reach {
+    if let Err(reason) = base_instruction_data(p, ix) {
+        return Reach::Skipped { reason };
+    }
+    if !is_deployed {
+        return Reach::Skipped {
+            reason: "program binary missing or invalid".to_string(),
+        };
+    }
+    match run() {
+        None => Reach::Executed,
+        Some(error) => Reach::Failed { error },
+    }
+}
+
+// Executed / failed / skipped counts.
+fn counts(reaches: &[(&str, Reach)]) -> [usize; 3] {
+    let mut out = [0; 3];
+    for (_, reach) in reaches {
+        let i = match reach {
+            Reach::Executed => 0,
+            Reach::Failed { .. } => 1,
+            Reach::Skipped { .. } => 2,
+        };
+        out[i] += 1;
+    }
+    out
+}
+
+fn program_json(p: &ProgramSpec, reaches: &[(&str, Reach)]) -> Value {
+    let names = |label: &str| -> Vec<&str> {
+        reaches
+            .iter()
+            .filter(|(_, r)| r.label() == label)
+            .map(|(name, _)| *name)
+            .collect()
+    };
+    let [executed, failed, skipped] = counts(reaches);
+    json!({
+        "idl_file": p.idl_file,
+        "program_id": p.program_id.to_string(),
+        "instructions": reaches.len(),
+        "executed_count": executed,
+        "failed_count": failed,
+        "skipped_count": skipped,
+        "executed": names("executed"),
+        "failed": reaches
+            .iter()
+            .filter_map(|(name, r)| match r {
+                Reach::Failed { error } => Some(json!({
+                    "instruction": name,
+                    "error": error,
+                    "error_class": error_class(error)
+                })),
+                _ => None,
+            })
+            .collect::<Vec<_>>(),
+        "skipped": reaches
+            .iter()
+            .filter_map(|(name, r)| match r {
+                Reach::Skipped { reason } => {
+                    Some(json!({ "instruction": name, "reason": reason }))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unencodable_args_are_skipped_before_anything_runs() {
+        let program = crate::specs::program_from_idl(
+            &json!({ "instructions": [
+                { "name": "deposit", "discriminator": [1, 0, 0, 0, 0, 0, 0, 0], "accounts": [],
+                  "args": [{ "name": "amount", "type": "u64" }] },
+                { "name": "configure", "discriminator": [2, 0, 0, 0, 0, 0, 0, 0], "accounts": [],
+                  "args": [{ "name": "config", "type": { "defined": { "name": "Missing" } } }] }
+            ]}),
+            "vault.json".to_string(),
+            crate::types::IdlFormat::Modern,
+            "0.1.0".to_string(),
+            solana_address::Address::default(),
+            std::path::PathBuf::from("vault.so"),
+        );
+        let [deposit, configure] = &program.instructions[..] else {
+            panic!("expected two instructions");
+        };
+        let must_not_run = || -> Option<String> { panic!("skipped instructions never run") };
+
+        assert!(matches!(
+            instruction_reach(&program, configure, true, must_not_run),
+            Reach::Skipped { reason } if reason.contains("arg config type not supported")
+        ));
+        assert!(matches!(
+            instruction_reach(&program, deposit, false, must_not_run),
+            Reach::Skipped { reason } if reason.contains("binary")
+        ));
+        assert_eq!(
+            instruction_reach(&program, deposit, true, || None),
+            Reach::Executed
+        );
+        let failed = instruction_reach(&program, deposit, true, || Some("boom".to_string()));
+        let reaches = [("configure", Reach::Executed), ("deposit", failed)];
+        assert_eq!(counts(&reaches), [1, 1, 0]);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::exit::Failure;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// Verdict of one executed case in a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CaseStatus {
+    display_name: String,
+    passed: bool,
+}
+
+// Executed cases of a baseline and a current report, matched by id.
+#[derive(Debug, Default)]
+struct ReportDiff {
+    newly_failing: Vec<(String, CaseStatus)>,
+    newly_passing: Vec<(String, CaseStatus)>,
+    // In the baseline but not executed in the current run.
+    disappeared: Vec<(String, CaseStatus)>,
+    added: Vec<(String, CaseStatus)>,
+}
+
+// Compare two test reports and fail only when a case that passed in the baseline fails now.
+pub fn diff_reports(baseline_path: &Path, current_path: &Path, as_json: bool) -> Result<()> {
+    let baseline = load_cases(baseline_path)?;
+    let current = load_cases(current_path)?;
+    let diff = diff_cases(&baseline, &current);
+
+    if as_json {
+        let entries = |cases: &[(String, CaseStatus)]| -> Vec<Value> {
+            cases
+                .iter()
+                .map(|(id, s)| {
+                    json!({ "id": id, "display_name": s.display_name, "passed": s.passed })
+                })
+                .collect()
+        };
+        let doc = json!({
+            "baseline": baseline_path.display().to_string(),
+            "current": current_path.display().to_string(),
+            "newly_failing": entries(&diff.newly_failing),
+            "newly_passing": entries(&diff.newly_passing),
+            "disappeared": entries(&diff.disappeared),
+            "added": entries(&diff.added)
+        });
+        println!("{}", serde_json::to_string_pretty(&doc)?);
+    } else {
+        println!(
+            "Comparing {} -> {}",
+            baseline_path.display(),
+            current_path.display()
+        );
+        println!("{:-^60}", " Case Changes ");
+        let sections = [
+            ("NEWLY_FAILING", &diff.newly_failing),
+            ("NEWLY_PASSING", &diff.newly_passing),
+            ("DISAPPEARED", &diff.disappeared),
+            ("ADDED", &diff.added),
+        ];
+        for (label, cases) in sections {
+            for (id, s) in cases.iter() {
+                let status = if s.passed { "pass" } else { "fail" };
+                println!("{:<14} {}  {} ({})", label, id, s.display_name, status);
+            }
+        }
+        println!("{:-^60}", " Summary ");
+        println!("newly_failing: {}", diff.newly_failing.len());
+        println!("newly_passing: {}", diff.newly_passing.len());
+        println!("disappeared: {}", diff.disappeared.len());
+        println!("added: {}", diff.added.len());
+    }
+
+    if !diff.newly_failing.is_empty() {
+        bail!(Failure::Cases(format!(
+            "{} cases regressed against the baseline",
+            diff.newly_failing.len()
+        )));
+    }
+    Ok(())
+}
+
+// `executed_cases` of a report.json, keyed by case id.
+fn load_cases(path: &Path) -> Result<BTreeMap<String, CaseStatus>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed reading {}", path.display()))?;
+    let report: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    let Some(cases) = report["executed_cases"].as_array() else {
+        bail!(
+            "{} has no `executed_cases`; is it a `test` report.json?",
+            path.display()
+        );
+    };
+    cases
+        .iter()
+        .map(|c| {
+            let id = c["id"]
+                .as_str()
+                .with_context(|| format!("{}: executed case without `id`", path.display()))?;
+            let status = CaseStatus {
+                // Reports written before display names existed only have the id.
+                display_name: c["display_name"].as_str().unwrap_or(id).to_string(),
+                passed: c["passed"].as_bool().unwrap_or(false),
+            };
+            Ok((id.to_string(), status))
+        })
+        .collect()
+}
+
+fn diff_cases(
+    baseline: &BTreeMap<String, CaseStatus>,
+    current: &BTreeMap<String, CaseStatus>,
+) -> ReportDiff {
+    let mut diff = ReportDiff::default();
+    for (id, now) in current {
+        let entry = (id.clone(), now.clone());
+        match baseline.get(id) {
+            None => diff.added.push(entry),
+            Some(before) if before.passed && !now.passed => diff.newly_failing.push(entry),
+            Some(before) if !before.passed && now.passed => diff.newly_passing.push(entry),
+            Some(_) => {}
+        }
+    }
+    for (id, before) in baseline {
+        if !current.contains_key(id) {
+            diff.disappeared.push((id.clone(), before.clone()));
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases_are_matched_by_id_across_reports() {
+        let cases = |entries: &[(&str, bool)]| {
+            entries
+                .iter()
+                .map(|(id, passed)| {
+                    let status = CaseStatus {
+                        display_name: format!("vault.json_{}", id),
+                        passed: *passed,
+                    };
+                    (id.to_string(), status)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let baseline = cases(&[("a", true), ("b", false), ("c", true), ("d", false)]);
+        let current = cases(&[("a", false), ("b", true), ("c", true), ("e", false)]);
+
+        let diff = diff_cases(&baseline, &current);
+        let ids =
+            |v: &[(String, CaseStatus)]| v.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.newly_failing), ["a"]);
+        assert_eq!(ids(&diff.newly_passing), ["b"]);
+        assert_eq!(ids(&diff.disappeared), ["d"]);
+        // A new case that fails is reported as added, not as a regression.
+        assert_eq!(ids(&diff.added), ["e"]);
+    }
+}
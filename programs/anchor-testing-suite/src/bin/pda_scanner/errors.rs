@@ -0,0 +1,151 @@
+use crate::types::{ProgramError, ProgramSpec};
+use serde_json::Value;
+
+// First code available to `#[error_code]` enums; everything below belongs to Anchor itself.
+const CUSTOM_ERROR_OFFSET: u32 = 6000;
+
+// Anchor's built-in `ErrorCode` variants (anchor-lang 0.32).
+const ANCHOR_ERRORS: &[(u32, &str)] = &[
+    (100, "InstructionMissing"),
+    (101, "InstructionFallbackNotFound"),
+    (102, "InstructionDidNotDeserialize"),
+    (103, "InstructionDidNotSerialize"),
+    (1000, "IdlInstructionStub"),
+    (1001, "IdlInstructionInvalidProgram"),
+    (1002, "IdlAccountNotEmpty"),
+    (1500, "EventInstructionStub"),
+    (2000, "ConstraintMut"),
+    (2001, "ConstraintHasOne"),
+    (2002, "ConstraintSigner"),
+    (2003, "ConstraintRaw"),
+    (2004, "ConstraintOwner"),
+    (2005, "ConstraintRentExempt"),
+    (2006, "ConstraintSeeds"),
+    (2007, "ConstraintExecutable"),
+    (2008, "ConstraintState"),
+    (2009, "ConstraintAssociated"),
+    (2010, "ConstraintAssociatedInit"),
+    (2011, "ConstraintClose"),
+    (2012, "ConstraintAddress"),
+    (2013, "ConstraintZero"),
+    (2014, "ConstraintTokenMint"),
+    (2015, "ConstraintTokenOwner"),
+    (2016, "ConstraintMintMintAuthority"),
+    (2017, "ConstraintMintFreezeAuthority"),
+    (2018, "ConstraintMintDecimals"),
+    (2019, "ConstraintSpace"),
+    (2020, "ConstraintAccountIsNone"),
+    (2021, "ConstraintTokenTokenProgram"),
+    (2022, "ConstraintMintTokenProgram"),
+    (2023, "ConstraintAssociatedTokenTokenProgram"),
+    (2024, "ConstraintMintGroupPointerExtension"),
+    (2025, "ConstraintMintGroupPointerExtensionAuthority"),
+    (2026, "ConstraintMintGroupPointerExtensionGroupAddress"),
+    (2027, "ConstraintMintGroupMemberPointerExtension"),
+    (2028, "ConstraintMintGroupMemberPointerExtensionAuthority"),
+    (2029, "ConstraintMintGroupMemberPointerExtensionMemberAddress"),
+    (2030, "ConstraintMintMetadataPointerExtension"),
+    (2031, "ConstraintMintMetadataPointerExtensionAuthority"),
+    (2032, "ConstraintMintMetadataPointerExtensionMetadataAddress"),
+    (2033, "ConstraintMintCloseAuthorityExtension"),
+    (2034, "ConstraintMintCloseAuthorityExtensionAuthority"),
+    (2035, "ConstraintMintPermanentDelegateExtension"),
+    (2036, "ConstraintMintPermanentDelegateExtensionDelegate"),
+    (2037, "ConstraintMintTransferHookExtension"),
+    (2038, "ConstraintMintTransferHookExtensionAuthority"),
+    (2039, "ConstraintMintTransferHookExtensionProgramId"),
+    (2040, "ConstraintDuplicateMutableAccount"),
+    (2500, "RequireViolated"),
+    (2501, "RequireEqViolated"),
+    (2502, "RequireKeysEqViolated"),
+    (2503, "RequireNeqViolated"),
+    (2504, "RequireKeysNeqViolated"),
+    (2505, "RequireGtViolated"),
+    (2506, "RequireGteViolated"),
+    (3000, "AccountDiscriminatorAlreadySet"),
+    (3001, "AccountDiscriminatorNotFound"),
+    (3002, "AccountDiscriminatorMismatch"),
+    (3003, "AccountDidNotDeserialize"),
+    (3004, "AccountDidNotSerialize"),
+    (3005, "AccountNotEnoughKeys"),
+    (3006, "AccountNotMutable"),
+    (3007, "AccountOwnedByWrongProgram"),
+    (3008, "InvalidProgramId"),
+    (3009, "InvalidProgramExecutable"),
+    (3010, "AccountNotSigner"),
+    (3011, "AccountNotSystemOwned"),
+    (3012, "AccountNotInitialized"),
+    (3013, "AccountNotProgramData"),
+    (3014, "AccountNotAssociatedTokenAccount"),
+    (3015, "AccountSysvarMismatch"),
+    (3016, "AccountReallocExceedsLimit"),
+    (3017, "AccountDuplicateReallocs"),
+    (4100, "DeclaredProgramIdMismatch"),
+    (4101, "TryingToInitPayerAsProgramAccount"),
+    (4102, "InvalidNumericConversion"),
+    (5000, "Deprecated"),
+];
+
+// Name for a `Custom(N)` code: Anchor's own table below 6000, the program's IDL `errors` above.
+pub fn error_name(code: u32, program: &ProgramSpec) -> Option<String> {
+    if code >= CUSTOM_ERROR_OFFSET {
+        return program
+            .errors
+            .iter()
+            .find(|e| e.code == code)
+            .map(|e| e.name.clone());
+    }
+    ANCHOR_ERRORS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| name.to_string())
+}
+
+// IDL `errors` entries (`{code, name, msg}`, same shape in modern and legacy IDLs).
+pub fn parse_program_errors(idl: &Value) -> Vec<ProgramError> {
+    idl["errors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|e| {
+            Some(ProgramError {
+                code: u32::try_from(e["code"].as_u64()?).ok()?,
+                name: e["name"].as_str()?.to_string(),
+                msg: e["msg"].as_str().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn names_come_from_anchor_below_6000_and_the_idl_above() {
+        let idl = json!({ "errors": [
+            { "code": 6000, "name": "InsufficientFunds", "msg": "Insufficient funds in vault" },
+            { "code": 6001, "name": "Unauthorized" }
+        ]});
+        let program = ProgramSpec {
+            idl_file: "vault.json".to_string(),
+            name: "vault".to_string(),
+            idl_format: crate::types::IdlFormat::Modern,
+            idl_version: "0.1.0".to_string(),
+            program_id: solana_address::Address::default(),
+            program_id_source: crate::types::ProgramIdSource::Idl,
+            deploy_so: std::path::PathBuf::from("vault.so"),
+            instructions: Vec::new(),
+            account_types: Vec::new(),
+            types: Default::default(),
+            errors: parse_program_errors(&idl),
+        };
+
+        assert_eq!(error_name(2006, &program).as_deref(), Some("ConstraintSeeds"));
+        assert_eq!(error_name(3002, &program).as_deref(), Some("AccountDiscriminatorMismatch"));
+        assert_eq!(error_name(6001, &program).as_deref(), Some("Unauthorized"));
+        assert_eq!(error_name(6002, &program), None);
+        assert_eq!(error_name(2999, &program), None);
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+// Process exit codes; anything that isn't a `Failure` exits with `TOOL_ERROR`.
+pub const TOOL_ERROR: i32 = 1;
+pub const PREFLIGHT_FAILED: i32 = 2;
+pub const NO_TESTABLE_PROGRAMS: i32 = 3;
+pub const CASES_FAILED: i32 = 4;
+pub const SMOKE_TEST_FAILED: i32 = 5;
+
+// Shown under `--help` and `test --help`.
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  all checks and cases passed
+  1  tool error: bad arguments, IO or invalid JSON
+  2  preflight failed: missing target/idl or target/deploy (run `anchor build`) or missing \
+--spec file
+  3  no testable program: no IDL with instructions and a matching binary
+  4  the suite ran and a check or generated case failed (also: `diff` regressions, breaking \
+`compare-idl` changes, a replayed case that doesn't match)
+  5  the optional cargo smoke test failed and nothing else did";
+
+// Expected ways a run fails, as opposed to the tool itself breaking.
+#[derive(Debug)]
+pub enum Failure {
+    // Build artifacts or inputs the run needs are missing; nothing was executed.
+    Preflight(String),
+    // The artifacts exist but no program in them can be tested.
+    NoPrograms(String),
+    // The run completed but a check or case didn't meet its expectation.
+    Cases(String),
+    // Everything passed except the optional `cargo test` smoke run.
+    SmokeTest(String),
+}
+
+impl Failure {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Failure::Preflight(_) => PREFLIGHT_FAILED,
+            Failure::NoPrograms(_) => NO_TESTABLE_PROGRAMS,
+            Failure::Cases(_) => CASES_FAILED,
+            Failure::SmokeTest(_) => SMOKE_TEST_FAILED,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Preflight(msg)
+            | Failure::NoPrograms(msg)
+            | Failure::Cases(msg)
+            | Failure::SmokeTest(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
+
+// Exit code for an error a subcommand returned; context added on top of a `Failure` is ignored.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<Failure>()
+        .map_or(TOOL_ERROR, Failure::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn only_failures_map_to_their_own_codes() {
+        let preflight = anyhow::Error::new(Failure::Preflight("no idl".to_string()));
+        assert_eq!(exit_code(&preflight), PREFLIGHT_FAILED);
+        let empty = anyhow::Error::new(Failure::NoPrograms("no programs".to_string()));
+        assert_eq!(exit_code(&empty), NO_TESTABLE_PROGRAMS);
+        let smoke = anyhow::Error::new(Failure::SmokeTest("cargo test failed".to_string()));
+        assert_eq!(exit_code(&smoke), SMOKE_TEST_FAILED);
+        let cases: anyhow::Result<()> = Err(Failure::Cases("1 case failed".to_string()).into());
+        assert_eq!(
+            exit_code(&cases.context("while testing").unwrap_err()),
+            CASES_FAILED
+        );
+        assert_eq!(exit_code(&anyhow::anyhow!("invalid JSON")), TOOL_ERROR);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::types::{AccountAssertion, AccountFixture, AccountFixtures, ProgramSpec};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::Value;
+use solana_address::Address;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+// Load a `--fixtures` file: a JSON object mapping account names to
+// `{ "lamports": <u64>, "owner": "<pubkey>", "data_base64": "<base64>" }` (`data_base64` may be
+// omitted for an empty account).
+pub fn load_fixtures(path: &Path) -> Result<AccountFixtures> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed reading {}", path.display()))?;
+    let doc: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    let Some(entries) = doc.as_object() else {
+        bail!(
+            "{} must be a JSON object keyed by account name",
+            path.display()
+        );
+    };
+    entries
+        .iter()
+        .map(|(name, v)| {
+            let fixture = parse_fixture(v)
+                .with_context(|| format!("Invalid fixture `{}` in {}", name, path.display()))?;
+            Ok((name.clone(), fixture))
+        })
+        .collect()
+}
+
+fn parse_fixture(v: &Value) -> Result<AccountFixture> {
+    let lamports = v["lamports"]
+        .as_u64()
+        .context("`lamports` must be an unsigned integer")?;
+    let owner = v["owner"]
+        .as_str()
+        .and_then(|s| s.parse::<Address>().ok())
+        .context("`owner` must be a base58 pubkey")?;
+    let data = match &v["data_base64"] {
+        Value::Null => Vec::new(),
+        Value::String(s) => STANDARD
+            .decode(s)
+            .map_err(|e| anyhow::anyhow!("`data_base64` is not valid base64: {}", e))?,
+        _ => bail!("`data_base64` must be a string"),
+    };
+    Ok(AccountFixture {
+        lamports,
+        owner,
+        data,
+    })
+}
+
+// Reject fixtures for names no instruction of the loaded programs declares, like the
+// `--instruction` filter does, so a typo doesn't silently fall back to a generated key.
+pub fn validate_fixture_names(programs: &[ProgramSpec], fixtures: &AccountFixtures) -> Result<()> {
+    let names: BTreeSet<&str> = programs
+        .iter()
+        .flat_map(|p| &p.instructions)
+        .flat_map(|ix| ix.accounts.iter().map(|a| a.name.as_str()))
+        .collect();
+    for name in fixtures.keys() {
+        if !names.contains(name.as_str()) {
+            bail!(
+                "--fixtures: no instruction has an account named `{}`. Available accounts: {}",
+                name,
+                names.iter().copied().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+// Load an `--assertions` file: a JSON array of
+// `{ "instruction", "account", "offset", "expected_hex" | "expected_u64" }` objects, where
+// `expected_u64` is shorthand for its 8 little-endian bytes.
+pub fn load_assertions(path: &Path) -> Result<Vec<AccountAssertion>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed reading {}", path.display()))?;
+    let doc: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    let Some(entries) = doc.as_array() else {
+        bail!("{} must be a JSON array of assertions", path.display());
+    };
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            parse_assertion(v)
+                .with_context(|| format!("Invalid assertion #{} in {}", i, path.display()))
+        })
+        .collect()
+}
+
+fn parse_assertion(v: &Value) -> Result<AccountAssertion> {
+    let field = |key: &str| {
+        v[key]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .with_context(|| format!("`{}` must be a non-empty string", key))
+    };
+    let offset = v["offset"]
+        .as_u64()
+        .and_then(|n| usize::try_from(n).ok())
+        .context("`offset` must be an unsigned integer")?;
+    let expected = match (&v["expected_hex"], &v["expected_u64"]) {
+        (Value::String(h), Value::Null) => decode_hex(h)?,
+        (Value::Null, n) if !n.is_null() => n
+            .as_u64()
+            .context("`expected_u64` must be an unsigned integer")?
+            .to_le_bytes()
+            .to_vec(),
+        _ => bail!("exactly one of `expected_hex` and `expected_u64` is required"),
+    };
+    if expected.is_empty() {
+        bail!("`expected_hex` must not be empty");
+    }
+    Ok(AccountAssertion {
+        instruction: field("instruction")?,
+        account: field("account")?,
+        offset,
+        expected,
+    })
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    if s.len() % 2 != 0 || !s.is_ascii() {
+        bail!("`expected_hex` must have an even number of hex digits");
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16)
+                .map_err(|_| anyhow::anyhow!("`expected_hex` is not valid hex: {}", s))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn fixtures_decode_owner_and_base64_data() {
+        let vault = parse_fixture(&json!({
+            "lamports": 5_000_000,
+            "owner": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "data_base64": "AQID"
+        }))
+        .unwrap();
+        assert_eq!(vault.lamports, 5_000_000);
+        assert_eq!(
+            vault.owner.to_string(),
+            "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS"
+        );
+        assert_eq!(vault.data, [1, 2, 3]);
+
+        let empty = parse_fixture(&json!({
+            "lamports": 0,
+            "owner": "11111111111111111111111111111111"
+        }))
+        .unwrap();
+        assert!(empty.data.is_empty());
+
+        let bad = json!({ "lamports": 1, "owner": "not-a-key" });
+        assert!(parse_fixture(&bad)
+            .unwrap_err()
+            .to_string()
+            .contains("`owner`"));
+        let bad = json!({ "lamports": 1, "owner": "11111111111111111111111111111111", "data_base64": "@@" });
+        assert!(parse_fixture(&bad)
+            .unwrap_err()
+            .to_string()
+            .contains("base64"));
+    }
+
+    #[test]
+    fn assertions_take_hex_or_u64_expectations() {
+        let balance = parse_assertion(&json!({
+            "instruction": "deposit",
+            "account": "vault",
+            "offset": 40,
+            "expected_u64": 1_000
+        }))
+        .unwrap();
+        assert_eq!(balance.offset, 40);
+        assert_eq!(balance.expected, 1_000u64.to_le_bytes());
+
+        let authority = parse_assertion(&json!({
+            "instruction": "initialize_vault",
+            "account": "vault",
+            "offset": 0,
+            "expected_hex": "d3f0Ab"
+        }))
+        .unwrap();
+        assert_eq!(authority.expected, [0xd3, 0xf0, 0xab]);
+
+        let both = json!({
+            "instruction": "deposit", "account": "vault", "offset": 0,
+            "expected_hex": "00", "expected_u64": 0
+        });
+        assert!(parse_assertion(&both).is_err());
+        let odd = json!({ "instruction": "deposit", "account": "vault", "offset": 0, "expected_hex": "abc" });
+        assert!(parse_assertion(&odd).is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use crate::binaries::load_program_binaries;
+use crate::cases::{
+    base_instruction_data, deployed_programs, fuzz_case, random_args, run_single_case, CaseRun,
+    Harness, Rng,
+};
+use crate::classify::error_class;
+use crate::exit::Failure;
+use crate::specs::load_program_specs;
+use crate::types::{
+    EdgeCase, Expectation, FuzzOptions, FuzzPerturbation, InstructionSpec, Mutation, ProgramSpec,
+};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Error text / log fragments of a program that crashed instead of returning an error.
+const PANIC_MARKERS: &[&str] = &[
+    "panicked at",
+    "ProgramFailedToComplete",
+    "Access violation",
+    "memory allocation failed",
+];
+
+// Most data bytes one `data_bytes` perturbation overwrites.
+const MAX_DATA_WRITES: usize = 3;
+
+// An instruction the fuzzer can target, with the zero-arg payload perturbations start from.
+struct Target<'a> {
+    program: &'a ProgramSpec,
+    ix: &'a InstructionSpec,
+    base_data: Vec<u8>,
+}
+
+// Case that crashed the program or succeeded despite a must-fail perturbation.
+struct Finding {
+    iteration: usize,
+    case: EdgeCase,
+    reason: &'static str,
+    run: CaseRun,
+}
+
+// Drive `fuzz`: random perturbations of every encodable instruction for a fixed iteration
+// budget. The `test` flow is not involved; only the loading and execution pipeline is shared.
+pub fn run_fuzz(project_dir: &str, opts: &FuzzOptions) -> Result<()> {
+    let project_root = Path::new(project_dir);
+    let idl_dir = project_root.join("target").join("idl");
+    let deploy_dir = project_root.join("target").join("deploy");
+    if !idl_dir.exists() || !deploy_dir.exists() {
+        bail!(Failure::Preflight(format!(
+            "No IDL/deploy artifacts under {}. Run `anchor build` first.",
+            project_root.join("target").display()
+        )));
+    }
+
+    let (programs, _) = load_program_specs(&idl_dir, &deploy_dir, &[], &[])?;
+    let binaries = load_program_binaries(&programs)?;
+    let deployed = deployed_programs(&programs, &binaries);
+    let targets: Vec<Target> = deployed
+        .iter()
+        .flat_map(|(p, _)| {
+            p.instructions.iter().filter_map(move |ix| {
+                let base_data = base_instruction_data(p, ix).ok()?;
+                Some(Target {
+                    program: p,
+                    ix,
+                    base_data,
+                })
+            })
+        })
+        .collect();
+    if targets.is_empty() {
+        bail!("No deployable program has an instruction the fuzzer can encode");
+    }
+
+    // Without --seed, pick one from the clock and print it so the run can be repeated.
+    let seed = opts.seed.unwrap_or_else(clock_seed);
+    println!("Running anchor-suite fuzz");
+    println!("seed: {} (pass --seed {} to reproduce)", seed, seed);
+    println!("targets: {} instructions", targets.len());
+
+    let mut rng = Rng(seed);
+    let mut signatures: BTreeMap<String, usize> = BTreeMap::new();
+    let mut findings = Vec::new();
+    for iteration in 0..opts.iterations {
+        let target = &targets[rng.below(targets.len())];
+        let (perturbation, must_fail) = draw_perturbation(&mut rng, target);
+        let expectation = if must_fail {
+            Expectation::MustFail
+        } else {
+            Expectation::Any
+        };
+        let case = fuzz_case(
+            target.program,
+            target.ix,
+            iteration,
+            perturbation,
+            expectation,
+        );
+        let run = run_single_case(&deployed, &case, Harness::default());
+
+        let signature = run
+            .error
+            .as_deref()
+            .map_or_else(|| "ok".to_string(), error_class);
+        *signatures.entry(signature).or_default() += 1;
+        if let Some(reason) = finding_reason(&case, &run) {
+            findings.push(Finding {
+                iteration,
+                case,
+                reason,
+                run,
+            });
+        }
+    }
+
+    let failures_dir = project_root
+        .join("target")
+        .join("anchor-suite")
+        .join("fuzz-failures");
+    // Files are named by iteration, so findings of an earlier run would be indistinguishable.
+    if failures_dir.exists() {
+        fs::remove_dir_all(&failures_dir)
+            .with_context(|| format!("Failed to clear {}", failures_dir.display()))?;
+    }
+    if !findings.is_empty() {
+        fs::create_dir_all(&failures_dir)
+            .with_context(|| format!("Failed to create {}", failures_dir.display()))?;
+    }
+    for f in &findings {
+        let path = failures_dir.join(format!("{}.json", f.case.id));
+        let doc = serde_json::to_string_pretty(&finding_json(seed, f))?;
+        fs::write(&path, doc).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    println!("{:-^60}", " Summary ");
+    println!("iterations: {}", opts.iterations);
+    println!("unique_error_signatures: {}", signatures.len());
+    for (signature, n) in &signatures {
+        println!("  {}: {}", signature, n);
+    }
+    println!("findings: {}", findings.len());
+    for f in &findings {
+        println!(
+            "  [{}] {} ({}) -> {}",
+            f.reason,
+            f.case.id,
+            f.case.mutation.label(),
+            failures_dir.join(format!("{}.json", f.case.id)).display()
+        );
+    }
+    Ok(())
+}
+
+// Seed used when none is given; always printed so the run can be repeated.
+pub fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+// Pick a perturbation the target supports, and whether the program must reject it.
+fn draw_perturbation(rng: &mut Rng, target: &Target) -> (FuzzPerturbation, bool) {
+    let ix = target.ix;
+    let pdas: Vec<_> = ix
+        .accounts
+        .iter()
+        .filter(|a| !a.pda_seeds.is_empty())
+        .collect();
+
+    let mut kinds = vec!["data_bytes"];
+    let types = &target.program.types;
+    if !ix.args.is_empty() && random_args(ix, types, &mut Rng(0)).is_ok() {
+        kinds.push("arg_values");
+    }
+    if ix.accounts.len() >= 2 {
+        kinds.push("account_order");
+    }
+    if !pdas.is_empty() {
+        kinds.push("pda_seed");
+    }
+
+    match kinds[rng.below(kinds.len())] {
+        "arg_values" => {
+            let args = random_args(ix, types, rng).unwrap_or_default();
+            let values = ix.args.iter().map(|a| a.name.clone()).zip(args).collect();
+            (FuzzPerturbation::ArgValues { values }, false)
+        }
+        "account_order" => {
+            // Fisher-Yates over IDL positions.
+            let mut order: Vec<usize> = (0..ix.accounts.len()).collect();
+            for i in (1..order.len()).rev() {
+                order.swap(i, rng.below(i + 1));
+            }
+            // Moving a signer or PDA into another slot must trip a signer/seeds check.
+            let must_fail = order.iter().enumerate().any(|(i, &from)| {
+                let acc = &ix.accounts[from];
+                i != from && (acc.signer || !acc.pda_seeds.is_empty())
+            });
+            (FuzzPerturbation::AccountOrder { order }, must_fail)
+        }
+        "pda_seed" => {
+            let acc = pdas[rng.below(pdas.len())];
+            let perturbation = FuzzPerturbation::PdaSeed {
+                account: acc.name.clone(),
+                seed_index: rng.below(acc.pda_seeds.len()),
+                xor: 1 + rng.below(255) as u8,
+            };
+            (perturbation, true)
+        }
+        _ => {
+            let data = &target.base_data;
+            let writes: Vec<(usize, u8)> = (0..1 + rng.below(MAX_DATA_WRITES))
+                .map(|_| (rng.below(data.len()), rng.byte()))
+                .collect();
+            // A changed discriminator byte can't dispatch to this instruction.
+            let must_fail = writes
+                .iter()
+                .any(|&(offset, value)| offset < ix.discriminator.len() && data[offset] != value);
+            (FuzzPerturbation::DataBytes { writes }, must_fail)
+        }
+    }
+}
+
+// Why an iteration is worth keeping: a crash rather than a clean error, or success under a
+// must-fail perturbation.
+fn finding_reason(case: &EdgeCase, run: &CaseRun) -> Option<&'static str> {
+    let crashed = |text: &str| PANIC_MARKERS.iter().any(|m| text.contains(m));
+    if run.error.as_deref().is_some_and(crashed) || run.logs.iter().any(|l| crashed(l)) {
+        return Some("panic");
+    }
+    if run.error.is_none() && case.expectation == Expectation::MustFail {
+        return Some("unexpected_success");
+    }
+    None
+}
+
+// Replay file: program, instruction, the exact data and accounts sent, and the outcome.
+fn finding_json(seed: u64, f: &Finding) -> Value {
+    let sent = f.run.sent.as_ref();
+    json!({
+        "seed": seed,
+        "iteration": f.iteration,
+        "case": f.case.id,
+        "display_name": f.case.display_name,
+        "reason": f.reason,
+        "idl_file": f.case.idl_file,
+        "program_id": sent.map_or(f.case.program_id, |s| s.program_id).to_string(),
+        "instruction": f.case.instruction.name,
+        "perturbation": f.case.mutation.label(),
+        "perturbation_detail": perturbation_json(&f.case.mutation),
+        "setup_chain": f.case.setup_chain,
+        "data_hex": sent.map(|s| hex(&s.data)),
+        "accounts": sent.map(|s| {
+            s.accounts
+                .iter()
+                .map(|(name, meta)| {
+                    json!({
+                        "name": name,
+                        "pubkey": meta.pubkey.to_string(),
+                        "signer": meta.is_signer,
+                        "writable": meta.is_writable
+                    })
+                })
+                .collect::<Vec<_>>()
+        }),
+        "error": f.run.error,
+        "logs": f.run.logs,
+    })
+}
+
+fn perturbation_json(mutation: &Mutation) -> Value {
+    let Mutation::Fuzz(p) = mutation else {
+        return Value::Null;
+    };
+    match p {
+        FuzzPerturbation::DataBytes { writes } => json!({
+            "writes": writes
+                .iter()
+                .map(|(offset, value)| json!({ "offset": offset, "value": value }))
+                .collect::<Vec<_>>()
+        }),
+        FuzzPerturbation::ArgValues { values } => json!({
+            "args": values
+                .iter()
+                .map(|(name, bytes)| json!({ "name": name, "hex": hex(bytes) }))
+                .collect::<Vec<_>>()
+        }),
+        FuzzPerturbation::AccountOrder { order } => json!({ "order": order }),
+        FuzzPerturbation::PdaSeed {
+            account,
+            seed_index,
+            xor,
+        } => json!({ "account": account, "seed_index": seed_index, "xor": xor }),
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut out, b| {
+            let _ = write!(out, "{:02x}", b);
+            out
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_draws_the_same_perturbations() {
+        let ix = crate::specs::parse_instruction(&json!({
+            "name": "deposit",
+            "discriminator": [242, 35, 198, 137, 82, 225, 242, 182],
+            "accounts": [
+                { "name": "vault", "writable": true, "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                    { "kind": "account", "path": "user" }
+                ]}},
+                { "name": "user", "writable": true, "signer": true }
+            ],
+            "args": [{ "name": "amount", "type": "u64" }]
+        }))
+        .unwrap();
+        let program = crate::specs::program_from_idl(
+            &json!({ "instructions": [] }),
+            "vault.json".to_string(),
+            crate::types::IdlFormat::Modern,
+            "0.1.0".to_string(),
+            solana_address::Address::default(),
+            std::path::PathBuf::from("vault.so"),
+        );
+        let target = Target {
+            program: &program,
+            ix: &ix,
+            base_data: base_instruction_data(&program, &ix).unwrap(),
+        };
+        let draw = |seed| {
+            let mut rng = Rng(seed);
+            (0..20)
+                .map(|_| {
+                    let (p, must_fail) = draw_perturbation(&mut rng, &target);
+                    format!("{:?} {}", p, must_fail)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+        // Every kind this instruction supports shows up within a few draws.
+        let all = draw(42).join(" ");
+        for kind in ["DataBytes", "ArgValues", "AccountOrder", "PdaSeed"] {
+            assert!(all.contains(kind), "{} never drawn", kind);
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::cases::{find_pda, known_account_address};
+use crate::report::create_parent_dir;
+use crate::types::{CheckResult, ExecutedCase, InstructionSpec, ProgramSpec, SeedSpec};
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Inline so the file renders from `file://` with no network access.
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { margin-bottom: 0.25rem; }
+.meta { color: #59636e; margin-top: 0; }
+.badge { display: inline-block; padding: 0.1rem 0.6rem; border-radius: 4px; color: #fff; font-weight: 600; }
+.badge.pass { background: #1a7f37; }
+.badge.fail { background: #cf222e; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; }
+th, td { border: 1px solid #d1d9e0; padding: 0.3rem 0.5rem; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+tr.pass td.result { background: #dafbe1; }
+tr.fail td.result { background: #ffebe9; font-weight: 600; }
+code, pre { font-family: ui-monospace, monospace; font-size: 0.85rem; }
+pre { white-space: pre-wrap; word-break: break-all; margin: 0.3rem 0; }
+.filters { margin: 0.5rem 0 1rem; }
+.filters input { width: 20rem; }
+";
+
+// Text and pass/fail filters over the case rows.
+const SCRIPT: &str = "
+function filterCases() {
+  var text = document.getElementById('case-text').value.toLowerCase();
+  var status = document.getElementById('case-status').value;
+  document.querySelectorAll('#cases tbody tr').forEach(function (row) {
+    var shown = row.textContent.toLowerCase().indexOf(text) !== -1
+      && (status === 'all' || row.classList.contains(status));
+    row.style.display = shown ? '' : 'none';
+  });
+}
+";
+
+// Self-contained `report.html` next to report.json: summary header, filterable case table with
+// expandable error/log details, and the PDAs each program declares.
+pub fn write_html_report(
+    project_root: &Path,
+    checks: &[CheckResult],
+    programs: &[ProgramSpec],
+    executed: &[ExecutedCase],
+    started: SystemTime,
+) -> Result<PathBuf> {
+    let path = project_root
+        .join("target")
+        .join("anchor-suite")
+        .join("report.html");
+    let doc = html(checks, programs, executed, started, SystemTime::now());
+    create_parent_dir(&path)?;
+    fs::write(&path, doc).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn html(
+    checks: &[CheckResult],
+    programs: &[ProgramSpec],
+    executed: &[ExecutedCase],
+    started: SystemTime,
+    finished: SystemTime,
+) -> String {
+    let passed = executed.iter().filter(|c| c.passed).count();
+    let checks_failed = checks.iter().filter(|c| !c.ok).count();
+    let ok = checks_failed == 0 && passed == executed.len();
+
+    let mut out = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    out.push_str("<meta charset=\"utf-8\">\n<title>anchor-suite report</title>\n");
+    let _ = writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(
+        out,
+        "<h1>anchor-suite report <span class=\"badge {}\">{}</span></h1>",
+        if ok { "pass" } else { "fail" },
+        if ok { "PASS" } else { "FAIL" }
+    );
+    let _ = writeln!(
+        out,
+        "<p class=\"meta\">started {} &middot; finished {}</p>",
+        utc_timestamp(started),
+        utc_timestamp(finished)
+    );
+    let _ = writeln!(
+        out,
+        "<p>{} of {} cases passed, {} failed; {} of {} checks passed</p>",
+        passed,
+        executed.len(),
+        executed.len() - passed,
+        checks.len() - checks_failed,
+        checks.len()
+    );
+
+    out.push_str("<h2>Checks</h2>\n<table>\n");
+    out.push_str("<thead><tr><th>check</th><th>result</th><th>detail</th></tr></thead>\n<tbody>\n");
+    for c in checks {
+        let hint = c
+            .hint
+            .as_deref()
+            .map(|h| format!("<br><em>{}</em>", escape(h)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "<tr class=\"{}\"><td><code>{}</code></td><td class=\"result\">{}</td><td>{}{}</td></tr>",
+            if c.ok { "pass" } else { "fail" },
+            escape(c.name),
+            if c.ok { "pass" } else { "FAIL" },
+            escape(&c.detail),
+            hint
+        );
+    }
+    out.push_str("</tbody>\n</table>\n");
+
+    out.push_str("<h2>Cases</h2>\n<div class=\"filters\">\n");
+    out.push_str(
+        "<input id=\"case-text\" type=\"search\" placeholder=\"Filter by name, instruction, mutation, error\" oninput=\"filterCases()\">\n",
+    );
+    out.push_str("<select id=\"case-status\" onchange=\"filterCases()\">");
+    out.push_str("<option value=\"all\">all</option><option value=\"fail\">failing</option>");
+    out.push_str("<option value=\"pass\">passing</option></select>\n</div>\n");
+    out.push_str("<table id=\"cases\">\n<thead><tr><th>case</th><th>instruction</th>");
+    out.push_str("<th>mutation</th><th>expected</th><th>actual</th><th>result</th>");
+    out.push_str("<th>rule</th><th>CU</th><th>details</th></tr></thead>\n<tbody>\n");
+    for c in executed {
+        let expected = match c.expected_success {
+            Some(true) => "success",
+            Some(false) => "failure",
+            None => "any",
+        };
+        let _ = writeln!(
+            out,
+            "<tr class=\"{}\"><td><code>{}</code></td><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td><td class=\"result\">{}</td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            if c.passed { "pass" } else { "fail" },
+            escape(&c.display_name),
+            escape(&c.instruction),
+            escape(&c.mutation),
+            expected,
+            if c.actual_success { "success" } else { "failure" },
+            if c.passed { "pass" } else { "FAIL" },
+            escape(&c.rule),
+            c.compute_units.map(|cu| cu.to_string()).unwrap_or_default(),
+            case_details(c)
+        );
+    }
+    out.push_str("</tbody>\n</table>\n");
+
+    out.push_str("<h2>PDAs</h2>\n");
+    for p in programs {
+        let _ = writeln!(
+            out,
+            "<h3>{} <code>{}</code></h3>",
+            escape(&p.idl_file),
+            p.program_id
+        );
+        let pdas = program_pdas(p);
+        if pdas.is_empty() {
+            out.push_str("<p>No PDA accounts declared.</p>\n");
+            continue;
+        }
+        out.push_str("<table>\n<thead><tr><th>account</th><th>seeds</th>");
+        out.push_str("<th>instructions</th><th>address</th></tr></thead>\n<tbody>\n");
+        for pda in &pdas {
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                escape(&pda.account),
+                escape(&pda.seeds.join(", ")),
+                escape(&pda.instructions.join(", ")),
+                match &pda.address {
+                    Some((addr, bump)) => format!("<code>{}</code> (bump {})", addr, bump),
+                    None => "dynamic".to_string(),
+                }
+            );
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+
+    let _ = writeln!(out, "<script>{}</script>\n</body>\n</html>", SCRIPT);
+    out
+}
+
+// Collapsed id, error and full (capped) logs of one case.
+fn case_details(c: &ExecutedCase) -> String {
+    let mut body = format!("<div>id <code>{}</code></div>", escape(&c.id));
+    if let Some(error) = &c.error {
+        let name = c
+            .error_name
+            .as_deref()
+            .map(|n| format!(" ({})", escape(n)))
+            .unwrap_or_default();
+        let _ = write!(body, "<div>error{}</div><pre>{}</pre>", name, escape(error));
+    }
+    if !c.logs.is_empty() {
+        let _ = write!(
+            body,
+            "<div>logs</div><pre>{}</pre>",
+            escape(&c.logs.join("\n"))
+        );
+    }
+    format!(
+        "<details{}><summary>show</summary>{}</details>",
+        if c.passed { "" } else { " open" },
+        body
+    )
+}
+
+// A PDA account as declared in the IDL, merged across the instructions that use it.
+struct PdaRow {
+    account: String,
+    seeds: Vec<String>,
+    instructions: Vec<String>,
+    // Set when no seed depends on runtime input.
+    address: Option<(String, u8)>,
+}
+
+fn program_pdas(p: &ProgramSpec) -> Vec<PdaRow> {
+    let mut rows: Vec<PdaRow> = Vec::new();
+    for ix in &p.instructions {
+        for acc in ix.accounts.iter().filter(|a| !a.pda_seeds.is_empty()) {
+            if let Some(row) = rows.iter_mut().find(|r| r.account == acc.name) {
+                row.instructions.push(ix.name.clone());
+                continue;
+            }
+            rows.push(PdaRow {
+                account: acc.name.clone(),
+                seeds: acc.pda_seeds.iter().map(SeedSpec::label).collect(),
+                instructions: vec![ix.name.clone()],
+                address: static_seeds(ix, &acc.pda_seeds).map(|seeds| {
+                    let (addr, bump) = find_pda(&seeds, &p.program_id);
+                    (addr.to_string(), bump)
+                }),
+            });
+        }
+    }
+    rows
+}
+
+// Seed bytes when every seed is a const or an account with a fixed address.
+fn static_seeds(ix: &InstructionSpec, seeds: &[SeedSpec]) -> Option<Vec<Vec<u8>>> {
+    seeds
+        .iter()
+        .map(|seed| match seed {
+            SeedSpec::Const(bytes) => Some(bytes.clone()),
+            SeedSpec::Account(path) => ix
+                .accounts
+                .iter()
+                .find(|a| a.name == *path)
+                .and_then(|a| a.address)
+                .or_else(|| known_account_address(path))
+                .map(|a| a.to_bytes().to_vec()),
+            SeedSpec::Arg(_) => None,
+        })
+        .collect()
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+// `YYYY-MM-DDTHH:MM:SSZ` without a date crate (days-to-civil from the proleptic Gregorian
+// calendar).
+fn utc_timestamp(t: SystemTime) -> String {
+    let secs = t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::executed;
+    use std::time::Duration;
+
+    #[test]
+    fn failing_cases_are_open_and_text_is_escaped() {
+        let case = |id: &str, passed: bool, error: Option<&str>| ExecutedCase {
+            expected_success: Some(true),
+            actual_success: error.is_none(),
+            rule: "builtin:must_succeed".to_string(),
+            logs: vec!["Program log: <script>".to_string()],
+            compute_units: Some(1200),
+            ..executed(&format!("vault.json_{}", id), passed, error)
+        };
+        let executed = [
+            case("ok", true, None),
+            case("bad", false, Some("custom program error: 0x1770")),
+        ];
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let doc = html(&[], &[], &executed, start, start + Duration::from_secs(61));
+
+        assert!(doc.contains("<span class=\"badge fail\">FAIL</span>"));
+        assert!(doc.contains("started 2023-11-14T22:13:20Z &middot; finished 2023-11-14T22:14:21Z"));
+        assert!(doc.contains("1 of 2 cases passed, 1 failed"));
+        assert!(doc.contains("<tr class=\"fail\"><td><code>vault.json_bad</code>"));
+        assert_eq!(doc.matches("<details open>").count(), 1);
+        assert!(doc.contains("Program log: &lt;script&gt;"));
+        assert!(
+            !doc.contains("http"),
+            "report must not load external assets"
+        );
+    }
+}
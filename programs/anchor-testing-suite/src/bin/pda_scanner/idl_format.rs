@@ -0,0 +1,250 @@
+use crate::cases::known_account_address;
+use crate::types::IdlFormat;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+// Detect the IDL layout and the version string it advertises.
+pub fn detect_format(idl: &Value) -> (IdlFormat, String) {
+    // Anchor >= 0.30 writes `metadata.spec` and per-instruction discriminators.
+    if let Some(spec) = idl["metadata"]["spec"].as_str() {
+        return (IdlFormat::Modern, spec.to_string());
+    }
+    let has_discriminators = idl["instructions"]
+        .as_array()
+        .map(|ixs| ixs.iter().any(|ix| ix["discriminator"].is_array()))
+        .unwrap_or(false);
+    if has_discriminators {
+        let version = idl["metadata"]["version"].as_str().unwrap_or("unknown");
+        return (IdlFormat::Modern, version.to_string());
+    }
+
+    // Older IDLs carry `version` + `name` at the root instead of under `metadata`.
+    let version = idl["version"].as_str().unwrap_or("unknown");
+    (IdlFormat::Legacy, version.to_string())
+}
+
+// Rewrite an IDL into the modern layout so the rest of the pipeline only handles one shape.
+pub fn normalize(idl: &Value) -> (IdlFormat, String, Value) {
+    let (format, version) = detect_format(idl);
+    let normalized = match format {
+        // Spec files are synthesized directly in the modern layout.
+        IdlFormat::Modern | IdlFormat::Spec => idl.clone(),
+        IdlFormat::Legacy => normalize_legacy(idl),
+    };
+    (format, version, normalized)
+}
+
+fn normalize_legacy(idl: &Value) -> Value {
+    let mut out = idl.clone();
+
+    // Legacy keeps the program name at the root; modern readers look under `metadata`.
+    if let Some(name) = idl["name"].as_str() {
+        if !out["metadata"].is_object() {
+            out["metadata"] = json!({});
+        }
+        if out["metadata"]["name"].is_null() {
+            out["metadata"]["name"] = json!(name);
+        }
+    }
+
+    if let Some(ixs) = idl["instructions"].as_array() {
+        out["instructions"] = Value::Array(ixs.iter().map(normalize_instruction).collect());
+    }
+
+    // Legacy inlines account layouts under `accounts`; modern splits them into `types`.
+    let mut types: Vec<Value> = idl["types"]
+        .as_array()
+        .map(|ts| ts.iter().map(normalize_type_def).collect())
+        .unwrap_or_default();
+    if let Some(accs) = idl["accounts"].as_array() {
+        let mut accounts = Vec::new();
+        for a in accs {
+            let name = match a["name"].as_str() {
+                Some(n) => n,
+                None => continue,
+            };
+            accounts.push(json!({
+                "name": name,
+                "discriminator": account_discriminator(name),
+            }));
+            types.push(normalize_type_def(a));
+        }
+        out["accounts"] = Value::Array(accounts);
+    }
+    out["types"] = Value::Array(types);
+    out
+}
+
+// Normalize field types inside a legacy struct/enum type definition.
+fn normalize_type_def(def: &Value) -> Value {
+    let mut out = def.clone();
+    if let Some(fields) = def["type"]["fields"].as_array() {
+        out["type"]["fields"] = Value::Array(
+            fields
+                .iter()
+                .map(|f| {
+                    let name = to_snake_case(f["name"].as_str().unwrap_or("field"));
+                    json!({ "name": name, "type": normalize_type(&f["type"]) })
+                })
+                .collect(),
+        );
+    }
+    if let Some(variants) = def["type"]["variants"].as_array() {
+        out["type"]["variants"] = Value::Array(
+            variants
+                .iter()
+                .map(|v| {
+                    let mut v = v.clone();
+                    if let Some(fields) = v["fields"].as_array().cloned() {
+                        v["fields"] = Value::Array(
+                            fields
+                                .iter()
+                                .map(|f| {
+                                    if f["type"].is_null() {
+                                        normalize_type(f)
+                                    } else {
+                                        let name = to_snake_case(f["name"].as_str().unwrap_or("field"));
+                                        json!({ "name": name, "type": normalize_type(&f["type"]) })
+                                    }
+                                })
+                                .collect(),
+                        );
+                    }
+                    v
+                })
+                .collect(),
+        );
+    }
+    out
+}
+
+fn normalize_instruction(ix: &Value) -> Value {
+    let raw_name = ix["name"].as_str().unwrap_or("");
+    let name = to_snake_case(raw_name);
+
+    let mut accounts = Vec::new();
+    if let Some(accs) = ix["accounts"].as_array() {
+        push_legacy_accounts(accs, &mut accounts);
+    }
+
+    let args: Vec<Value> = ix["args"]
+        .as_array()
+        .map(|a| {
+            a.iter()
+                .map(|arg| {
+                    json!({
+                        "name": to_snake_case(arg["name"].as_str().unwrap_or("arg")),
+                        "type": normalize_type(&arg["type"]),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    json!({
+        "name": name,
+        "discriminator": instruction_discriminator(&name),
+        "accounts": accounts,
+        "args": args,
+    })
+}
+
+// Flatten legacy accounts in declaration order. Composite `#[derive(Accounts)]` fields show up as
+// nested `{ name, accounts: [...] }` groups, but on the wire they are just consecutive metas.
+fn push_legacy_accounts(accs: &[Value], out: &mut Vec<Value>) {
+    for a in accs {
+        if let Some(group) = a["accounts"].as_array() {
+            push_legacy_accounts(group, out);
+            continue;
+        }
+        let name = to_snake_case(a["name"].as_str().unwrap_or("unknown"));
+        let mut acc = Map::new();
+        acc.insert("name".into(), json!(name));
+        acc.insert("writable".into(), json!(a["isMut"].as_bool().unwrap_or(false)));
+        acc.insert("signer".into(), json!(a["isSigner"].as_bool().unwrap_or(false)));
+        // Modern IDLs pin program/sysvar addresses; legacy ones only name the account.
+        if let Some(address) = known_account_address(&name) {
+            acc.insert("address".into(), json!(address.to_string()));
+        }
+        if let Some(seeds) = a["pda"]["seeds"].as_array() {
+            let seeds: Vec<Value> = seeds.iter().filter_map(normalize_seed).collect();
+            acc.insert("pda".into(), json!({ "seeds": seeds }));
+        }
+        out.push(Value::Object(acc));
+    }
+}
+
+// Legacy seeds carry a `type` tag and a typed `value` instead of raw const bytes. Some
+// generators put the seed kind under `type` too (`{"type": "const", "value": "vault"}`).
+fn normalize_seed(seed: &Value) -> Option<Value> {
+    let kind = seed["kind"]
+        .as_str()
+        .or_else(|| seed["type"].as_str().filter(|t| matches!(*t, "const" | "account" | "arg")))?;
+    match kind {
+        "const" => {
+            let bytes: Vec<u8> = match (&seed["value"], seed["type"].as_str()) {
+                (Value::String(s), _) => s.as_bytes().to_vec(),
+                (Value::Array(a), _) => a.iter().filter_map(|v| v.as_u64().map(|n| n as u8)).collect(),
+                (Value::Number(n), Some("u8")) => vec![n.as_u64()? as u8],
+                _ => return None,
+            };
+            Some(json!({ "kind": "const", "value": bytes }))
+        }
+        "account" | "arg" => {
+            let path = seed["path"].as_str()?;
+            Some(json!({ "kind": kind, "path": to_snake_case(path) }))
+        }
+        _ => None,
+    }
+}
+
+// Map legacy type spellings onto their modern equivalents.
+fn normalize_type(ty: &Value) -> Value {
+    match ty {
+        Value::String(s) if s == "publicKey" => json!("pubkey"),
+        Value::Object(obj) => {
+            let mut out = Map::new();
+            for (k, v) in obj {
+                let v = match (k.as_str(), v) {
+                    ("defined", Value::String(name)) => json!({ "name": name }),
+                    ("array", Value::Array(parts)) if parts.len() == 2 => {
+                        json!([normalize_type(&parts[0]), parts[1]])
+                    }
+                    _ => normalize_type(v),
+                };
+                out.insert(k.clone(), v);
+            }
+            Value::Object(out)
+        }
+        other => other.clone(),
+    }
+}
+
+// Anchor's instruction discriminator: sha256("global:<name>")[..8].
+pub fn instruction_discriminator(name: &str) -> Vec<u8> {
+    Sha256::digest(format!("global:{}", name).as_bytes())[..8].to_vec()
+}
+
+// Anchor's account discriminator: sha256("account:<TypeName>")[..8].
+pub fn account_discriminator(name: &str) -> Vec<u8> {
+    Sha256::digest(format!("account:{}", name).as_bytes())[..8].to_vec()
+}
+
+// camelCase -> snake_case, matching how Anchor names handlers.
+fn to_snake_case(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 4);
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else {
+            out.push(c);
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    out
+}
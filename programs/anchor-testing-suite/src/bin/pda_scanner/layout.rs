@@ -0,0 +1,142 @@
+use crate::types::{AccountTypeSpec, InstructionSpec, ProgramSpec};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Anchor account discriminator prefix.
+pub const ACCOUNT_DISCRIMINATOR_LEN: usize = 8;
+
+// Borsh size of a fixed-size IDL type; dynamic types (string/bytes/vec) return an error.
+pub fn fixed_size(ty: &Value, types: &HashMap<String, Value>) -> Result<usize, String> {
+    if let Some(s) = ty.as_str() {
+        return match s {
+            "bool" | "u8" | "i8" => Ok(1),
+            "u16" | "i16" => Ok(2),
+            "u32" | "i32" | "f32" => Ok(4),
+            "u64" | "i64" | "f64" => Ok(8),
+            "u128" | "i128" => Ok(16),
+            "pubkey" => Ok(32),
+            "string" | "bytes" => Err(format!("`{}` is dynamically sized", s)),
+            other => Err(format!("unknown type `{}`", other)),
+        };
+    }
+
+    let obj = ty.as_object().ok_or("unrecognized type shape")?;
+    if let Some(parts) = obj.get("array").and_then(Value::as_array) {
+        if parts.len() == 2 {
+            let len = parts[1].as_u64().ok_or("array length is not a number")? as usize;
+            return Ok(fixed_size(&parts[0], types)? * len);
+        }
+    }
+    // Anchor reserves the Some variant's full size for options.
+    if let Some(inner) = obj.get("option") {
+        return Ok(1 + fixed_size(inner, types)?);
+    }
+    if obj.contains_key("vec") {
+        return Err("`vec` is dynamically sized".to_string());
+    }
+    if let Some(name) = defined_name(ty) {
+        let def = types
+            .get(name)
+            .ok_or_else(|| format!("type `{}` not found in IDL types", name))?;
+        return defined_size(def, types);
+    }
+
+    Err("unrecognized type shape".to_string())
+}
+
+// Size of a struct (sum of fields) or enum (tag + largest variant).
+fn defined_size(def: &Value, types: &HashMap<String, Value>) -> Result<usize, String> {
+    match def["type"]["kind"].as_str() {
+        Some("struct") => {
+            let mut total = 0;
+            for f in def["type"]["fields"].as_array().into_iter().flatten() {
+                total += fixed_size(&f["type"], types)?;
+            }
+            Ok(total)
+        }
+        Some("enum") => {
+            let mut largest = 0;
+            for v in def["type"]["variants"].as_array().into_iter().flatten() {
+                let mut size = 0;
+                for f in v["fields"].as_array().into_iter().flatten() {
+                    // Named fields carry `type`; tuple fields are bare types.
+                    let fty = if f["type"].is_null() { f } else { &f["type"] };
+                    size += fixed_size(fty, types)?;
+                }
+                largest = largest.max(size);
+            }
+            Ok(1 + largest)
+        }
+        _ => Err("unsupported type kind".to_string()),
+    }
+}
+
+// `{"defined": {"name": X}}` (modern) or `{"defined": X}` (legacy) -> X.
+pub fn defined_name(ty: &Value) -> Option<&str> {
+    let d = ty.get("defined")?;
+    d.as_str().or_else(|| d["name"].as_str())
+}
+
+// Byte range of `field` inside an account's data (discriminator included). Every field before
+// it must be fixed-size for the offset to be known.
+pub fn field_range(
+    acct: &AccountTypeSpec,
+    field: &str,
+    types: &HashMap<String, Value>,
+) -> Result<(usize, usize), String> {
+    let mut offset = ACCOUNT_DISCRIMINATOR_LEN;
+    for f in &acct.fields {
+        let size = fixed_size(&f.ty, types);
+        if f.name == field {
+            return Ok((offset, size?));
+        }
+        offset += size.map_err(|e| format!("field `{}` before `{}`: {}", f.name, field, e))?;
+    }
+    Err(format!("`{}` has no field `{}`", acct.name, field))
+}
+
+// Account type an instruction account holds, matched by name (`vault` -> `Vault`). A role
+// prefix is dropped when the full name matches no type (`destination_vault` -> `Vault`).
+pub fn account_type_for<'a>(program: &'a ProgramSpec, account: &str) -> Option<&'a AccountTypeSpec> {
+    let parts: Vec<&str> = account.split('_').collect();
+    (0..parts.len()).find_map(|skip| {
+        let want = parts[skip..].concat().to_ascii_lowercase();
+        program
+            .account_types
+            .iter()
+            .find(|t| t.name.to_ascii_lowercase() == want)
+    })
+}
+
+// Allocated space for an account: discriminator + fields. Dynamic fields are sized from the
+// instruction arg of the same name (Anchor's `space = 8 + 4 + name.len()` pattern).
+pub fn account_space(
+    acct: &AccountTypeSpec,
+    ix: &InstructionSpec,
+    types: &HashMap<String, Value>,
+    encoded_arg_len: impl Fn(&str) -> Option<usize>,
+) -> Result<usize, String> {
+    let mut total = ACCOUNT_DISCRIMINATOR_LEN;
+    for field in &acct.fields {
+        match fixed_size(&field.ty, types) {
+            Ok(n) => total += n,
+            Err(reason) => {
+                let from_arg = ix
+                    .args
+                    .iter()
+                    .find(|a| a.name == field.name)
+                    .and_then(|a| encoded_arg_len(&a.name));
+                match from_arg {
+                    Some(n) => total += n,
+                    None => {
+                        return Err(format!(
+                            "field `{}`: {} and no `{}` instruction arg to size it from",
+                            field.name, reason, field.name
+                        ))
+                    }
+                }
+            }
+        }
+    }
+    Ok(total)
+}
@@ -0,0 +1,179 @@
+use crate::cases::unresolved_input;
+use crate::exit::Failure;
+use crate::runner::{
+    deploy_dir, filter_regex, generate_cases, idl_dir, load_programs, retain_selected,
+    validate_instruction_filter, validate_mutation_kinds, InstructionFilter,
+};
+use crate::types::{EdgeCase, ProgramSpec, TestOptions};
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+
+// Drive `list`: load specs and generate cases exactly like `test` (same filters), then print
+// the case matrix instead of executing it.
+pub fn run_list(project_dir: &str, opts: &TestOptions, json_out: bool) -> Result<()> {
+    validate_mutation_kinds(&opts.mutations)?;
+    let name_regex = filter_regex(opts)?;
+    let project_root = Path::new(project_dir);
+    if opts.spec_file.is_none() {
+        let idl_dir = idl_dir(project_root, opts);
+        let deploy_dir = deploy_dir(project_root, opts);
+        if (opts.idl_files.is_empty() && !idl_dir.exists()) || !deploy_dir.exists() {
+            bail!(Failure::Preflight(format!(
+                "No IDL/deploy artifacts at {} and {}. Run `anchor build` first.",
+                idl_dir.display(),
+                deploy_dir.display()
+            )));
+        }
+    }
+
+    let (programs, _) = load_programs(project_root, opts)?;
+    if programs.is_empty() {
+        bail!(Failure::NoPrograms("No testable programs found".to_string()));
+    }
+    let filter = InstructionFilter::new(opts, name_regex.as_ref());
+    validate_instruction_filter(&programs, &filter)?;
+    let mut cases = generate_cases(&programs, opts, None)?;
+    retain_selected(&mut cases, &filter, &opts.mutations);
+    let rows: Vec<ListRow> = cases.iter().map(|c| list_row(&programs, c)).collect();
+
+    if json_out {
+        println!("{}", serde_json::to_string_pretty(&list_json(&rows))?);
+        return Ok(());
+    }
+    println!("Planned {} cases:", rows.len());
+    println!(
+        "{:<40} {:<20} {:<24} {:<36} {:<13} RESOLVABLE",
+        "ID", "PROGRAM", "INSTRUCTION", "MUTATION", "EXPECTATION"
+    );
+    for row in &rows {
+        let resolvable = match &row.unresolved {
+            None => "yes".to_string(),
+            Some(reason) => format!("no: {}", reason),
+        };
+        println!(
+            "{:<40} {:<20} {:<24} {:<36} {:<13} {}",
+            row.case.id,
+            row.case.idl_file,
+            row.case.instruction.name,
+            row.case.mutation.label(),
+            row.case.expectation.label(),
+            resolvable
+        );
+    }
+    Ok(())
+}
+
+// One planned case and, when its instruction isn't fully resolvable, the first input the
+// harness would fabricate.
+struct ListRow<'a> {
+    case: &'a EdgeCase,
+    unresolved: Option<String>,
+}
+
+fn list_row<'a>(programs: &[ProgramSpec], case: &'a EdgeCase) -> ListRow<'a> {
+    let unresolved = programs
+        .iter()
+        .find(|p| p.program_id == case.program_id && p.idl_file == case.idl_file)
+        .and_then(|p| unresolved_input(p, &case.instruction));
+    ListRow { case, unresolved }
+}
+
+// Stable `list --json` document; field names are part of the CLI contract.
+fn list_json(rows: &[ListRow]) -> Value {
+    let cases: Vec<Value> = rows
+        .iter()
+        .map(|r| {
+            json!({
+                "id": r.case.id,
+                "display_name": r.case.display_name,
+                "program": r.case.idl_file,
+                "program_id": r.case.program_id.to_string(),
+                "instruction": r.case.instruction.name,
+                "mutation": r.case.mutation.label(),
+                "expectation": r.case.expectation.label(),
+                "setup_chain": r.case.setup_chain,
+                "resolvable": r.unresolved.is_none(),
+                "unresolved_reason": r.unresolved,
+            })
+        })
+        .collect();
+    json!({
+        "tool": "anchor-suite",
+        "command": "list",
+        "case_count": cases.len(),
+        "cases": cases,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        AccountSpec, Expectation, IdlFormat, InstructionSpec, Mutation, ProgramIdSource, SeedSpec,
+    };
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn list_json_flags_instructions_with_fabricated_inputs() {
+        let account = |name: &str, seeds: Vec<SeedSpec>| AccountSpec {
+            name: name.to_string(),
+            signer: false,
+            writable: true,
+            pda_seeds: seeds,
+            address: None,
+        };
+        let ix = |name: &str, accounts: Vec<AccountSpec>| InstructionSpec {
+            name: name.to_string(),
+            discriminator: vec![0; 8],
+            accounts,
+            args: Vec::new(),
+        };
+        let init = ix(
+            "initialize",
+            vec![account("vault", vec![SeedSpec::Const(b"vault".to_vec())])],
+        );
+        let sweep = ix("sweep", vec![account("treasury", Vec::new())]);
+        let program = ProgramSpec {
+            idl_file: "test_vault".to_string(),
+            name: "test_vault".to_string(),
+            idl_format: IdlFormat::Modern,
+            idl_version: "0.1.0".to_string(),
+            program_id: "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS"
+                .parse()
+                .unwrap(),
+            program_id_source: ProgramIdSource::Idl,
+            deploy_so: PathBuf::from("test_vault.so"),
+            instructions: vec![init.clone(), sweep.clone()],
+            account_types: Vec::new(),
+            types: HashMap::new(),
+            errors: Vec::new(),
+        };
+        let case = |instruction: InstructionSpec| EdgeCase {
+            id: format!("{}_base", instruction.name),
+            display_name: format!("test_vault_{}_base", instruction.name),
+            idl_file: program.idl_file.clone(),
+            program_id: program.program_id,
+            instruction,
+            mutation: Mutation::None,
+            expectation: Expectation::Any,
+            setup_chain: Vec::new(),
+        };
+        let cases = [case(init), case(sweep)];
+        let programs = [program];
+
+        let rows: Vec<ListRow> = cases.iter().map(|c| list_row(&programs, c)).collect();
+        let doc = list_json(&rows);
+        assert_eq!(doc["command"], "list");
+        assert_eq!(doc["case_count"], 2);
+        assert_eq!(doc["cases"][0]["resolvable"], true);
+        assert_eq!(doc["cases"][0]["unresolved_reason"], Value::Null);
+        assert_eq!(doc["cases"][1]["mutation"], "none");
+        assert_eq!(doc["cases"][1]["resolvable"], false);
+        assert_eq!(
+            doc["cases"][1]["unresolved_reason"],
+            "account `treasury` is not derivable"
+        );
+    }
+}
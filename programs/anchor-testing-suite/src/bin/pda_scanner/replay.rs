@@ -0,0 +1,390 @@
+use crate::binaries::{load_program_binaries, ProgramBinary};
+use crate::cases::{deployed_programs, run_single_case, Harness};
+use crate::classify::{CaseOutcome, Classifier};
+use crate::exit::Failure;
+use crate::fixtures::load_fixtures;
+use crate::fuzz::hex;
+use crate::runner::{generate_cases, load_programs};
+use crate::types::{EdgeCase, Expectation, ProgramSpec, StateCheck, TestOptions};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Most similar ids listed when `--case-id` matches no generated case.
+const MAX_SUGGESTIONS: usize = 5;
+
+// Case ids (and display names) are derived from the IDL alone, so either one from an earlier
+// report.json selects the same regenerated case. Runs it alone and prints everything needed to
+// debug it; returns whether it matched its expectation.
+pub fn replay_case(
+    programs: &[ProgramSpec],
+    binaries: &[ProgramBinary],
+    cases: &[EdgeCase],
+    id: &str,
+    classifier: &Classifier,
+    harness: Harness,
+) -> Result<bool> {
+    let Some(case) = cases.iter().find(|c| c.id == id || c.display_name == id) else {
+        let names = cases
+            .iter()
+            .flat_map(|c| [c.id.as_str(), c.display_name.as_str()]);
+        let close = close_matches(id, names);
+        if close.is_empty() {
+            bail!("No generated case has id `{}`", id);
+        }
+        bail!(
+            "No generated case has id `{}`. Did you mean:\n  {}",
+            id,
+            close.join("\n  ")
+        );
+    };
+    let deployed = deployed_programs(programs, binaries);
+    if !deployed
+        .iter()
+        .any(|(p, _)| p.program_id == case.program_id)
+    {
+        bail!(
+            "Case `{}` belongs to {}, whose binary is invalid",
+            id,
+            case.idl_file
+        );
+    }
+
+    let run = run_single_case(&deployed, case, harness);
+    let mutation = case.mutation.label();
+    let log_matched = match &case.expectation {
+        Expectation::LogContains { substring } => {
+            Some(run.logs.iter().any(|l| l.contains(substring.as_str())))
+        }
+        _ => None,
+    };
+    let state_matched = matches!(case.expectation, Expectation::AccountState { .. })
+        .then(|| run.state_checks.iter().all(StateCheck::matched));
+    let (passed, rule) = classifier.classify(&CaseOutcome {
+        case,
+        mutation: &mutation,
+        actual_success: run.error.is_none(),
+        error: run.error.as_deref(),
+        log_matched,
+        state_matched,
+    });
+
+    println!("{:-^60}", " Case ");
+    println!("id: {}", case.id);
+    println!("display_name: {}", case.display_name);
+    println!("program: {} ({})", case.idl_file, case.program_id);
+    println!("instruction: {}", case.instruction.name);
+    println!("mutation: {}", mutation);
+    println!("expectation: {}", case.expectation.label());
+    if !run.setup.is_empty() {
+        println!("{:-^60}", " Setup Chain ");
+        for step in &run.setup {
+            match &step.error {
+                None => println!("ok    {}", step.instruction),
+                Some(e) => println!("fail  {}: {}", step.instruction, e),
+            }
+        }
+    }
+    if !run.fabricated.is_empty() {
+        println!("fabricated accounts: {}", run.fabricated.join(", "));
+    }
+    for f in &run.fabricated_fields {
+        println!("  {}.{} = {}", f.account, f.field, f.value);
+    }
+    match &run.sent {
+        Some(sent) => {
+            println!("{:-^60}", " Accounts ");
+            let width = sent
+                .accounts
+                .iter()
+                .map(|(n, _)| n.len())
+                .max()
+                .unwrap_or(0);
+            for (name, meta) in &sent.accounts {
+                println!(
+                    "{:<width$}  {:<44}  {}  {}",
+                    name,
+                    meta.pubkey.to_string(),
+                    if meta.is_signer { "signer" } else { "      " },
+                    if meta.is_writable {
+                        "writable"
+                    } else {
+                        "readonly"
+                    },
+                    width = width
+                );
+            }
+            println!("{:-^60}", " Instruction Data ");
+            println!("{} ({} bytes)", hex(&sent.data), sent.data.len());
+        }
+        None => println!("instruction: not sent (the case failed while being built)"),
+    }
+    println!("{:-^60}", " Program Logs ");
+    for line in &run.logs {
+        println!("{}", line);
+    }
+    if !run.state_checks.is_empty() {
+        println!("{:-^60}", " Account State ");
+        for check in &run.state_checks {
+            let a = &check.assertion;
+            println!(
+                "{}  {}[{}..{}]: expected {}, found {}",
+                if check.matched() { "ok  " } else { "fail" },
+                a.account,
+                a.offset,
+                a.offset + a.expected.len(),
+                hex(&a.expected),
+                check.actual.as_deref().map_or_else(|| "nothing".to_string(), hex)
+            );
+        }
+    }
+    println!("{:-^60}", " Result ");
+    if let Some(cu) = run.compute_units {
+        println!("compute_units: {}", cu);
+    }
+    println!("error: {}", run.error.as_deref().unwrap_or("none"));
+    println!("rule: {}", rule);
+    println!("passed: {}", passed);
+    Ok(passed)
+}
+
+// Drive `replay`: rebuild one case of an earlier `test` report from the IDL plus the report's
+// `replay` inputs (and `fuzz_seed`), check it is still the case the report ran, and rerun it.
+pub fn replay_from_report(report_path: &Path, id: &str, project_dir: Option<&str>) -> Result<()> {
+    let content = fs::read_to_string(report_path)
+        .with_context(|| format!("Failed reading {}", report_path.display()))?;
+    let report: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid JSON in {}", report_path.display()))?;
+    let inputs = &report["replay"];
+    if !inputs.is_object() {
+        bail!(
+            "{} has no `replay` section; rerun `test` to write a report replay can use",
+            report_path.display()
+        );
+    }
+    let recorded_cases = report["generated_cases"]
+        .as_array()
+        .map_or(&[][..], Vec::as_slice);
+    let Some(recorded) = recorded_cases
+        .iter()
+        .find(|c| c["id"].as_str() == Some(id) || c["display_name"].as_str() == Some(id))
+    else {
+        let names = recorded_cases
+            .iter()
+            .flat_map(|c| [c["id"].as_str(), c["display_name"].as_str()])
+            .flatten();
+        let close = close_matches(id, names);
+        if close.is_empty() {
+            bail!("{} has no case with id `{}`", report_path.display(), id);
+        }
+        bail!(
+            "{} has no case with id `{}`. Did you mean:\n  {}",
+            report_path.display(),
+            id,
+            close.join("\n  ")
+        );
+    };
+    let case_id = recorded["id"].as_str().unwrap_or(id);
+
+    let opts = replay_options(inputs);
+    let project_root = match project_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(
+            inputs["project_dir"]
+                .as_str()
+                .context("`replay.project_dir` is missing; pass --project-dir")?,
+        ),
+    };
+    let (programs, _) = load_programs(&project_root, &opts)?;
+    let binaries = load_program_binaries(&programs)?;
+    let cases = generate_cases(&programs, &opts, report["fuzz_seed"].as_u64())?;
+
+    // Same id but a different mutation means the IDL changed since the report was written.
+    let Some(case) = cases.iter().find(|c| c.id == case_id) else {
+        bail!(
+            "Case {} is no longer generated from the IDL under {}; it changed since the report",
+            case_id,
+            project_root.display()
+        );
+    };
+    let mutation = format!("{:?}", case.mutation);
+    if recorded["mutation"].as_str() != Some(mutation.as_str()) {
+        bail!(
+            "Case {} is now {} but the report ran {}; the IDL changed since the report",
+            case_id,
+            mutation,
+            recorded["mutation"]
+        );
+    }
+
+    let classifier = match &opts.rules_file {
+        Some(path) => Classifier::from_file(path)?,
+        None => Classifier::builtin(),
+    };
+    let fixtures = opts.fixtures.as_deref().map(load_fixtures).transpose()?;
+    println!("Replaying {} from {}", case_id, report_path.display());
+    let passed = replay_case(
+        &programs,
+        &binaries,
+        &cases,
+        case_id,
+        &classifier,
+        Harness::from_options(&opts, fixtures.as_ref()),
+    )?;
+    let executed = report["executed_cases"]
+        .as_array()
+        .and_then(|cases| cases.iter().find(|c| c["id"].as_str() == Some(case_id)));
+    match executed {
+        Some(c) => println!(
+            "reported: passed {} error {}",
+            c["passed"],
+            c["error"].as_str().unwrap_or("none")
+        ),
+        None => println!("reported: not executed"),
+    }
+    if !passed {
+        bail!(Failure::Cases(format!(
+            "Case {} did not match its expectation",
+            case_id
+        )));
+    }
+    Ok(())
+}
+
+// The `test` options a report's `replay` section recorded.
+fn replay_options(inputs: &Value) -> TestOptions {
+    let strings = |key: &str| -> Vec<String> {
+        inputs[key]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect()
+    };
+    let path = |key: &str| inputs[key].as_str().map(PathBuf::from);
+    TestOptions {
+        spec_file: path("spec_file"),
+        rules_file: path("rules_file"),
+        fixtures: path("fixtures"),
+        assertions: path("assertions"),
+        idl_dir: path("idl_dir"),
+        deploy_dir: path("deploy_dir"),
+        idl_files: strings("idl_files").into_iter().map(PathBuf::from).collect(),
+        program_ids: strings("program_ids"),
+        so_map: strings("so_map"),
+        non_empty_strings: inputs["non_empty_strings"].as_bool().unwrap_or(false),
+        fund_writable: inputs["fund_writable"].as_bool().unwrap_or(false),
+        fabricated_balance: inputs["fabricated_balance"].as_u64().unwrap_or(0),
+        duplicate_accounts_must_fail: inputs["duplicate_accounts_must_fail"]
+            .as_bool()
+            .unwrap_or(false),
+        extra_accounts_must_fail: inputs["extra_accounts_must_fail"]
+            .as_bool()
+            .unwrap_or(false),
+        expect_logs: strings("expect_logs"),
+        fuzz: inputs["fuzz"].as_u64().map(|n| n as usize),
+        ..TestOptions::default()
+    }
+}
+
+// Ids closest to `id` by edit distance, nearest first; only reasonably close ones are kept.
+fn close_matches<'a>(id: &str, ids: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let max = (id.len() / 4).max(3);
+    let mut scored: Vec<(usize, &str)> = ids
+        .map(|c| (edit_distance(id, c), c))
+        .filter(|(d, _)| *d <= max)
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, c)| c)
+        .collect()
+}
+
+// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = sub.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_ids_suggest_the_nearest_generated_ids() {
+        let ids = [
+            "test_vault.json_withdraw_wrong_pda_vault",
+            "test_vault.json_withdraw_wrong_pda_user",
+            "test_vault.json_deposit_wrong_pda_vault",
+            "test_vault.json_withdraw_truncate_data",
+        ];
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(
+            close_matches("test_vault.json_withdraw_wrong_pda_vualt", ids.into_iter()),
+            vec![
+                "test_vault.json_withdraw_wrong_pda_vault",
+                "test_vault.json_withdraw_wrong_pda_user",
+                "test_vault.json_deposit_wrong_pda_vault",
+            ]
+        );
+        assert!(close_matches("something_else", ids.into_iter()).is_empty());
+    }
+
+    #[test]
+    fn replay_section_restores_the_generation_options() {
+        let inputs = serde_json::json!({
+            "project_dir": "/work/vault",
+            "spec_file": null,
+            "rules_file": "/work/vault/rules.json",
+            "fixtures": "/work/vault/fixtures.json",
+            "assertions": "/work/vault/assertions.json",
+            "idl_dir": "/work/vault/artifacts/idl",
+            "deploy_dir": null,
+            "idl_files": [],
+            "program_ids": ["vault:Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS"],
+            "so_map": [],
+            "non_empty_strings": true,
+            "fund_writable": true,
+            "fabricated_balance": 1000000,
+            "duplicate_accounts_must_fail": true,
+            "extra_accounts_must_fail": true,
+            "expect_logs": ["deposit=Deposited"],
+            "fuzz": 3
+        });
+        let opts = replay_options(&inputs);
+        assert_eq!(opts.spec_file, None);
+        assert_eq!(
+            opts.rules_file,
+            Some(PathBuf::from("/work/vault/rules.json"))
+        );
+        assert_eq!(
+            opts.program_ids,
+            ["vault:Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS"]
+        );
+        assert!(opts.non_empty_strings && opts.duplicate_accounts_must_fail);
+        assert!(opts.extra_accounts_must_fail);
+        assert!(opts.fund_writable);
+        assert_eq!(opts.fabricated_balance, 1_000_000);
+        assert_eq!(opts.fixtures, Some(PathBuf::from("/work/vault/fixtures.json")));
+        assert_eq!(opts.assertions, Some(PathBuf::from("/work/vault/assertions.json")));
+        assert_eq!(opts.idl_dir, Some(PathBuf::from("/work/vault/artifacts/idl")));
+        assert_eq!(opts.deploy_dir, None);
+        assert!(opts.idl_files.is_empty());
+        assert_eq!(opts.expect_logs, ["deposit=Deposited"]);
+        assert_eq!(opts.fuzz, Some(3));
+        // Options that only filter or report are never replayed.
+        assert!(opts.mutations.is_empty() && opts.case_id.is_none());
+    }
+}
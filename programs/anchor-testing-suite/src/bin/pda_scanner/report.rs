@@ -1,14 +1,28 @@
-use crate::types::{CheckResult, EdgeCase, ExecutedCase, SmokeResult};
+use crate::binaries::ProgramBinary;
+use crate::cases::integer_arg_values;
+use crate::classify::error_class;
+use crate::fuzz::hex;
+use crate::runner::{deploy_dir, idl_dir};
+use crate::types::{
+    CheckResult, ComputeBudget, EdgeCase, ExecutedCase, Mutation, HarnessTimings, ProgramSpec, SequenceStep, SkipReason, SmokeResult, TestOptions,
+    UnsupportedArg, Warning,
+};
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-// Emit a minimal report when we fail during preflight checks.
-pub fn write_min_report(project_root: &Path, checks: &[CheckResult]) -> Result<()> {
-    let report_dir = project_root.join("target").join("anchor-suite");
-    fs::create_dir_all(&report_dir)?;
-    let report_path = report_dir.join("report.json");
+// Emit a minimal report when we fail during preflight checks (plus JUnit when requested, so
+// CI dashboards show the failed check).
+pub fn write_min_report(
+    project_root: &Path,
+    checks: &[CheckResult],
+    opts: &TestOptions,
+) -> Result<()> {
+    if let Some(path) = &opts.junit {
+        write_junit_report(path, checks, &[])?;
+    }
 
     let checks_json: Vec<Value> = checks
         .iter()
@@ -25,24 +39,94 @@ pub fn write_min_report(project_root: &Path, checks: &[CheckResult]) -> Result<(
     let report = json!({
         "tool": "anchor-suite",
         "step": 4,
+        "artifacts": artifacts_json(project_root, opts),
         "checks": checks_json
     });
-    fs::write(&report_path, serde_json::to_string_pretty(&report)?)?;
+    emit_report(project_root, opts.output.as_deref(), &report)?;
+    Ok(())
+}
+
+// `--output -`: the JSON report goes to stdout (and human output to stderr).
+pub fn report_to_stdout(output: Option<&Path>) -> bool {
+    output == Some(Path::new("-"))
+}
+
+// Output files may point into directories that don't exist yet.
+pub fn create_parent_dir(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
     Ok(())
 }
 
+// Where the report went (None = stdout) and the JSON that was written.
+pub struct WrittenReport {
+    pub path: Option<PathBuf>,
+    pub json: String,
+}
+
+// Write report.json to `--output` (creating its parent directories), stdout for `-`, or
+// target/anchor-suite/report.json by default.
+fn emit_report(project_root: &Path, output: Option<&Path>, report: &Value) -> Result<WrittenReport> {
+    let json = serde_json::to_string_pretty(report)?;
+    if report_to_stdout(output) {
+        println!("{}", json);
+        return Ok(WrittenReport { path: None, json });
+    }
+    let path = match output {
+        Some(path) => path.to_path_buf(),
+        None => project_root
+            .join("target")
+            .join("anchor-suite")
+            .join("report.json"),
+    };
+    create_parent_dir(&path)?;
+    fs::write(&path, &json)
+        .with_context(|| format!("Failed to write report to {}", path.display()))?;
+    Ok(WrittenReport {
+        path: Some(path),
+        json,
+    })
+}
+
+// Everything the full report is built from.
+pub struct ReportInput<'a> {
+    pub checks: &'a [CheckResult],
+    pub programs: &'a [ProgramSpec],
+    pub binaries: &'a [ProgramBinary],
+    pub unsupported: &'a [UnsupportedArg],
+    pub warnings: &'a [Warning],
+    pub generated: &'a [EdgeCase],
+    pub executed: &'a [ExecutedCase],
+    // Why each generated case without a result was skipped, keyed by case id.
+    pub skipped: &'a HashMap<String, SkipReason>,
+    // `--sequence` steps, in order; empty without `--sequence`.
+    pub sequence: &'a [SequenceStep],
+    pub smoke: &'a Option<SmokeResult>,
+    // Instructions left out by `--instruction`.
+    pub instructions_skipped: usize,
+    // Run seed of `--fuzz` random-args cases.
+    pub fuzz_seed: Option<u64>,
+    pub opts: &'a TestOptions,
+}
+
 // Emit the full report with checks, generated cases, and execution results.
-pub fn write_report(
-    project_root: &Path,
-    checks: &[CheckResult],
-    generated: &[EdgeCase],
-    executed: &[ExecutedCase],
-    smoke: &Option<SmokeResult>,
-) -> Result<PathBuf> {
-    let report_dir = project_root.join("target").join("anchor-suite");
-    fs::create_dir_all(&report_dir)
-        .with_context(|| format!("Failed to create {}", report_dir.display()))?;
-    let report_path = report_dir.join("report.json");
+pub fn write_report(project_root: &Path, input: &ReportInput) -> Result<WrittenReport> {
+    let ReportInput {
+        checks,
+        programs,
+        binaries,
+        unsupported,
+        warnings,
+        generated,
+        executed,
+        skipped,
+        sequence,
+        smoke,
+        instructions_skipped,
+        fuzz_seed,
+        opts,
+    } = *input;
 
     let checks_json: Vec<Value> = checks
         .iter()
@@ -56,16 +140,69 @@ pub fn write_report(
         })
         .collect();
 
+    let programs_json: Vec<Value> = programs
+        .iter()
+        .map(|p| {
+            json!({
+                "idl_file": p.idl_file,
+                "idl_format": p.idl_format.label(),
+                "idl_version": p.idl_version,
+                "program_id": p.program_id.to_string(),
+                "program_id_source": p.program_id_source.label(),
+                "deploy_so": p.deploy_so.display().to_string(),
+                "binary": binaries.iter().find(|b| b.program_id == p.program_id).map(|b| json!({
+                    "bytes": b.bytes.len(),
+                    "sha256": b.sha256,
+                    "valid": b.is_valid(),
+                    "error": b.invalid
+                })),
+                "instructions": p.instructions.len()
+            })
+        })
+        .collect();
+
+    let unsupported_json: Vec<Value> = unsupported
+        .iter()
+        .map(|u| {
+            json!({
+                "idl_file": u.idl_file,
+                "instruction": u.instruction,
+                "field": u.field,
+                "type": u.ty,
+                "reason": u.reason
+            })
+        })
+        .collect();
+
+    let warnings_json: Vec<Value> = warnings
+        .iter()
+        .map(|w| {
+            json!({
+                "code": w.code,
+                "message": w.message,
+                "program": w.program,
+                "location": w.location
+            })
+        })
+        .collect();
+
     let generated_json: Vec<Value> = generated
         .iter()
         .map(|c| {
+            let reason = skipped.get(&c.id);
             json!({
+                "skipped": reason.is_some(),
+                "skipped_reason": reason.map(|r| r.label()),
                 "id": c.id,
+                "display_name": c.display_name,
                 "idl_file": c.idl_file,
                 "program_id": c.program_id.to_string(),
                 "instruction": c.instruction.name,
                 "mutation": format!("{:?}", c.mutation),
-                "expectation": format!("{:?}", c.expectation)
+                "expectation": format!("{:?}", c.expectation),
+                "setup_chain": c.setup_chain,
+                "arg_values": arg_values_json(&integer_arg_values(c)),
+                "random_args": random_args_json(c)
             })
         })
         .collect();
@@ -73,28 +210,150 @@ pub fn write_report(
     let executed_json: Vec<Value> = executed
         .iter()
         .map(|c| {
+            // Only MustFailWith cases name the error they expect.
+            let expected_error = (c.expected_error_code.is_some()
+                || c.expected_error_contains.is_some())
+            .then(|| {
+                json!({
+                    "anchor_code": c.expected_error_code,
+                    "contains": c.expected_error_contains
+                })
+            });
+            let expected_log = c.expected_log.as_ref().map(|substring| {
+                json!({ "substring": substring, "matched": c.log_matched })
+            });
+            let expected_state = c.state_matched.map(|matched| {
+                json!({
+                    "matched": matched,
+                    "checks": c.state_checks.iter().map(|check| json!({
+                        "account": check.assertion.account,
+                        "offset": check.assertion.offset,
+                        "expected_hex": hex(&check.assertion.expected),
+                        "actual_hex": check.actual.as_deref().map(hex),
+                        "matched": check.matched()
+                    })).collect::<Vec<_>>()
+                })
+            });
             json!({
                 "id": c.id,
+                "display_name": c.display_name,
                 "idl_file": c.idl_file,
                 "instruction": c.instruction,
                 "mutation": c.mutation,
                 "expected_success": c.expected_success,
                 "actual_success": c.actual_success,
                 "passed": c.passed,
-                "error": c.error
+                "rule": c.rule,
+                "error": c.error,
+                "expected_error": expected_error,
+                "error_code": c.error_code,
+                "error_name": c.error_name,
+                "expected_log": expected_log,
+                "expected_state": expected_state,
+                "fabricated_accounts": c.fabricated_accounts,
+                "fabricated_fields": c.fabricated_fields.iter().map(|f| json!({
+                    "account": f.account,
+                    "field": f.field,
+                    "value": f.value
+                })).collect::<Vec<_>>(),
+                "foreign_pda": c.foreign_pda.as_ref().map(|f| json!({
+                    "account": f.account,
+                    "expected_pubkey": f.expected.to_string(),
+                    "substituted_pubkey": f.substituted.to_string(),
+                    "foreign_owner": f.owner.to_string()
+                })),
+                "non_canonical_bump": c.non_canonical_bump.as_ref().map(|b| json!({
+                    "account": b.account,
+                    "expected_pubkey": b.expected.to_string(),
+                    "canonical_bump": b.canonical_bump,
+                    "substituted_pubkey": b.substituted.to_string(),
+                    "bump": b.bump
+                })),
+                "wrong_authority": c.wrong_authority.as_ref().map(|w| json!({
+                    "account": w.account,
+                    "authority": w.authority.to_string(),
+                    "signer": w.signer.to_string()
+                })),
+                "arg_values": arg_values_json(&c.arg_values),
+                "base_case_id": c.base_case_id,
+                "same_as_base": c.same_as_base,
+                "reinitialization_allowed": c.reinitialization_allowed,
+                "unresolved_seeds": c.unresolved_seeds.len(),
+                "loaded_programs": c.loaded_programs,
+                "compute_units": c.compute_units,
+                "logs": c.logs,
+                "setup": c.setup.iter().map(|s| json!({
+                    "instruction": s.instruction,
+                    "success": s.success,
+                    "error": s.error
+                })).collect::<Vec<_>>(),
+                "timings_us": c.timings.as_ref().map(timings_json)
+            })
+        })
+        .collect();
+
+    let declared_errors = declared_error_coverage(programs, executed);
+    let declared_errors_json: Vec<Value> = declared_errors
+        .iter()
+        .map(|d| {
+            json!({
+                "idl_file": d.idl_file,
+                "code": d.code,
+                "name": d.name,
+                "msg": d.msg,
+                "observed": !d.cases.is_empty(),
+                "cases": d.cases
             })
         })
         .collect();
 
     // Summary is a compact roll-up for CI logs and quick inspection.
     let summary = json!({
+        // Set when `--program` narrowed the run, so a filtered report isn't read as a full one.
+        "program_filter": opts.program,
         "checks_failed": checks.iter().filter(|c| !c.ok).count(),
+        "warnings": warnings.len(),
+        "instructions_skipped": instructions_skipped,
         "generated_edge_cases": generated.len(),
         "executed_cases": executed.len(),
         "case_passed": executed.iter().filter(|c| c.passed).count(),
-        "case_failed": executed.iter().filter(|c| !c.passed).count()
+        "case_failed": executed.iter().filter(|c| !c.passed).count(),
+        "skipped_cases": skipped.len(),
+        "setup_failed_cases": setup_failed_cases(executed),
+        "compute_units": compute_unit_stats(executed).map(|s| json!({
+            "cases": s.cases,
+            "total": s.total,
+            "median": s.median,
+            "max": s.max
+        })),
+        "error_code_histogram": error_code_histogram(executed)
+            .into_iter()
+            .map(|(class, n)| (class, json!(n)))
+            .collect::<serde_json::Map<_, _>>(),
+        "declared_error_coverage": json!({
+            "declared": declared_errors.len(),
+            "observed": declared_errors.iter().filter(|d| !d.cases.is_empty()).count()
+        }),
+        "error_name_histogram": error_name_histogram(executed)
+            .into_iter()
+            .map(|(name, n)| (name, json!(n)))
+            .collect::<serde_json::Map<_, _>>()
     });
 
+    let sequence_json: Vec<Value> = sequence
+        .iter()
+        .map(|s| {
+            json!({
+                "idl_file": s.idl_file,
+                "instruction": s.instruction,
+                "success": s.success,
+                "error": s.error,
+                "compute_units": s.compute_units,
+                "logs": s.logs
+            })
+        })
+        .collect();
+
     // Optional smoke output is included if the local test was available.
     let smoke_json = match smoke {
         Some(s) => json!({
@@ -110,14 +369,795 @@ pub fn write_report(
         "tool": "anchor-suite",
         "step": 4,
         "summary": summary,
+        // Empty/null entries mean "no filter", so a report says which cases it covers.
+        "filters": {
+            "program": opts.program,
+            "instructions": opts.instructions,
+            "instruction_regex": opts.instruction_regex,
+            "mutations": opts.mutations
+        },
+        "fuzz_seed": fuzz_seed,
+        "artifacts": artifacts_json(project_root, opts),
+        "replay": replay_json(project_root, opts),
         "checks": checks_json,
+        "warnings": warnings_json,
         "optional_smoke": smoke_json,
+        "programs": programs_json,
+        "timings": harness_timings_json(executed),
+        "unsupported_types": unsupported_json,
+        "unresolved_seeds": unresolved_seeds_json(executed),
+        "declared_errors": declared_errors_json,
         "generated_cases": generated_json,
-        "executed_cases": executed_json
+        "executed_cases": executed_json,
+        "sequence": sequence_json
     });
 
-    fs::write(&report_path, serde_json::to_string_pretty(&report)?)
-        .with_context(|| format!("Failed to write {}", report_path.display()))?;
+    emit_report(project_root, opts.output.as_deref(), &report)
+}
+
+// Where the run read IDLs and program binaries, after applying `--idl-dir`, `--deploy-dir`
+// and `--idl`; `idl_files` is empty when the IDL directory was scanned, and `spec_file` is set
+// instead of both IDL entries for `--spec` runs.
+fn artifacts_json(project_root: &Path, opts: &TestOptions) -> Value {
+    let scans_idl_dir = opts.spec_file.is_none() && opts.idl_files.is_empty();
+    json!({
+        "idl_dir": scans_idl_dir.then(|| idl_dir(project_root, opts).display().to_string()),
+        "idl_files": opts.idl_files.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+        "deploy_dir": deploy_dir(project_root, opts).display().to_string(),
+        "spec_file": opts.spec_file.as_deref().map(|p| p.display().to_string())
+    })
+}
+
+// Inputs that shape case generation, so `replay` can rebuild any case of this report. Paths are
+// made absolute so replay works from another directory.
+fn replay_json(project_root: &Path, opts: &TestOptions) -> Value {
+    let absolute = |p: &Path| {
+        fs::canonicalize(p)
+            .unwrap_or_else(|_| p.to_path_buf())
+            .display()
+            .to_string()
+    };
+    json!({
+        "project_dir": absolute(project_root),
+        "spec_file": opts.spec_file.as_deref().map(absolute),
+        "rules_file": opts.rules_file.as_deref().map(absolute),
+        "fixtures": opts.fixtures.as_deref().map(absolute),
+        "assertions": opts.assertions.as_deref().map(absolute),
+        "idl_dir": opts.idl_dir.as_deref().map(absolute),
+        "deploy_dir": opts.deploy_dir.as_deref().map(absolute),
+        "idl_files": opts.idl_files.iter().map(|p| absolute(p)).collect::<Vec<_>>(),
+        "program_ids": opts.program_ids,
+        "so_map": opts.so_map,
+        "non_empty_strings": opts.non_empty_strings,
+        "fund_writable": opts.fund_writable,
+        "fabricated_balance": opts.fabricated_balance,
+        "duplicate_accounts_must_fail": opts.duplicate_accounts_must_fail,
+        "extra_accounts_must_fail": opts.extra_accounts_must_fail,
+        "expect_logs": opts.expect_logs,
+        "fuzz": opts.fuzz
+    })
+}
+
+// JUnit XML for CI: one suite for the checks, one per IDL file with a testcase per executed
+// case (failures carry the error string).
+pub fn write_junit_report(
+    path: &Path,
+    checks: &[CheckResult],
+    executed: &[ExecutedCase],
+) -> Result<()> {
+    create_parent_dir(path)?;
+    fs::write(path, junit_xml(checks, executed))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn junit_xml(checks: &[CheckResult], executed: &[ExecutedCase]) -> String {
+    let mut suites: Vec<(String, Vec<String>, usize)> = Vec::new();
+
+    let mut check_cases = Vec::new();
+    for c in checks {
+        let failure = (!c.ok).then(|| {
+            let body = c.hint.as_deref().unwrap_or_default();
+            (c.detail.as_str(), body)
+        });
+        check_cases.push(junit_testcase(c.name, "checks", failure));
+    }
+    suites.push((
+        "checks".to_string(),
+        check_cases,
+        checks.iter().filter(|c| !c.ok).count(),
+    ));
+
+    let mut by_file: BTreeMap<&str, Vec<&ExecutedCase>> = BTreeMap::new();
+    for c in executed {
+        by_file.entry(c.idl_file.as_str()).or_default().push(c);
+    }
+    for (file, cases) in by_file {
+        let testcases = cases
+            .iter()
+            .map(|c| {
+                let failure = (!c.passed).then(|| {
+                    let message = c
+                        .error
+                        .as_deref()
+                        .unwrap_or("case did not match expectation");
+                    (message, c.rule.as_str())
+                });
+                junit_testcase(&c.id, file, failure)
+            })
+            .collect();
+        let failures = cases.iter().filter(|c| !c.passed).count();
+        suites.push((file.to_string(), testcases, failures));
+    }
+
+    let tests: usize = suites.iter().map(|(_, cases, _)| cases.len()).sum();
+    let failures: usize = suites.iter().map(|(_, _, f)| f).sum();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"anchor-suite\" tests=\"{}\" failures=\"{}\">\n",
+        tests, failures
+    ));
+    for (name, cases, failures) in &suites {
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            xml_escape(name),
+            cases.len(),
+            failures
+        ));
+        for c in cases {
+            out.push_str(c);
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+// `failure` is (message attribute, element text).
+fn junit_testcase(name: &str, classname: &str, failure: Option<(&str, &str)>) -> String {
+    let open = format!(
+        "    <testcase name=\"{}\" classname=\"{}\"",
+        xml_escape(name),
+        xml_escape(classname)
+    );
+    match failure {
+        None => format!("{} />\n", open),
+        Some((message, text)) => format!(
+            "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+            open,
+            xml_escape(message),
+            xml_escape(text)
+        ),
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML 1.0 forbids most control characters even when escaped.
+            c if (c as u32) < 0x20 && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// `report.tap` next to report.json for TAP consumers (`--format tap`).
+pub fn write_tap_report(
+    project_root: &Path,
+    checks: &[CheckResult],
+    executed: &[ExecutedCase],
+) -> Result<PathBuf> {
+    let path = project_root
+        .join("target")
+        .join("anchor-suite")
+        .join("report.tap");
+    create_parent_dir(&path)?;
+    fs::write(&path, tap(checks, executed))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+// TAP 13: one test point per check, then one per case, with the plan at the end. Failures
+// carry a YAML diagnostic block; JSON strings are valid YAML scalars, so values are written
+// with serde_json to keep quotes and newlines intact.
+fn tap(checks: &[CheckResult], executed: &[ExecutedCase]) -> String {
+    let mut out = String::from("TAP version 13\n");
+    let mut n = 0;
+    let mut point = |ok: bool, name: &str, diagnostics: &[(&str, Value)]| {
+        n += 1;
+        let status = if ok { "ok" } else { "not ok" };
+        out.push_str(&format!("{} {} - {}\n", status, n, name.replace('#', "\\#")));
+        if !ok {
+            out.push_str("  ---\n");
+            for (key, value) in diagnostics {
+                out.push_str(&format!("  {}: {}\n", key, value));
+            }
+            out.push_str("  ...\n");
+        }
+    };
+    for c in checks {
+        point(
+            c.ok,
+            &format!("check {}", c.name),
+            &[("message", json!(c.detail)), ("hint", json!(c.hint))],
+        );
+    }
+    for c in executed {
+        let message = c.error.as_deref().unwrap_or("case did not match expectation");
+        point(
+            c.passed,
+            &c.id,
+            &[
+                ("message", json!(message)),
+                ("mutation", json!(c.mutation)),
+                ("rule", json!(c.rule)),
+            ],
+        );
+    }
+    out.push_str(&format!("1..{}\n", checks.len() + executed.len()));
+    out
+}
+
+// Error text kept per failing row in report.md.
+const MD_ERROR_CHARS: usize = 120;
+
+// Human-readable `report.md` next to report.json, meant for pasting into PR descriptions.
+pub fn write_markdown_report(
+    project_root: &Path,
+    checks: &[CheckResult],
+    executed: &[ExecutedCase],
+) -> Result<PathBuf> {
+    let path = project_root
+        .join("target")
+        .join("anchor-suite")
+        .join("report.md");
+    create_parent_dir(&path)?;
+    fs::write(&path, markdown(checks, executed))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn markdown(checks: &[CheckResult], executed: &[ExecutedCase]) -> String {
+    let passed = executed.iter().filter(|c| c.passed).count();
+    let failed_checks: Vec<&CheckResult> = checks.iter().filter(|c| !c.ok).collect();
+    let ok = failed_checks.is_empty() && passed == executed.len();
+
+    let mut out = String::from("# anchor-suite report\n\n");
+    out.push_str(&format!(
+        "**{}**: {} of {} cases passed, {} failed; {} checks failed\n\n",
+        if ok { "PASS" } else { "FAIL" },
+        passed,
+        executed.len(),
+        executed.len() - passed,
+        failed_checks.len()
+    ));
+
+    if !failed_checks.is_empty() {
+        out.push_str("## Failed checks\n\n");
+        for c in &failed_checks {
+            out.push_str(&format!("- `{}`: {}\n", c.name, md_cell(&c.detail)));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Cases\n\n");
+    out.push_str("| id | instruction | mutation | expected | actual | result | error |\n");
+    out.push_str("|---|---|---|---|---|---|---|\n");
+    for c in executed {
+        let expected = match c.expected_success {
+            Some(true) => "success",
+            Some(false) => "failure",
+            None => "any",
+        };
+        let actual = if c.actual_success {
+            "success"
+        } else {
+            "failure"
+        };
+        // Only failing rows carry the error; expected failures would just be noise.
+        let error = match (&c.error, c.passed) {
+            (Some(e), false) => md_cell(&truncate_chars(e, MD_ERROR_CHARS)),
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "| `{}` | {} | `{}` | {} | {} | {} | {} |\n",
+            c.display_name,
+            c.instruction,
+            c.mutation,
+            expected,
+            actual,
+            if c.passed { "pass" } else { "**FAIL**" },
+            error
+        ));
+    }
+    out
+}
+
+// Table-safe cell text: no pipes, line breaks or raw HTML tags.
+fn md_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+        .replace(['\n', '\r'], " ")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Log lines shown per failing case in the PR summary.
+const SUMMARY_LOG_LINES: usize = 20;
+
+// `--summary-markdown`: compact GitHub-flavored summary for a PR comment. Only failures are
+// listed; report.json stays the source of truth.
+pub fn write_summary_markdown(
+    path: &Path,
+    checks: &[CheckResult],
+    executed: &[ExecutedCase],
+) -> Result<()> {
+    create_parent_dir(path)?;
+    fs::write(path, summary_markdown(checks, executed))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn summary_markdown(checks: &[CheckResult], executed: &[ExecutedCase]) -> String {
+    let failures: Vec<&ExecutedCase> = executed.iter().filter(|c| !c.passed).collect();
+    let failed_checks: Vec<&CheckResult> = checks.iter().filter(|c| !c.ok).collect();
+    let ok = failures.is_empty() && failed_checks.is_empty();
+
+    let mut out = format!(
+        "## anchor-suite ![{}](https://img.shields.io/badge/anchor--suite-{}-{})\n\n",
+        if ok { "PASS" } else { "FAIL" },
+        if ok { "passing" } else { "failing" },
+        if ok { "brightgreen" } else { "red" }
+    );
+    out.push_str(&format!(
+        "{} passed, {} failed, {} checks failed\n\n",
+        executed.len() - failures.len(),
+        failures.len(),
+        failed_checks.len()
+    ));
+    for c in &failed_checks {
+        out.push_str(&format!("- check `{}`: {}\n", c.name, md_cell(&c.detail)));
+    }
+    if !failed_checks.is_empty() {
+        out.push('\n');
+    }
+    if failures.is_empty() {
+        return out;
+    }
+
+    out.push_str("| instruction | mutation | expected | actual | result |\n");
+    out.push_str("|---|---|---|---|---|\n");
+    for c in &failures {
+        let expected = match c.expected_success {
+            Some(true) => "success",
+            Some(false) => "failure",
+            None => "any",
+        };
+        let actual = match &c.error {
+            None => "success".to_string(),
+            Some(e) => format!("failure: {}", md_cell(&truncate_chars(e, MD_ERROR_CHARS))),
+        };
+        out.push_str(&format!(
+            "| {} | `{}` | {} | {} | **FAIL** |\n",
+            md_cell(&c.instruction),
+            c.mutation,
+            expected,
+            actual
+        ));
+    }
+
+    out.push_str("\n<details>\n<summary>Logs of failing cases</summary>\n\n");
+    for c in &failures {
+        out.push_str(&format!("**{}**\n\n", md_cell(&c.display_name)));
+        let mut lines: Vec<&str> = c
+            .logs
+            .iter()
+            .take(SUMMARY_LOG_LINES)
+            .map(String::as_str)
+            .collect();
+        if lines.is_empty() {
+            lines.push("(no logs: the transaction was not sent)");
+        }
+        let more = c.logs.len().saturating_sub(SUMMARY_LOG_LINES);
+        let more_line = format!("... {} more log lines", more);
+        if more > 0 {
+            lines.push(&more_line);
+        }
+        // A fence longer than any backtick run in the logs can't be closed early.
+        let fence = "`".repeat(longest_backtick_run(&lines).max(2) + 1);
+        out.push_str(&format!("{}text\n{}\n{}\n\n", fence, lines.join("\n"), fence));
+    }
+    out.push_str("</details>\n");
+    out
+}
+
+fn longest_backtick_run(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .flat_map(|l| l.split(|ch| ch != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => format!("{}...", &s[..i]),
+        None => s.to_string(),
+    }
+}
+
+// Compute units over every case whose transaction was sent.
+pub struct ComputeUnitStats {
+    pub cases: usize,
+    pub total: u64,
+    pub median: u64,
+    pub max: u64,
+}
+
+pub fn compute_unit_stats(executed: &[ExecutedCase]) -> Option<ComputeUnitStats> {
+    let mut units: Vec<u64> = executed.iter().filter_map(|c| c.compute_units).collect();
+    if units.is_empty() {
+        return None;
+    }
+    units.sort_unstable();
+    Some(ComputeUnitStats {
+        cases: units.len(),
+        total: units.iter().sum(),
+        median: units[units.len() / 2],
+        max: units[units.len() - 1],
+    })
+}
+
+// Base cases whose compute units exceed their `--max-cu` ceiling, with the ceiling they broke.
+pub fn compute_budget_overruns<'a>(
+    executed: &'a [ExecutedCase],
+    budget: &ComputeBudget,
+) -> Vec<(&'a ExecutedCase, u64)> {
+    executed
+        .iter()
+        .filter(|c| c.mutation == "none")
+        .filter_map(|c| {
+            let limit = budget.limit(&c.instruction)?;
+            (c.compute_units? > limit).then_some((c, limit))
+        })
+        .collect()
+}
+
+// Cases where at least one setup-chain instruction failed, so the case ran on missing state.
+pub fn setup_failed_cases(executed: &[ExecutedCase]) -> usize {
+    executed
+        .iter()
+        .filter(|c| c.setup.iter().any(|s| !s.success))
+        .count()
+}
+
+// PDAs that couldn't be derived, deduplicated per instruction account and seed.
+fn unresolved_seeds_json(executed: &[ExecutedCase]) -> Vec<Value> {
+    let mut grouped: BTreeMap<(&str, &str, &str, &str), (&str, usize)> = BTreeMap::new();
+    for c in executed {
+        for u in &c.unresolved_seeds {
+            let key = (
+                c.idl_file.as_str(),
+                c.instruction.as_str(),
+                u.account.as_str(),
+                u.seed.as_str(),
+            );
+            grouped.entry(key).or_insert((u.reason.as_str(), 0)).1 += 1;
+        }
+    }
+    grouped
+        .into_iter()
+        .map(
+            |((idl_file, instruction, account, seed), (reason, cases))| {
+                json!({
+                    "idl_file": idl_file,
+                    "instruction": instruction,
+                    "account": account,
+                    "seed": seed,
+                    "reason": reason,
+                    "cases": cases
+                })
+            },
+        )
+        .collect()
+}
+
+// Failing cases bucketed by error class, most frequent first (ties by name).
+pub fn error_code_histogram(executed: &[ExecutedCase]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for e in executed.iter().filter_map(|c| c.error.as_deref()) {
+        *counts.entry(error_class(e)).or_default() += 1;
+    }
+    let mut out: Vec<(String, usize)> = counts.into_iter().collect();
+    out.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    out
+}
+
+// One error from a program's IDL `errors` section and the cases whose failure carried its code.
+pub struct DeclaredError<'a> {
+    pub idl_file: &'a str,
+    pub code: u32,
+    pub name: &'a str,
+    pub msg: Option<&'a str>,
+    pub cases: Vec<&'a str>,
+}
+
+// Which declared errors any executed case actually triggered.
+pub fn declared_error_coverage<'a>(
+    programs: &'a [ProgramSpec],
+    executed: &'a [ExecutedCase],
+) -> Vec<DeclaredError<'a>> {
+    programs
+        .iter()
+        .flat_map(|p| {
+            p.errors.iter().map(move |e| DeclaredError {
+                idl_file: &p.idl_file,
+                code: e.code,
+                name: &e.name,
+                msg: e.msg.as_deref(),
+                cases: executed
+                    .iter()
+                    .filter(|c| c.idl_file == p.idl_file && c.error_code == Some(e.code))
+                    .map(|c| c.id.as_str())
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+// Failing cases grouped by decoded error name, falling back to the error class when the code
+// has no known name (or there is no code at all).
+pub fn error_name_histogram(executed: &[ExecutedCase]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for c in executed {
+        let Some(error) = c.error.as_deref() else {
+            continue;
+        };
+        let name = c.error_name.clone().unwrap_or_else(|| error_class(error));
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut out: Vec<(String, usize)> = counts.into_iter().collect();
+    out.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    out
+}
+
+// Sum per-case phase timings; null when the run wasn't started with --debug-harness.
+pub fn total_harness_timings(executed: &[ExecutedCase]) -> Option<(usize, HarnessTimings)> {
+    let mut cases = 0;
+    let mut total = HarnessTimings::default();
+    for t in executed.iter().filter_map(|c| c.timings.as_ref()) {
+        cases += 1;
+        total.add_program += t.add_program;
+        total.airdrop += t.airdrop;
+        total.build_accounts += t.build_accounts;
+        total.send_ix += t.send_ix;
+    }
+    (cases > 0).then_some((cases, total))
+}
+
+fn harness_timings_json(executed: &[ExecutedCase]) -> Value {
+    match total_harness_timings(executed) {
+        Some((cases, total)) => json!({
+            "timed_cases": cases,
+            "total_us": timings_json(&total),
+            "mean_us": {
+                "add_program": total.add_program.as_micros() / cases as u128,
+                "airdrop": total.airdrop.as_micros() / cases as u128,
+                "build_accounts": total.build_accounts.as_micros() / cases as u128,
+                "send_ix": total.send_ix.as_micros() / cases as u128
+            }
+        }),
+        None => json!(null),
+    }
+}
+
+fn timings_json(t: &HarnessTimings) -> Value {
+    json!({
+        "add_program": t.add_program.as_micros(),
+        "airdrop": t.airdrop.as_micros(),
+        "build_accounts": t.build_accounts.as_micros(),
+        "send_ix": t.send_ix.as_micros()
+    })
+}
+
+// `[{name, value}]` in arg order; values are decimal strings so u128/i128 survive JSON.
+fn arg_values_json(values: &[(String, String)]) -> Value {
+    values
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+// Seed and hex-encoded Borsh bytes of a `--fuzz` case, enough to reproduce it; null otherwise.
+fn random_args_json(case: &EdgeCase) -> Value {
+    let Mutation::RandomArgs { seed, args } = &case.mutation else {
+        return Value::Null;
+    };
+    json!({
+        "seed": seed,
+        "args": case
+            .instruction
+            .args
+            .iter()
+            .zip(args)
+            .map(|(a, bytes)| json!({ "name": a.name, "hex": hex(bytes) }))
+            .collect::<Vec<_>>()
+    })
+}
+
+// Test fixture for the report writers: a passing or failing `any` case of `deposit`; tests
+// override the fields they care about with struct-update syntax.
+#[cfg(test)]
+pub(crate) fn executed(id: &str, passed: bool, error: Option<&str>) -> ExecutedCase {
+    ExecutedCase {
+        id: id.to_string(),
+        display_name: id.to_string(),
+        idl_file: "vault.json".to_string(),
+        instruction: "deposit".to_string(),
+        mutation: "none".to_string(),
+        expected_success: None,
+        actual_success: passed,
+        passed,
+        rule: "builtin:any".to_string(),
+        error: error.map(str::to_string),
+        timings: None,
+        base_case_id: None,
+        same_as_base: None,
+        reinitialization_allowed: None,
+        unresolved_seeds: Vec::new(),
+        loaded_programs: Vec::new(),
+        setup: Vec::new(),
+        logs: Vec::new(),
+        compute_units: None,
+        expected_error_code: None,
+        expected_error_contains: None,
+        error_code: None,
+        error_name: None,
+        expected_log: None,
+        log_matched: None,
+        state_checks: Vec::new(),
+        state_matched: None,
+        fabricated_accounts: Vec::new(),
+        fabricated_fields: Vec::new(),
+        foreign_pda: None,
+        non_canonical_bump: None,
+        wrong_authority: None,
+        arg_values: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_budget_checks_base_cases_against_the_most_specific_limit() {
+        let case = |id: &str, instruction: &str, mutation: &str, units: u64| ExecutedCase {
+            instruction: instruction.to_string(),
+            mutation: mutation.to_string(),
+            compute_units: Some(units),
+            ..executed(id, true, None)
+        };
+        let cases = vec![
+            case("deposit_base", "deposit", "none", 6_000),
+            case("deposit_wrong_pda", "deposit", "wrong_pda", 90_000),
+            case("withdraw_base", "withdraw", "none", 6_000),
+            case("close_base", "close", "none", 4_000),
+        ];
+        let budget = ComputeBudget {
+            default: Some(5_000),
+            per_instruction: vec![("withdraw".to_string(), 8_000)],
+        };
+
+        let over = compute_budget_overruns(&cases, &budget);
+        assert_eq!(over.len(), 1);
+        assert_eq!((over[0].0.id.as_str(), over[0].1), ("deposit_base", 5_000));
+        assert!(compute_budget_overruns(&cases, &ComputeBudget::default()).is_empty());
+    }
+
+    #[test]
+    fn markdown_rows_show_truncated_errors_only_for_failures() {
+        let long = format!("a|b\n{}", "x".repeat(200));
+        let cases = vec![
+            executed("vault.json_deposit_base", true, None),
+            executed("vault.json_deposit_wrong_pda_vault", false, Some(&long)),
+        ];
+        let md = markdown(&[], &cases);
+
+        assert!(md.contains("**FAIL**: 1 of 2 cases passed, 1 failed; 0 checks failed"));
+        assert!(md.contains(
+            "| `vault.json_deposit_base` | deposit | `none` | any | success | pass |  |"
+        ));
+        let row = md.lines().find(|l| l.contains("wrong_pda")).unwrap();
+        assert!(row.contains("a\\|b xxx"));
+        assert!(row.ends_with("x... |"));
+    }
+
+    #[test]
+    fn tap_lists_checks_then_cases_with_yaml_diagnostics_for_failures() {
+        let checks = vec![CheckResult::pass("idl_files_exist", "1 file".to_string())];
+        let cases = vec![
+            executed("vault.json_deposit_base", true, None),
+            executed(
+                "vault.json_deposit_wrong_pda_vault",
+                false,
+                Some("Custom(2006)\n\"seeds\""),
+            ),
+        ];
+        let tap = tap(&checks, &cases);
+
+        let expected = [
+            "TAP version 13",
+            "ok 1 - check idl_files_exist",
+            "ok 2 - vault.json_deposit_base",
+            "not ok 3 - vault.json_deposit_wrong_pda_vault",
+            "  ---",
+            r#"  message: "Custom(2006)\n\"seeds\"""#,
+            r#"  mutation: "none""#,
+            r#"  rule: "builtin:any""#,
+            "  ...",
+            "1..3",
+        ];
+        assert_eq!(tap.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn pr_summary_lists_only_failures_with_escaped_errors_and_capped_logs() {
+        let mut failing = executed(
+            "vault.json_deposit_wrong_pda_vault",
+            false,
+            Some("Error | <Custom(2006)>\nsecond line"),
+        );
+        failing.expected_success = Some(false);
+        failing.logs = (0..25).map(|i| format!("Program log: ``` line {}", i)).collect();
+        let cases = vec![executed("vault.json_deposit_base", true, None), failing];
+        let md = summary_markdown(&[], &cases);
+
+        assert!(md.contains("badge/anchor--suite-failing-red"));
+        assert!(md.contains("1 passed, 1 failed, 0 checks failed"));
+        assert_eq!(md.matches("| deposit |").count(), 1);
+        assert!(md.contains(
+            "| deposit | `none` | failure | failure: Error \\| &lt;Custom(2006)&gt; second line | **FAIL** |"
+        ));
+        assert!(md.contains("Program log: ``` line 19\n... 5 more log lines\n````\n"));
+        assert!(!md.contains("line 20"));
+
+        let clean = summary_markdown(&[], &cases[..1]);
+        assert!(clean.contains("badge/anchor--suite-passing-brightgreen"));
+        assert!(!clean.contains("<details>"));
+    }
+
+    #[test]
+    fn junit_maps_checks_and_cases_to_escaped_testcases() {
+        let checks = vec![
+            CheckResult::pass("idl_dir_exists", "target/idl".to_string()),
+            CheckResult::fail(
+                "deploy_dir_exists",
+                "missing".to_string(),
+                "Run <build>".to_string(),
+            ),
+        ];
+        let cases = vec![
+            executed("vault.json_deposit_base", true, None),
+            executed(
+                "vault.json_deposit_wrong_pda_vault",
+                false,
+                Some("err: \"Custom(6000)\""),
+            ),
+        ];
+        let xml = junit_xml(&checks, &cases);
 
-    Ok(report_path)
+        assert!(xml.contains("<testsuites name=\"anchor-suite\" tests=\"4\" failures=\"2\">"));
+        assert!(xml.contains("<testsuite name=\"checks\" tests=\"2\" failures=\"1\">"));
+        assert!(xml.contains("<failure message=\"missing\">Run &lt;build&gt;</failure>"));
+        assert!(
+            xml.contains("<testcase name=\"vault.json_deposit_base\" classname=\"vault.json\" />")
+        );
+        assert!(xml.contains("message=\"err: &quot;Custom(6000)&quot;\""));
+    }
 }
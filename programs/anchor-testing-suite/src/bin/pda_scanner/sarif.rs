@@ -0,0 +1,220 @@
+use crate::report::create_parent_dir;
+use crate::types::ExecutedCase;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// A finding kind: the mutation kinds that reveal it, its SARIF rule id, level and description.
+struct Rule {
+    mutations: &'static [&'static str],
+    id: &'static str,
+    level: &'static str,
+    description: &'static str,
+}
+
+// Mutations the program should have rejected, grouped by the check that was missing. Anything
+// not listed falls back to `unexpected-success`, the last rule.
+const RULES: &[Rule] = &[
+    Rule {
+        mutations: &["missing_signer"],
+        id: "missing-signer-check",
+        level: "error",
+        description: "An account that must sign was accepted without a signature",
+    },
+    Rule {
+        mutations: &["wrong_signer", "wrong_authority"],
+        id: "missing-authority-check",
+        level: "error",
+        description: "A signer other than the account's authority was accepted",
+    },
+    Rule {
+        mutations: &["wrong_pda", "corrupt_seed"],
+        id: "missing-seeds-check",
+        level: "error",
+        description: "An address not derived from the PDA's seeds was accepted",
+    },
+    Rule {
+        mutations: &["non_canonical_bump"],
+        id: "non-canonical-bump",
+        level: "error",
+        description: "A PDA derived with a bump other than the canonical one was accepted",
+    },
+    Rule {
+        mutations: &["foreign_pda"],
+        id: "account-confusion",
+        level: "error",
+        description: "Another user's PDA was accepted in place of the signer's own",
+    },
+    Rule {
+        mutations: &["wrong_owner"],
+        id: "missing-owner-check",
+        level: "error",
+        description: "An account owned by the wrong program was accepted",
+    },
+    Rule {
+        mutations: &["double_init"],
+        id: "reinitialization",
+        level: "error",
+        description: "An initializer accepted an account that was already initialized",
+    },
+    Rule {
+        mutations: &["uninitialized"],
+        id: "missing-initialization-check",
+        level: "warning",
+        description: "An uninitialized account was accepted",
+    },
+    Rule {
+        mutations: &["duplicate_account"],
+        id: "duplicate-mutable-accounts",
+        level: "warning",
+        description: "The same account was accepted in two account slots",
+    },
+    Rule {
+        mutations: &["not_writable"],
+        id: "missing-writable-check",
+        level: "warning",
+        description: "An account declared writable was accepted as readonly",
+    },
+    Rule {
+        mutations: &["huge_amount"],
+        id: "missing-balance-check",
+        level: "warning",
+        description: "An amount larger than the available balance was accepted",
+    },
+    Rule {
+        mutations: &["truncate_data", "corrupt_discriminator", "empty_data"],
+        id: "malformed-instruction-data",
+        level: "warning",
+        description: "Malformed instruction data was accepted",
+    },
+    Rule {
+        mutations: &[],
+        id: "unexpected-success",
+        level: "warning",
+        description: "An instruction the suite expected to fail succeeded",
+    },
+];
+
+// `report.sarif` next to report.json for security scanners (`--format sarif`).
+pub fn write_sarif_report(project_root: &Path, executed: &[ExecutedCase]) -> Result<PathBuf> {
+    let path = project_root
+        .join("target")
+        .join("anchor-suite")
+        .join("report.sarif");
+    let doc = serde_json::to_string_pretty(&sarif(executed))?;
+    create_parent_dir(&path)?;
+    fs::write(&path, doc).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+// SARIF 2.1.0 with one result per missing guard: a case that had to fail, didn't, and so
+// failed its verdict. Other failures (a rejection with the wrong error, a setup problem) are
+// not findings about the program and stay in report.json only.
+fn sarif(executed: &[ExecutedCase]) -> Value {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|r| {
+            json!({
+                "id": r.id,
+                "shortDescription": { "text": r.description },
+                "defaultConfiguration": { "level": r.level }
+            })
+        })
+        .collect();
+    let results: Vec<Value> = executed
+        .iter()
+        .filter(|c| !c.passed && c.expected_success == Some(false) && c.actual_success)
+        .map(|c| {
+            let index = rule_index(&c.mutation);
+            let rule = &RULES[index];
+            json!({
+                "ruleId": rule.id,
+                "ruleIndex": index,
+                "level": rule.level,
+                "message": {
+                    "text": format!(
+                        "`{}` succeeded under `{}`, which must fail: {}",
+                        c.instruction, c.mutation, rule.description
+                    )
+                },
+                "locations": [{
+                    "physicalLocation": { "artifactLocation": { "uri": c.idl_file } },
+                    "logicalLocations": [{
+                        "name": c.instruction,
+                        "fullyQualifiedName": format!("{}/{}", c.idl_file, c.instruction),
+                        "kind": "function"
+                    }]
+                }],
+                "partialFingerprints": { "caseId": c.id },
+                "properties": { "caseId": c.id, "mutation": c.mutation, "rule": c.rule }
+            })
+        })
+        .collect();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "pda-scanner",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules
+                }
+            },
+            "results": results
+        }]
+    })
+}
+
+// Index into RULES for a mutation label (`missing_signer:user` -> missing-signer-check).
+fn rule_index(mutation: &str) -> usize {
+    let kind = mutation.split_once(':').map_or(mutation, |(kind, _)| kind);
+    RULES
+        .iter()
+        .position(|r| r.mutations.contains(&kind))
+        .unwrap_or(RULES.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::executed;
+
+    #[test]
+    fn only_must_fail_cases_that_succeeded_become_results() {
+        let case = |id: &str, mutation: &str, actual_success: bool, passed: bool| ExecutedCase {
+            instruction: "withdraw".to_string(),
+            mutation: mutation.to_string(),
+            expected_success: Some(mutation == "none"),
+            actual_success,
+            rule: "builtin:must_fail".to_string(),
+            ..executed(id, passed, None)
+        };
+        let executed = [
+            case("base", "none", false, false),
+            case("signer", "missing_signer:user", true, false),
+            case("seeds", "wrong_pda:vault", false, false),
+            case("odd", "option_some", true, false),
+            case("held", "wrong_owner:vault", false, true),
+        ];
+        let doc = sarif(&executed);
+
+        assert_eq!(doc["version"], "2.1.0");
+        let results = doc["runs"][0]["results"].as_array().unwrap();
+        let ids: Vec<_> = results.iter().map(|r| r["ruleId"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["missing-signer-check", "unexpected-success"]);
+
+        let signer = &results[0];
+        assert_eq!(signer["level"], "error");
+        let index = signer["ruleIndex"].as_u64().unwrap() as usize;
+        let rule = &doc["runs"][0]["tool"]["driver"]["rules"][index];
+        assert_eq!(rule["id"], "missing-signer-check");
+        let location = &signer["locations"][0];
+        assert_eq!(location["physicalLocation"]["artifactLocation"]["uri"], "vault.json");
+        assert_eq!(location["logicalLocations"][0]["fullyQualifiedName"], "vault.json/withdraw");
+        assert_eq!(signer["properties"]["caseId"], "signer");
+    }
+}
@@ -1,80 +1,318 @@
+use crate::cases::{find_pda, known_account_address};
+use crate::exit::Failure;
+use crate::idl_format::normalize;
+use crate::specs::{list_idl_files, program_matches};
 use anyhow::{bail, Context, Result};
-use serde_json::Value;
-use std::collections::BTreeSet;
+use serde_json::{json, Value};
+use solana_address::Address;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // Lightweight PDA summary used for display output.
 #[derive(Debug)]
 struct PdaInfo {
     program_id: String,
+    program_name: String,
     account_name: String,
+    // Instructions that declare this PDA, in IDL order.
+    instructions: Vec<String>,
     seeds: Value,
+    // Set when every seed is known statically (const or a fixed-address account).
+    derived_address: Option<String>,
+    bump: Option<u8>,
 }
 
-// Scan IDL files and print PDA accounts derived from instruction metadata.
-pub fn scan_pdas(project_dir: &str) -> Result<()> {
-    // Anchor generates IDL JSON at `target/idl`.
-    let idl_dir = Path::new(project_dir).join("target").join("idl");
-    if !idl_dir.exists() {
-        bail!("No IDL directory found at {}. Run `anchor build` first.", idl_dir.display());
-    }
-
-    // Deduplicate by `program_id:account_name`.
-    let mut pdas = Vec::new();
-    let mut seen = BTreeSet::new();
-
-    for entry in fs::read_dir(&idl_dir)? {
-        let path = entry?.path();
-        // Only process IDL JSON files.
-        if path.extension().and_then(|s| s.to_str()) != Some("json") {
-            continue;
+// Scan IDL files and print PDA accounts derived from instruction metadata, as text or JSON.
+// `--idl` files replace the directory scan; `--idl-dir` replaces `target/idl`; `--program`
+// keeps only the IDL whose name, file stem or address matches.
+pub fn scan_pdas(
+    project_dir: &str,
+    idl_dir: Option<&Path>,
+    idl_files: &[PathBuf],
+    program: Option<&str>,
+    json_out: bool,
+    output: Option<&Path>,
+) -> Result<()> {
+    let paths = if idl_files.is_empty() {
+        // Anchor generates IDL JSON at `target/idl`.
+        let idl_dir = idl_dir.map_or_else(
+            || Path::new(project_dir).join("target").join("idl"),
+            Path::to_path_buf,
+        );
+        if !idl_dir.exists() {
+            bail!(Failure::Preflight(format!(
+                "No IDL directory found at {}. Run `anchor build` first or pass --idl-dir.",
+                idl_dir.display()
+            )));
         }
+        // Only IDL JSON files, sorted so output order is stable.
+        list_idl_files(&idl_dir)?
+    } else {
+        idl_files.to_vec()
+    };
 
+    let mut pdas = Vec::new();
+    let mut available = Vec::new();
+    for path in &paths {
         // Parse IDL.
-        let idl_content = fs::read_to_string(&path)
+        let idl_content = fs::read_to_string(path)
             .with_context(|| format!("Failed reading {}", path.display()))?;
-        let idl: Value = serde_json::from_str(&idl_content)
+        let raw: Value = serde_json::from_str(&idl_content)
             .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+        // Legacy IDLs are normalized so seeds always come out as modern const/account/arg.
+        let (_, _, idl) = normalize(&raw);
+        if let Some(filter) = program {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let address = idl["address"]
+                .as_str()
+                .or_else(|| idl["metadata"]["address"].as_str())
+                .unwrap_or("");
+            let name = idl["metadata"]["name"].as_str().unwrap_or("");
+            if !program_matches(filter, name, stem, address) {
+                available.push(format!("{} ({})", stem, address));
+                continue;
+            }
+        }
+        pdas.extend(collect_pdas(&idl));
+    }
+    if let Some(filter) = program {
+        if available.len() == paths.len() {
+            bail!(
+                "--program `{}` matches no IDL. Available programs: {}",
+                filter,
+                available.join(", ")
+            );
+        }
+    }
 
-        // Program id is stored either at root or metadata in some IDLs.
-        let program_id = idl["address"]
-            .as_str()
-            .or_else(|| idl["metadata"]["address"].as_str())
-            .unwrap_or("<unknown_program>");
-
-        // PDA metadata is stored per-instruction account entry.
-        if let Some(instructions) = idl["instructions"].as_array() {
-            for instruction in instructions {
-                if let Some(accounts) = instruction["accounts"].as_array() {
-                    for account in accounts {
-                        if let Some(seeds) = account["pda"]["seeds"].as_array() {
-                            // Collect PDA definition from seeds.
-                            let account_name = account["name"].as_str().unwrap_or("<unknown_account>");
-                            let key = format!("{}:{}", program_id, account_name);
-                            if seen.insert(key) {
-                                pdas.push(PdaInfo {
-                                    program_id: program_id.to_string(),
-                                    account_name: account_name.to_string(),
-                                    seeds: Value::Array(seeds.clone()),
-                                });
-                            }
-                        }
-                    }
-                }
+    if json_out || output.is_some() {
+        let doc = serde_json::to_string_pretty(&scan_json(&pdas))?;
+        match output {
+            Some(path) => {
+                fs::write(path, doc)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                println!("Wrote {} PDAs to {}", pdas.len(), path.display());
             }
+            None => println!("{}", doc),
         }
+        return Ok(());
     }
 
     // Print results.
     println!("Found {} PDAs:", pdas.len());
     println!("{:-^60}", " PDAs ");
     for pda in pdas {
+        let address = match (&pda.derived_address, pda.bump) {
+            (Some(addr), Some(bump)) => format!("{} (bump {})", addr, bump),
+            _ => "<dynamic>".to_string(),
+        };
         println!(
-            "Program: {} | Account: {} | Seeds: {:?}",
-            pda.program_id, pda.account_name, pda.seeds
+            "Program: {} | Account: {} | Address: {} | Seeds: {:?}",
+            pda.program_id, pda.account_name, address, pda.seeds
         );
     }
 
     Ok(())
 }
+
+// PDA accounts of one (normalized) IDL, deduplicated by account name.
+fn collect_pdas(idl: &Value) -> Vec<PdaInfo> {
+    // Program id is stored either at root or metadata in some IDLs.
+    let program_id = idl["address"]
+        .as_str()
+        .or_else(|| idl["metadata"]["address"].as_str())
+        .unwrap_or("<unknown_program>");
+    let program_name = idl["metadata"]["name"].as_str().unwrap_or("<unknown_program>");
+    let program_address = program_id.parse::<Address>().ok();
+
+    // PDA metadata is stored per-instruction account entry.
+    let mut by_account: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut pdas: Vec<PdaInfo> = Vec::new();
+    for instruction in idl["instructions"].as_array().into_iter().flatten() {
+        let ix_name = instruction["name"].as_str().unwrap_or("<unknown_instruction>");
+        for account in instruction["accounts"].as_array().into_iter().flatten() {
+            let Some(seeds) = account["pda"]["seeds"].as_array() else {
+                continue;
+            };
+            let account_name = account["name"].as_str().unwrap_or("<unknown_account>");
+            match by_account.get(account_name) {
+                Some(&i) => pdas[i].instructions.push(ix_name.to_string()),
+                None => {
+                    let derived = program_address.as_ref().and_then(|pid| {
+                        let seeds = static_seeds(seeds, instruction)?;
+                        Some(find_pda(&seeds, pid))
+                    });
+                    by_account.insert(account_name, pdas.len());
+                    pdas.push(PdaInfo {
+                        program_id: program_id.to_string(),
+                        program_name: program_name.to_string(),
+                        account_name: account_name.to_string(),
+                        instructions: vec![ix_name.to_string()],
+                        seeds: Value::Array(seeds.clone()),
+                        derived_address: derived.map(|(addr, _)| addr.to_string()),
+                        bump: derived.map(|(_, bump)| bump),
+                    });
+                }
+            }
+        }
+    }
+    pdas
+}
+
+// Seed bytes when none depend on runtime input: consts, and account seeds naming an account
+// with a fixed IDL `address` or a well-known program/sysvar. Arg seeds are always dynamic.
+fn static_seeds(seeds: &[Value], instruction: &Value) -> Option<Vec<Vec<u8>>> {
+    seeds
+        .iter()
+        .map(|seed| match seed["kind"].as_str()? {
+            "const" => seed["value"]
+                .as_array()?
+                .iter()
+                .map(|b| b.as_u64().and_then(|n| u8::try_from(n).ok()))
+                .collect(),
+            "account" => {
+                let path = seed["path"].as_str()?;
+                let fixed = instruction["accounts"]
+                    .as_array()?
+                    .iter()
+                    .find(|a| a["name"].as_str() == Some(path))
+                    .and_then(|a| a["address"].as_str()?.parse::<Address>().ok());
+                fixed
+                    .or_else(|| known_account_address(path))
+                    .map(|a| a.to_bytes().to_vec())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Stable `scan --json` document; field names are part of the CLI contract.
+fn scan_json(pdas: &[PdaInfo]) -> Value {
+    let entries: Vec<Value> = pdas
+        .iter()
+        .map(|p| {
+            json!({
+                "program_id": p.program_id,
+                "program_name": p.program_name,
+                "account": p.account_name,
+                "instructions": p.instructions,
+                "derived_address": p.derived_address,
+                "bump": p.bump,
+                "seeds": p
+                    .seeds
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(seed_json)
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json!({
+        "tool": "anchor-suite",
+        "command": "scan",
+        "pda_count": entries.len(),
+        "pdas": entries,
+    })
+}
+
+// Const seeds keep their bytes and add a UTF-8 rendering when printable; account/arg seeds
+// keep their path.
+fn seed_json(seed: &Value) -> Value {
+    match seed["kind"].as_str() {
+        Some("const") => {
+            let bytes: Vec<u8> = seed["value"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|b| b.as_u64().map(|n| n as u8))
+                .collect();
+            let utf8 = std::str::from_utf8(&bytes)
+                .ok()
+                .filter(|s| !s.is_empty() && !s.chars().any(char::is_control));
+            json!({ "kind": "const", "bytes": bytes, "utf8": utf8 })
+        }
+        Some(kind @ ("account" | "arg")) => json!({ "kind": kind, "path": seed["path"] }),
+        _ => json!({ "kind": "unknown", "raw": seed }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_json_schema_is_stable() {
+        let ix = |name: &str| {
+            json!({
+                "name": name,
+                "accounts": [{ "name": "vault", "pda": { "seeds": [
+                    { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                    { "kind": "const", "value": [255] },
+                    { "kind": "account", "path": "user" }
+                ]}}]
+            })
+        };
+        let idl = json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "metadata": { "name": "test_vault", "spec": "0.1.0" },
+            "instructions": [ix("initialize_vault"), ix("deposit")]
+        });
+
+        let doc = scan_json(&collect_pdas(&idl));
+        assert_eq!(
+            doc,
+            json!({
+                "tool": "anchor-suite",
+                "command": "scan",
+                "pda_count": 1,
+                "pdas": [{
+                    "program_id": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+                    "program_name": "test_vault",
+                    "account": "vault",
+                    "instructions": ["initialize_vault", "deposit"],
+                    "derived_address": null,
+                    "bump": null,
+                    "seeds": [
+                        { "kind": "const", "bytes": [118, 97, 117, 108, 116], "utf8": "vault" },
+                        { "kind": "const", "bytes": [255], "utf8": null },
+                        { "kind": "account", "path": "user" }
+                    ]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn static_seeds_derive_the_canonical_pda() {
+        let idl = json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "metadata": { "name": "test_vault" },
+            "instructions": [{
+                "name": "init_config",
+                "accounts": [
+                    { "name": "config", "pda": { "seeds": [
+                        { "kind": "const", "value": [99, 102, 103] },
+                        { "kind": "account", "path": "system_program" }
+                    ]}},
+                    { "name": "system_program" }
+                ]
+            }]
+        });
+
+        let pdas = collect_pdas(&idl);
+        let program_id = anchor_lang::prelude::Pubkey::new_from_array(
+            "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS"
+                .parse::<Address>()
+                .unwrap()
+                .to_bytes(),
+        );
+        let (expected, bump) = anchor_lang::prelude::Pubkey::find_program_address(
+            &[b"cfg", &[0u8; 32]],
+            &program_id,
+        );
+        assert_eq!(pdas[0].derived_address, Some(expected.to_string()));
+        assert_eq!(pdas[0].bump, Some(bump));
+    }
+}
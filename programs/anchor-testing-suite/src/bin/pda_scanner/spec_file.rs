@@ -0,0 +1,290 @@
+use crate::idl_format::{account_discriminator, instruction_discriminator};
+use crate::specs::{list_deploy_sos, program_from_idl, resolve_so_file};
+use crate::types::{IdlFormat, ProgramIdSource, ProgramSpec};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use solana_address::Address;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// Version string reported for programs loaded from a spec file.
+const SPEC_VERSION: &str = "spec-1";
+
+// Load a hand-written spec file into the same ProgramSpec an IDL would produce.
+pub fn load_spec_file(path: &Path, deploy_dir: &Path) -> Result<ProgramSpec> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed reading {}", path.display()))?;
+    let spec: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    let idl =
+        spec_to_idl(&spec).with_context(|| format!("Invalid spec file {}", path.display()))?;
+
+    // Both were validated by spec_to_idl.
+    let name = idl["metadata"]["name"].as_str().unwrap_or_default();
+    let program_id: Address = idl["address"].as_str().unwrap_or_default().parse()?;
+
+    // `program_so` is relative to the spec file; otherwise match by name in the deploy dir.
+    let deploy_so = match spec["program_so"].as_str() {
+        Some(rel) => {
+            let so = path.parent().unwrap_or(Path::new(".")).join(rel);
+            if !so.exists() {
+                bail!("program_so {} does not exist", so.display());
+            }
+            so
+        }
+        None => {
+            let sos = list_deploy_sos(deploy_dir)?;
+            resolve_so_file(deploy_dir, &sos, name, name, &program_id)?
+        }
+    };
+
+    let file = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("<spec>")
+        .to_string();
+    let mut program = program_from_idl(
+        &idl,
+        file,
+        IdlFormat::Spec,
+        SPEC_VERSION.to_string(),
+        program_id,
+        deploy_so,
+    );
+    program.program_id_source = ProgramIdSource::Spec;
+    Ok(program)
+}
+
+// Validate the spec and rewrite it as a modern-layout IDL with derived discriminators.
+fn spec_to_idl(spec: &Value) -> Result<Value> {
+    let name = match spec["name"].as_str() {
+        Some(n) if !n.is_empty() => n,
+        _ => bail!("`name` is required"),
+    };
+    let program_id = spec["program_id"]
+        .as_str()
+        .context("`program_id` is required")?;
+    if program_id.parse::<Address>().is_err() {
+        bail!("`program_id` `{}` is not a valid address", program_id);
+    }
+
+    let ixs = match spec["instructions"].as_array() {
+        Some(v) if !v.is_empty() => v,
+        _ => bail!("`instructions` must be a non-empty array"),
+    };
+    let mut seen = HashSet::new();
+    let mut instructions = Vec::with_capacity(ixs.len());
+    for (i, ix) in ixs.iter().enumerate() {
+        let ix_name = ix["name"]
+            .as_str()
+            .with_context(|| format!("instructions[{}] is missing `name`", i))?;
+        if !seen.insert(ix_name) {
+            bail!("duplicate instruction `{}`", ix_name);
+        }
+        instructions.push(
+            spec_instruction(ix_name, ix).with_context(|| format!("instruction `{}`", ix_name))?,
+        );
+    }
+
+    // Account types become IDL `accounts` + struct entries in `types`, like modern IDLs.
+    let mut accounts = Vec::new();
+    let mut types: Vec<Value> = spec["types"].as_array().cloned().unwrap_or_default();
+    for (i, a) in spec["account_types"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let type_name = a["name"]
+            .as_str()
+            .with_context(|| format!("account_types[{}] is missing `name`", i))?;
+        let fields = a["fields"]
+            .as_array()
+            .with_context(|| format!("account type `{}` needs a `fields` array", type_name))?;
+        accounts.push(json!({
+            "name": type_name,
+            "discriminator": account_discriminator(type_name),
+        }));
+        types.push(json!({
+            "name": type_name,
+            "type": { "kind": "struct", "fields": fields },
+        }));
+    }
+
+    Ok(json!({
+        "address": program_id,
+        "metadata": { "name": name, "spec": SPEC_VERSION },
+        "instructions": instructions,
+        "accounts": accounts,
+        "types": types,
+        // Same `{code, name, msg}` entries as an IDL, used to name program errors.
+        "errors": spec["errors"].as_array().cloned().unwrap_or_default(),
+    }))
+}
+
+fn spec_instruction(name: &str, ix: &Value) -> Result<Value> {
+    // Anchor derives this from the handler name; native programs can pin their own 8 bytes.
+    let discriminator = match &ix["discriminator"] {
+        Value::Null => instruction_discriminator(name),
+        Value::Array(bytes) => {
+            let d: Vec<u8> = bytes
+                .iter()
+                .filter_map(|b| b.as_u64().and_then(|n| u8::try_from(n).ok()))
+                .collect();
+            if d.len() != 8 || d.len() != bytes.len() {
+                bail!("`discriminator` must be exactly 8 bytes");
+            }
+            d
+        }
+        _ => bail!("`discriminator` must be an array of bytes"),
+    };
+
+    let raw_accounts = ix["accounts"].as_array().cloned().unwrap_or_default();
+    let mut names = HashSet::new();
+    for (i, a) in raw_accounts.iter().enumerate() {
+        let acc = a["name"]
+            .as_str()
+            .with_context(|| format!("accounts[{}] is missing `name`", i))?;
+        if !names.insert(acc) {
+            bail!("duplicate account `{}`", acc);
+        }
+    }
+
+    let raw_args = ix["args"].as_array().cloned().unwrap_or_default();
+    let arg_names: HashSet<&str> = raw_args.iter().filter_map(|a| a["name"].as_str()).collect();
+
+    let mut accounts = Vec::with_capacity(raw_accounts.len());
+    for a in &raw_accounts {
+        let acc = a["name"].as_str().unwrap_or_default();
+        let mut out = json!({
+            "name": acc,
+            "signer": flag(a, "signer").with_context(|| format!("account `{}`", acc))?,
+            "writable": flag(a, "writable").with_context(|| format!("account `{}`", acc))?,
+        });
+        if let Some(address) = a["address"].as_str() {
+            if address.parse::<Address>().is_err() {
+                bail!("account `{}` has an invalid `address`", acc);
+            }
+            out["address"] = json!(address);
+        }
+        if let Some(seeds) = a["seeds"].as_array() {
+            let seeds = seeds
+                .iter()
+                .map(|s| spec_seed(s, &names, &arg_names))
+                .collect::<Result<Vec<_>>>()
+                .with_context(|| format!("account `{}` seeds", acc))?;
+            out["pda"] = json!({ "seeds": seeds });
+        }
+        accounts.push(out);
+    }
+
+    let mut args = Vec::new();
+    for (i, arg) in raw_args.iter().enumerate() {
+        let arg_name = arg["name"]
+            .as_str()
+            .with_context(|| format!("args[{}] is missing `name`", i))?;
+        if arg["type"].is_null() {
+            bail!("arg `{}` is missing `type`", arg_name);
+        }
+        args.push(json!({ "name": arg_name, "type": arg["type"] }));
+    }
+
+    Ok(json!({
+        "name": name,
+        "discriminator": discriminator,
+        "accounts": accounts,
+        "args": args,
+    }))
+}
+
+// Optional boolean account flag; anything but true/false/absent is a spec error.
+fn flag(account: &Value, key: &str) -> Result<bool> {
+    match &account[key] {
+        Value::Null => Ok(false),
+        Value::Bool(b) => Ok(*b),
+        _ => bail!("`{}` must be a boolean", key),
+    }
+}
+
+// `{"const": "vault"}` / `{"const": [1, 2]}` / `{"account": "user"}` / `{"arg": "index"}`
+// -> IDL seed objects.
+fn spec_seed(seed: &Value, accounts: &HashSet<&str>, args: &HashSet<&str>) -> Result<Value> {
+    if let Some(c) = seed.get("const") {
+        let bytes: Vec<u8> = match c {
+            Value::String(s) => s.as_bytes().to_vec(),
+            Value::Array(a) => a
+                .iter()
+                .map(|b| b.as_u64().and_then(|n| u8::try_from(n).ok()))
+                .collect::<Option<_>>()
+                .context("const seed bytes must be 0-255")?,
+            _ => bail!("const seed must be a string or byte array"),
+        };
+        return Ok(json!({ "kind": "const", "value": bytes }));
+    }
+    if let Some(path) = seed["account"].as_str() {
+        // `vault.authority` reads a field of the `vault` account.
+        let base = path.split_once('.').map_or(path, |(base, _)| base);
+        if !accounts.contains(base) {
+            bail!("seed references unknown account `{}`", path);
+        }
+        return Ok(json!({ "kind": "account", "path": path }));
+    }
+    if let Some(path) = seed["arg"].as_str() {
+        if !args.contains(path) {
+            bail!("seed references unknown arg `{}`", path);
+        }
+        return Ok(json!({ "kind": "arg", "path": path }));
+    }
+    bail!("seed must have one of `const`, `account` or `arg`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault_spec() -> Value {
+        json!({
+            "name": "anchor_testing_suite",
+            "program_id": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "instructions": [{
+                "name": "initialize_vault",
+                "accounts": [
+                    { "name": "vault", "writable": true,
+                      "seeds": [{ "const": "vault" }, { "account": "user" }] },
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "system_program" }
+                ]
+            }],
+            "account_types": [{ "name": "Vault", "fields": [
+                { "name": "authority", "type": "pubkey" }
+            ] }]
+        })
+    }
+
+    #[test]
+    fn spec_derives_anchor_discriminators_and_seeds() {
+        let idl = spec_to_idl(&vault_spec()).unwrap();
+        let ix = crate::specs::parse_instruction(&idl["instructions"][0]).unwrap();
+        // sha256("global:initialize_vault")[..8], same bytes the real IDL carries.
+        assert_eq!(ix.discriminator, vec![48, 191, 163, 44, 71, 129, 63, 164]);
+        assert_eq!(ix.accounts[0].pda_seeds.len(), 2);
+        assert!(ix.accounts[1].signer && !ix.accounts[2].writable);
+        assert_eq!(
+            idl["accounts"][0]["discriminator"],
+            json!(account_discriminator("Vault"))
+        );
+    }
+
+    #[test]
+    fn spec_rejects_dangling_seed_and_duplicate_instruction() {
+        let mut spec = vault_spec();
+        spec["instructions"][0]["accounts"][0]["seeds"][1] = json!({ "account": "nobody" });
+        assert!(spec_to_idl(&spec).is_err());
+
+        let mut spec = vault_spec();
+        let ix = spec["instructions"][0].clone();
+        spec["instructions"].as_array_mut().unwrap().push(ix);
+        assert!(spec_to_idl(&spec).is_err());
+    }
+}
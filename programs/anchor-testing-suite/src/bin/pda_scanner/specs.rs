@@ -1,41 +1,62 @@
-use crate::types::{AccountSpec, ArgSpec, InstructionSpec, ProgramSpec, SeedSpec};
+use crate::errors::parse_program_errors;
+use crate::idl_format::{instruction_discriminator, normalize};
+use crate::types::{
+    AccountSpec, AccountTypeSpec, ArgSpec, FieldSpec, IdlFormat, InstructionSpec, ProgramIdSource,
+    ProgramSpec, SeedSpec, SkippedIdl,
+};
 use anyhow::{bail, Context, Result};
 use serde_json::Value;
 use solana_address::Address;
+use solana_keypair::read_keypair_file;
+use solana_signer::Signer;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-// Load program specs by pairing IDL files with the compiled .so in target/deploy.
-pub fn load_program_specs(idl_dir: &Path, deploy_dir: &Path) -> Result<Vec<ProgramSpec>> {
-    let deploy_sos: Vec<PathBuf> = fs::read_dir(deploy_dir)?
+// Load program specs by pairing IDL files with the compiled .so in target/deploy (`so_map`
+// entries, `<idl_stem>=<so_filename>`, take precedence). Also returns the IDL files skipped
+// because no program id or no matching binary could be found for them.
+pub fn load_program_specs(
+    idl_dir: &Path,
+    deploy_dir: &Path,
+    id_overrides: &[(String, Address)],
+    so_map: &[(String, String)],
+) -> Result<(Vec<ProgramSpec>, Vec<SkippedIdl>)> {
+    load_idl_files(&list_idl_files(idl_dir)?, deploy_dir, id_overrides, so_map)
+}
+
+// Every `.json` file directly under the IDL directory, sorted so program order is stable.
+pub fn list_idl_files(idl_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(idl_dir)
+        .with_context(|| format!("Failed reading {}", idl_dir.display()))?
         .filter_map(|e| e.ok().map(|x| x.path()))
-        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("so"))
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
         .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// Same as `load_program_specs`, for an explicit list of IDL files (`--idl`).
+pub fn load_idl_files(
+    idl_files: &[PathBuf],
+    deploy_dir: &Path,
+    id_overrides: &[(String, Address)],
+    so_map: &[(String, String)],
+) -> Result<(Vec<ProgramSpec>, Vec<SkippedIdl>)> {
+    let deploy_sos = list_deploy_sos(deploy_dir)?;
 
     let mut programs = Vec::new();
+    let mut skipped = Vec::new();
+    let mut used_overrides = HashSet::new();
+    let mut idl_stems = HashSet::new();
     // Each IDL may contain multiple instructions; we only keep those that parse cleanly.
-    for entry in fs::read_dir(idl_dir)? {
-        let path = entry?.path();
-        if path.extension().and_then(|s| s.to_str()) != Some("json") {
-            continue;
-        }
-
-        let idl_content = fs::read_to_string(&path)?;
-        let idl: Value = serde_json::from_str(&idl_content)
+    for path in idl_files {
+        let idl_content = fs::read_to_string(path)
+            .with_context(|| format!("Failed reading {}", path.display()))?;
+        let raw: Value = serde_json::from_str(&idl_content)
             .with_context(|| format!("Invalid JSON in {}", path.display()))?;
-
-        // Address is commonly at idl.address or idl.metadata.address.
-        let program_id_str = match idl["address"]
-            .as_str()
-            .or_else(|| idl["metadata"]["address"].as_str())
-        {
-            Some(v) => v,
-            None => continue,
-        };
-        let program_id: Address = match program_id_str.parse() {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
+        // Detect the format per file so mixed-version workspaces load every program.
+        let (idl_format, idl_version, idl) = normalize(&raw);
 
         let idl_file = path
             .file_name()
@@ -44,39 +65,227 @@ pub fn load_program_specs(idl_dir: &Path, deploy_dir: &Path) -> Result<Vec<Progr
             .to_string();
 
         let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+        idl_stems.insert(stem.to_string());
         let meta_name = idl["metadata"]["name"].as_str().unwrap_or("");
-        let deploy_so = resolve_so_file(deploy_dir, &deploy_sos, stem, meta_name)?;
-
-        let mut instructions = Vec::new();
-        if let Some(ixs) = idl["instructions"].as_array() {
-            for ix in ixs {
-                if let Some(spec) = parse_instruction(ix) {
-                    instructions.push(spec);
+        let names = [stem, meta_name];
+        let override_id = id_overrides
+            .iter()
+            .find(|(name, _)| names.iter().any(|n| !n.is_empty() && norm(n) == norm(name)));
+        let (program_id, source) = match override_id {
+            Some((name, id)) => {
+                used_overrides.insert(name.as_str());
+                (*id, ProgramIdSource::Override)
+            }
+            None => match idl_address(&idl) {
+                Some(id) => (id, ProgramIdSource::Idl),
+                None => match deploy_keypair_address(deploy_dir, &names) {
+                    Some(id) => (id, ProgramIdSource::DeployKeypair),
+                    None => {
+                        skipped.push(SkippedIdl::NoProgramId { idl_file });
+                        continue;
+                    }
+                },
+            },
+        };
+        let deploy_so = match so_map.iter().find(|(idl_stem, _)| idl_stem == stem) {
+            Some((_, so)) => {
+                let p = deploy_dir.join(so);
+                if !p.exists() {
+                    bail!(
+                        "--map {}={}: {} does not exist. Available .so files: {}",
+                        stem,
+                        so,
+                        p.display(),
+                        so_names(&deploy_sos)
+                    );
                 }
+                p
             }
+            // One unbuilt program shouldn't abort the run; it becomes a failed check.
+            None => match resolve_so_file(deploy_dir, &deploy_sos, stem, meta_name, &program_id) {
+                Ok(so) => so,
+                Err(e) => {
+                    skipped.push(SkippedIdl::NoMatchingBinary {
+                        idl_file,
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            },
+        };
+
+        let mut program =
+            program_from_idl(&idl, idl_file, idl_format, idl_version, program_id, deploy_so);
+        program.program_id_source = source;
+        if !program.instructions.is_empty() {
+            programs.push(program);
         }
+    }
 
-        if !instructions.is_empty() {
-            programs.push(ProgramSpec {
-                idl_file,
-                program_id,
-                deploy_so,
-                instructions,
-            });
+    if let Some((stem, _)) = so_map.iter().find(|(stem, _)| !idl_stems.contains(stem.as_str())) {
+        bail!("--map `{}` matches no loaded IDL file stem", stem);
+    }
+    if let Some((name, _)) = id_overrides
+        .iter()
+        .find(|(name, _)| !used_overrides.contains(name.as_str()))
+    {
+        bail!("--program-id `{}` matches no loaded IDL file stem or program name", name);
+    }
+
+    Ok((programs, skipped))
+}
+
+// Anchor names deploy artifacts with underscores even when the crate uses dashes.
+fn norm(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+// `--program` matches the IDL `metadata.name`, the IDL file stem or the base58 program address.
+pub fn program_matches(filter: &str, name: &str, idl_stem: &str, address: &str) -> bool {
+    [name, idl_stem]
+        .iter()
+        .any(|n| !n.is_empty() && norm(n) == norm(filter))
+        || address == filter
+}
+
+// Keep only the program `--program` selects; matching nothing lists what was loaded.
+pub fn select_program(programs: Vec<ProgramSpec>, filter: &str) -> Result<Vec<ProgramSpec>> {
+    let available = programs
+        .iter()
+        .map(|p| format!("{} ({})", p.idl_file, p.program_id))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let selected: Vec<ProgramSpec> = programs
+        .into_iter()
+        .filter(|p| {
+            let stem = p.idl_file.strip_suffix(".json").unwrap_or(&p.idl_file);
+            program_matches(filter, &p.name, stem, &p.program_id.to_string())
+        })
+        .collect();
+    if selected.is_empty() {
+        bail!(
+            "--program `{}` matches no loaded program. Available programs: {}",
+            filter,
+            available
+        );
+    }
+    Ok(selected)
+}
+
+// Address is commonly at idl.address or idl.metadata.address.
+fn idl_address(idl: &Value) -> Option<Address> {
+    idl["address"]
+        .as_str()
+        .or_else(|| idl["metadata"]["address"].as_str())?
+        .parse()
+        .ok()
+}
+
+// Pubkey of `<deploy>/<name>-keypair.json`, the keypair `anchor build` generates per program.
+fn deploy_keypair_address(deploy_dir: &Path, names: &[&str]) -> Option<Address> {
+    names.iter().filter(|n| !n.is_empty()).find_map(|n| {
+        let path = deploy_dir.join(format!("{}-keypair.json", norm(n)));
+        let keypair = read_keypair_file(path).ok()?;
+        Some(keypair.pubkey())
+    })
+}
+
+// Build a program spec from a modern-layout IDL (real or synthesized from a spec file).
+pub fn program_from_idl(
+    idl: &Value,
+    idl_file: String,
+    idl_format: IdlFormat,
+    idl_version: String,
+    program_id: Address,
+    deploy_so: PathBuf,
+) -> ProgramSpec {
+    let mut instructions = Vec::new();
+    if let Some(ixs) = idl["instructions"].as_array() {
+        for ix in ixs {
+            if let Some(spec) = parse_instruction(ix) {
+                instructions.push(spec);
+            }
+        }
+    }
+
+    let (account_types, types) = parse_account_types(idl);
+    let errors = parse_program_errors(idl);
+
+    ProgramSpec {
+        idl_file,
+        name: idl["metadata"]["name"].as_str().unwrap_or_default().to_string(),
+        idl_format,
+        idl_version,
+        program_id,
+        program_id_source: ProgramIdSource::Idl,
+        deploy_so,
+        instructions,
+        account_types,
+        types,
+        errors,
+    }
+}
+
+// Every `.so` directly under the deploy directory.
+pub fn list_deploy_sos(deploy_dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(fs::read_dir(deploy_dir)
+        .with_context(|| format!("Failed reading {}", deploy_dir.display()))?
+        .filter_map(|e| e.ok().map(|x| x.path()))
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("so"))
+        .collect())
+}
+
+// Collect the IDL `types` table and the account types (with fields) declared under `accounts`.
+fn parse_account_types(idl: &Value) -> (Vec<AccountTypeSpec>, HashMap<String, Value>) {
+    let mut types = HashMap::new();
+    for t in idl["types"].as_array().into_iter().flatten() {
+        if let Some(name) = t["name"].as_str() {
+            types.insert(name.to_string(), t.clone());
         }
     }
 
-    Ok(programs)
+    let mut accounts = Vec::new();
+    for a in idl["accounts"].as_array().into_iter().flatten() {
+        let name = match a["name"].as_str() {
+            Some(n) => n.to_string(),
+            None => continue,
+        };
+        let discriminator = a["discriminator"]
+            .as_array()
+            .map(|d| d.iter().filter_map(|v| v.as_u64().map(|n| n as u8)).collect())
+            .unwrap_or_default();
+        let fields = types
+            .get(&name)
+            .and_then(|t| t["type"]["fields"].as_array())
+            .map(|list| {
+                list.iter()
+                    .map(|f| FieldSpec {
+                        name: f["name"].as_str().unwrap_or("field").to_string(),
+                        ty: f["type"].clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        accounts.push(AccountTypeSpec {
+            name,
+            discriminator,
+            fields,
+        });
+    }
+
+    (accounts, types)
 }
 
-// Find the best matching .so file for a given IDL (by stem, metadata name, or single .so fallback).
-fn resolve_so_file(
+// Find the .so for an IDL: named after its file stem or metadata name, else the only binary
+// that embeds its program id (`declare_id!` compiles the 32 address bytes into the program).
+// A lone .so that doesn't carry the id is never assumed to match.
+pub fn resolve_so_file(
     deploy_dir: &Path,
     sos: &[PathBuf],
     stem: &str,
     meta_name: &str,
+    program_id: &Address,
 ) -> Result<PathBuf> {
-    let norm = |s: &str| s.replace('-', "_");
     let candidates = [format!("{}.so", norm(stem)), format!("{}.so", norm(meta_name))];
 
     for c in &candidates {
@@ -86,26 +295,53 @@ fn resolve_so_file(
         }
     }
 
-    if sos.len() == 1 {
-        return Ok(sos[0].clone());
+    let id = program_id.as_ref();
+    let embedding: Vec<&PathBuf> = sos
+        .iter()
+        .filter(|so| fs::read(so).is_ok_and(|bytes| bytes.windows(id.len()).any(|w| w == id)))
+        .collect();
+    if let [so] = embedding.as_slice() {
+        return Ok(so.to_path_buf());
     }
 
     bail!(
-        "Could not resolve matching .so in {} for idl stem={} meta_name={}",
+        "Could not resolve matching .so in {} for idl stem={} meta_name={}: no file is named \
+         after it and {} embed program id {}. Candidates: {}. Pick one with --map {}=<so_filename>",
         deploy_dir.display(),
         stem,
-        meta_name
+        meta_name,
+        if embedding.is_empty() { "none" } else { "several" },
+        program_id,
+        so_names(sos),
+        stem
     )
 }
 
+// Comma-separated file names of the deploy dir's .so files, for error messages.
+fn so_names(sos: &[PathBuf]) -> String {
+    let mut names: Vec<&str> = sos
+        .iter()
+        .filter_map(|p| p.file_name().and_then(|s| s.to_str()))
+        .collect();
+    names.sort_unstable();
+    if names.is_empty() {
+        "<none>".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
 // Convert an IDL instruction JSON blob into an internal InstructionSpec.
-fn parse_instruction(ix: &Value) -> Option<InstructionSpec> {
+pub fn parse_instruction(ix: &Value) -> Option<InstructionSpec> {
     let name = ix["name"].as_str()?.to_string();
-    let discriminator = ix["discriminator"]
-        .as_array()?
-        .iter()
-        .filter_map(|v| v.as_u64().map(|n| n as u8))
-        .collect::<Vec<_>>();
+    // Pre-0.30 IDLs have no `discriminator`; Anchor derived it from the handler name.
+    let discriminator = match ix["discriminator"].as_array() {
+        Some(bytes) => bytes
+            .iter()
+            .filter_map(|v| v.as_u64().map(|n| n as u8))
+            .collect::<Vec<_>>(),
+        None => instruction_discriminator(&name),
+    };
     if discriminator.len() != 8 {
         return None;
     }
@@ -136,6 +372,11 @@ fn parse_instruction(ix: &Value) -> Option<InstructionSpec> {
                                 pda_seeds.push(SeedSpec::Account(path.to_string()));
                             }
                         }
+                        "arg" => {
+                            if let Some(path) = s["path"].as_str() {
+                                pda_seeds.push(SeedSpec::Arg(path.to_string()));
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -146,6 +387,7 @@ fn parse_instruction(ix: &Value) -> Option<InstructionSpec> {
                 signer,
                 writable,
                 pda_seeds,
+                address: a["address"].as_str().and_then(|s| s.parse().ok()),
             });
         }
     }
@@ -166,3 +408,313 @@ fn parse_instruction(ix: &Value) -> Option<InstructionSpec> {
         args,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Fresh scratch dir under the system temp dir (no tempfile dependency).
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("anchor-suite-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("idl")).unwrap();
+        fs::create_dir_all(dir.join("deploy")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn loads_legacy_and_modern_idls_side_by_side() {
+        let dir = scratch_dir("mixed-idl");
+        let modern = json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "metadata": { "name": "modern_vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "initialize_vault",
+                "discriminator": [48, 191, 163, 44, 71, 129, 63, 164],
+                "accounts": [
+                    { "name": "vault", "writable": true, "pda": { "seeds": [
+                        { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                        { "kind": "account", "path": "user" }
+                    ]}},
+                    { "name": "user", "writable": true, "signer": true }
+                ],
+                "args": []
+            }]
+        });
+        let legacy = json!({
+            "version": "0.1.0",
+            "name": "legacy_vault",
+            "metadata": { "address": "GLnH5huAyPLjEY9rNkWceL5mi9zYwwua5apJcZm1hC51" },
+            "instructions": [{
+                "name": "initializeVault",
+                "accounts": [
+                    { "name": "vault", "isMut": true, "isSigner": false, "pda": { "seeds": [
+                        { "kind": "const", "type": "string", "value": "vault" },
+                        { "kind": "account", "type": "publicKey", "path": "user" }
+                    ]}},
+                    { "name": "user", "isMut": true, "isSigner": true }
+                ],
+                "args": [{ "name": "startAmount", "type": "u64" }]
+            }]
+        });
+        fs::write(dir.join("idl/modern_vault.json"), modern.to_string()).unwrap();
+        fs::write(dir.join("idl/legacy_vault.json"), legacy.to_string()).unwrap();
+        fs::write(dir.join("deploy/modern_vault.so"), b"").unwrap();
+        fs::write(dir.join("deploy/legacy_vault.so"), b"").unwrap();
+
+        let (mut programs, unresolved) =
+            load_program_specs(&dir.join("idl"), &dir.join("deploy"), &[], &[]).unwrap();
+        assert!(unresolved.is_empty());
+        programs.sort_by(|a, b| a.idl_file.cmp(&b.idl_file));
+        assert_eq!(programs.len(), 2);
+
+        let (legacy, modern) = (&programs[0], &programs[1]);
+        assert_eq!(legacy.idl_format, IdlFormat::Legacy);
+        assert_eq!(modern.idl_format, IdlFormat::Modern);
+
+        // Both shapes normalize to the same instruction layout.
+        let (l, m) = (&legacy.instructions[0], &modern.instructions[0]);
+        assert_eq!(l.name, m.name);
+        assert_eq!(l.discriminator, m.discriminator);
+        assert_eq!(l.accounts.len(), m.accounts.len());
+        assert!(l.accounts[0].writable && !l.accounts[0].signer);
+        assert!(l.accounts[1].signer);
+        assert_eq!(l.accounts[0].pda_seeds.len(), 2);
+        assert_eq!(l.args[0].name, "start_amount");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn legacy_account_groups_flatten_and_missing_discriminators_are_derived() {
+        let legacy = json!({
+            "version": "0.1.0",
+            "name": "legacy_vault",
+            "instructions": [{
+                "name": "deposit",
+                "accounts": [
+                    { "name": "common", "accounts": [
+                        { "name": "vault", "isMut": true, "isSigner": false },
+                        { "name": "user", "isMut": true, "isSigner": true }
+                    ]},
+                    { "name": "systemProgram", "isMut": false, "isSigner": false }
+                ],
+                "args": [{ "name": "amount", "type": "u64" }]
+            }]
+        });
+        let (_, _, idl) = normalize(&legacy);
+        let ix = parse_instruction(&idl["instructions"][0]).unwrap();
+        let names: Vec<&str> = ix.accounts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["vault", "user", "system_program"]);
+        assert!(ix.accounts[1].signer);
+
+        // A hand-trimmed modern-looking instruction without `discriminator` still loads.
+        let bare = json!({ "name": "deposit", "accounts": [], "args": [] });
+        assert_eq!(parse_instruction(&bare).unwrap().discriminator, ix.discriminator);
+    }
+
+    #[test]
+    fn program_id_falls_back_to_the_deploy_keypair_then_overrides() {
+        let dir = scratch_dir("keypair-id");
+        let legacy = json!({
+            "version": "0.1.0",
+            "name": "old_vault",
+            "instructions": [{ "name": "deposit", "accounts": [], "args": [] }]
+        });
+        fs::write(dir.join("idl/old_vault.json"), legacy.to_string()).unwrap();
+        fs::write(dir.join("deploy/old_vault.so"), b"").unwrap();
+        let (idl_dir, deploy_dir) = (dir.join("idl"), dir.join("deploy"));
+
+        // No address anywhere: reported instead of silently dropped.
+        let (programs, unresolved) = load_program_specs(&idl_dir, &deploy_dir, &[], &[]).unwrap();
+        assert!(programs.is_empty());
+        assert_eq!(
+            unresolved,
+            vec![SkippedIdl::NoProgramId {
+                idl_file: "old_vault.json".to_string()
+            }]
+        );
+
+        let keypair = solana_keypair::Keypair::new();
+        solana_keypair::write_keypair_file(&keypair, deploy_dir.join("old_vault-keypair.json"))
+            .unwrap();
+        let (programs, _) = load_program_specs(&idl_dir, &deploy_dir, &[], &[]).unwrap();
+        assert_eq!(programs[0].program_id, keypair.pubkey());
+        assert_eq!(programs[0].program_id_source, ProgramIdSource::DeployKeypair);
+
+        let pinned = Address::new_from_array([9; 32]);
+        let overrides = [("old-vault".to_string(), pinned)];
+        let (programs, _) = load_program_specs(&idl_dir, &deploy_dir, &overrides, &[]).unwrap();
+        assert_eq!(programs[0].program_id, pinned);
+        assert_eq!(programs[0].program_id_source, ProgramIdSource::Override);
+
+        let unknown = [("nope".to_string(), pinned)];
+        assert!(load_program_specs(&idl_dir, &deploy_dir, &unknown, &[]).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn binaries_fall_back_to_the_one_embedding_the_program_id() {
+        let dir = scratch_dir("embedded-id");
+        let id: Address = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS".parse().unwrap();
+        let idl = json!({
+            "address": id.to_string(),
+            "metadata": { "name": "vault", "spec": "0.1.0" },
+            "instructions": [{ "name": "deposit", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }]
+        });
+        fs::write(dir.join("idl/vault.json"), idl.to_string()).unwrap();
+        let elf_with = |id: &[u8]| [b"\x7fELF".as_slice(), &[0; 60], id, &[0; 16]].concat();
+        let other: Address = "GLnH5huAyPLjEY9rNkWceL5mi9zYwwua5apJcZm1hC51".parse().unwrap();
+        let (idl_dir, deploy_dir) = (dir.join("idl"), dir.join("deploy"));
+
+        // A lone binary built from another program is no longer paired with the IDL.
+        fs::write(deploy_dir.join("escrow.so"), elf_with(other.as_ref())).unwrap();
+        let (programs, skipped) = load_program_specs(&idl_dir, &deploy_dir, &[], &[]).unwrap();
+        assert!(programs.is_empty());
+        assert!(matches!(
+            &skipped[0],
+            SkippedIdl::NoMatchingBinary { idl_file, .. } if idl_file == "vault.json"
+        ));
+
+        fs::write(deploy_dir.join("renamed.so"), elf_with(id.as_ref())).unwrap();
+        let (programs, skipped) = load_program_specs(&idl_dir, &deploy_dir, &[], &[]).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(programs[0].deploy_so, deploy_dir.join("renamed.so"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn so_map_pairs_idls_the_name_heuristic_cannot() {
+        let dir = scratch_dir("so-map");
+        let idl = json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "metadata": { "name": "vault", "spec": "0.1.0" },
+            "instructions": [{ "name": "deposit", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }]
+        });
+        fs::write(dir.join("idl/vault.json"), idl.to_string()).unwrap();
+        fs::write(dir.join("deploy/vault_v2.so"), b"").unwrap();
+        fs::write(dir.join("deploy/other.so"), b"").unwrap();
+        let (idl_dir, deploy_dir) = (dir.join("idl"), dir.join("deploy"));
+
+        // Neither binary is named after the IDL or embeds its id: skipped, not fatal.
+        let (programs, skipped) = load_program_specs(&idl_dir, &deploy_dir, &[], &[]).unwrap();
+        assert!(programs.is_empty());
+        let SkippedIdl::NoMatchingBinary { idl_file, reason } = &skipped[0] else {
+            panic!("{:?}", skipped);
+        };
+        assert_eq!(idl_file, "vault.json");
+        assert!(reason.contains("Candidates: other.so, vault_v2.so"), "{}", reason);
+
+        let map = [("vault".to_string(), "vault_v2.so".to_string())];
+        let (programs, _) = load_program_specs(&idl_dir, &deploy_dir, &[], &map).unwrap();
+        assert_eq!(programs[0].deploy_so, deploy_dir.join("vault_v2.so"));
+
+        let missing = [("vault".to_string(), "gone.so".to_string())];
+        assert!(load_program_specs(&idl_dir, &deploy_dir, &[], &missing).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn explicit_idl_files_load_from_any_directory() {
+        let dir = scratch_dir("idl-files");
+        let idl = |name: &str| {
+            json!({
+                "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+                "metadata": { "name": name, "spec": "0.1.0" },
+                "instructions": [{ "name": "deposit", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }]
+            })
+        };
+        fs::create_dir_all(dir.join("artifacts/idl")).unwrap();
+        fs::write(dir.join("artifacts/idl/vault.json"), idl("vault").to_string()).unwrap();
+        fs::write(dir.join("artifacts/idl/other.json"), idl("other").to_string()).unwrap();
+        fs::write(dir.join("deploy/vault.so"), b"").unwrap();
+
+        // Only the listed file is loaded, even though its directory has another IDL.
+        let files = [dir.join("artifacts/idl/vault.json")];
+        let (programs, _) = load_idl_files(&files, &dir.join("deploy"), &[], &[]).unwrap();
+        assert_eq!(programs.len(), 1);
+        assert_eq!(programs[0].idl_file, "vault.json");
+
+        assert_eq!(
+            list_idl_files(&dir.join("artifacts/idl")).unwrap(),
+            [dir.join("artifacts/idl/other.json"), files[0].clone()]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn legacy_and_modern_fixtures_parse_to_identical_instructions() {
+        let parse = |raw: &str| {
+            let (format, _, idl) = normalize(&serde_json::from_str(raw).unwrap());
+            let ixs: Vec<InstructionSpec> = idl["instructions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|ix| parse_instruction(ix).unwrap())
+                .collect();
+            (format, idl_address(&idl), ixs)
+        };
+        let legacy = parse(include_str!("../../../tests/fixtures/idl/test_vault.legacy.json"));
+        let modern = parse(include_str!("../../../tests/fixtures/idl/test_vault.modern.json"));
+
+        assert_eq!(legacy.0, IdlFormat::Legacy);
+        assert_eq!(modern.0, IdlFormat::Modern);
+        assert_eq!(legacy.1, modern.1);
+        assert_eq!(legacy.2.len(), 6);
+        assert_eq!(legacy.2, modern.2);
+
+        // `{"type": "const", "value": "vault"}` without a `kind` key.
+        let (_, _, idl) = normalize(&json!({
+            "name": "v",
+            "instructions": [{ "name": "deposit", "accounts": [{ "name": "vault", "pda": { "seeds": [
+                { "type": "const", "value": "vault" }
+            ]}}]}]
+        }));
+        let ix = parse_instruction(&idl["instructions"][0]).unwrap();
+        assert_eq!(ix.accounts[0].pda_seeds, vec![SeedSpec::Const(b"vault".to_vec())]);
+    }
+
+    #[test]
+    fn program_filter_matches_name_stem_or_address() {
+        let dir = scratch_dir("program-filter");
+        let idl = |address: &str, name: &str| {
+            json!({
+                "address": address,
+                "metadata": { "name": name, "version": "0.1.0", "spec": "0.1.0" },
+                "instructions": [{
+                    "name": "deposit",
+                    "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                    "accounts": [],
+                    "args": []
+                }]
+            })
+        };
+        let vault = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS";
+        let escrow = "GLnH5huAyPLjEY9rNkWceL5mi9zYwwua5apJcZm1hC51";
+        fs::write(dir.join("idl/vault.json"), idl(vault, "my-vault").to_string()).unwrap();
+        fs::write(dir.join("idl/escrow.json"), idl(escrow, "escrow").to_string()).unwrap();
+        fs::write(dir.join("deploy/my_vault.so"), b"").unwrap();
+        fs::write(dir.join("deploy/escrow.so"), b"").unwrap();
+        let load = || {
+            let (programs, _) =
+                load_program_specs(&dir.join("idl"), &dir.join("deploy"), &[], &[]).unwrap();
+            programs
+        };
+
+        for filter in ["vault", "my_vault", vault] {
+            let selected = select_program(load(), filter).unwrap();
+            assert_eq!(selected.len(), 1);
+            assert_eq!(selected[0].idl_file, "vault.json");
+        }
+        let err = select_program(load(), "swap").unwrap_err().to_string();
+        assert!(err.contains(&format!("escrow.json ({})", escrow)), "{}", err);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,9 @@
 use serde_json::Value;
 use solana_address::Address;
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::time::Duration;
 
 // Result summary for the optional smoke test run.
 #[derive(Debug)]
@@ -11,17 +14,113 @@ pub struct SmokeResult {
     pub stderr: String,
 }
 
+// IDL layout family; each file is detected independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdlFormat {
+    // Anchor >= 0.30 (`metadata.spec`, explicit discriminators).
+    Modern,
+    // Anchor < 0.30 (`isMut`/`isSigner`, name-derived discriminators).
+    Legacy,
+    // Hand-written spec file (`--spec`), no IDL involved.
+    Spec,
+}
+
+impl IdlFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IdlFormat::Modern => "modern",
+            IdlFormat::Legacy => "legacy",
+            IdlFormat::Spec => "spec",
+        }
+    }
+}
+
+// An IDL left out of the run because it can't be paired with a deployable program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkippedIdl {
+    // No IDL address, `--program-id` override or deploy keypair.
+    NoProgramId { idl_file: String },
+    // No .so is named after the IDL or embeds its program id; `reason` lists the candidates.
+    NoMatchingBinary { idl_file: String, reason: String },
+}
+
+impl SkippedIdl {
+    pub fn idl_file(&self) -> &str {
+        match self {
+            SkippedIdl::NoProgramId { idl_file } => idl_file,
+            SkippedIdl::NoMatchingBinary { idl_file, .. } => idl_file,
+        }
+    }
+}
+
+// Where a program's id came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramIdSource {
+    // `address` / `metadata.address` in the IDL.
+    Idl,
+    // `target/deploy/<name>-keypair.json`, for older IDLs without an address.
+    DeployKeypair,
+    // `--program-id <name>:<pubkey>`.
+    Override,
+    // `program_id` of a `--spec` file.
+    Spec,
+}
+
+impl ProgramIdSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProgramIdSource::Idl => "idl",
+            ProgramIdSource::DeployKeypair => "deploy_keypair",
+            ProgramIdSource::Override => "override",
+            ProgramIdSource::Spec => "spec",
+        }
+    }
+}
+
 // Parsed representation of one program + its instructions from IDL.
 #[derive(Debug)]
 pub struct ProgramSpec {
     pub idl_file: String,
+    // IDL `metadata.name`; empty when the IDL doesn't declare one.
+    pub name: String,
+    pub idl_format: IdlFormat,
+    pub idl_version: String,
     pub program_id: Address,
+    pub program_id_source: ProgramIdSource,
     pub deploy_so: PathBuf,
     pub instructions: Vec<InstructionSpec>,
+    pub account_types: Vec<AccountTypeSpec>,
+    // Raw IDL `types` entries keyed by name, for resolving `defined` references.
+    pub types: HashMap<String, Value>,
+    // Program-specific error codes from the IDL `errors` section.
+    pub errors: Vec<ProgramError>,
 }
 
-// Parsed instruction schema from the IDL.
+// One `#[error_code]` variant declared in the IDL.
 #[derive(Debug, Clone)]
+pub struct ProgramError {
+    pub code: u32,
+    pub name: String,
+    pub msg: Option<String>,
+}
+
+// Account type from the IDL `accounts` section with its field layout.
+#[derive(Debug, Clone)]
+pub struct AccountTypeSpec {
+    pub name: String,
+    pub discriminator: Vec<u8>,
+    pub fields: Vec<FieldSpec>,
+}
+
+// One named field of an account or struct type.
+#[derive(Debug, Clone)]
+pub struct FieldSpec {
+    pub name: String,
+    pub ty: Value,
+}
+
+// Parsed instruction schema from the IDL.
+#[derive(Debug, Clone, PartialEq)]
 pub struct InstructionSpec {
     pub name: String,
     pub discriminator: Vec<u8>,
@@ -30,37 +129,75 @@ pub struct InstructionSpec {
 }
 
 // Account metadata for an instruction (signer, writable, PDA seeds).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AccountSpec {
     pub name: String,
     pub signer: bool,
     pub writable: bool,
     pub pda_seeds: Vec<SeedSpec>,
+    // Fixed address from the IDL (`address` field), e.g. programs and sysvars.
+    pub address: Option<Address>,
 }
 
 // Supported PDA seed kinds extracted from the IDL.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SeedSpec {
     Const(Vec<u8>),
     Account(String),
+    // Instruction arg by name; seeded with the same bytes the instruction data carries.
+    Arg(String),
+}
+
+impl SeedSpec {
+    // Short form used when reporting unresolved seeds.
+    pub fn label(&self) -> String {
+        match self {
+            SeedSpec::Const(bytes) => format!("const:{:?}", bytes),
+            SeedSpec::Account(path) => format!("account:{}", path),
+            SeedSpec::Arg(path) => format!("arg:{}", path),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SeedSpec::Const(_) => "const",
+            SeedSpec::Account(_) => "account",
+            SeedSpec::Arg(_) => "arg",
+        }
+    }
 }
 
 // Instruction argument schema (type is raw IDL JSON for flexible parsing).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ArgSpec {
     pub name: String,
     pub ty: Value,
 }
 
+// Instruction argument the encoder cannot produce bytes for.
+#[derive(Debug, Clone)]
+pub struct UnsupportedArg {
+    pub idl_file: String,
+    pub instruction: String,
+    pub field: String,
+    pub ty: String,
+    pub reason: String,
+}
+
 // One generated mutation case to execute.
 #[derive(Debug, Clone)]
 pub struct EdgeCase {
+    // `<instruction>_<suffix>_<hash>`; see `case_id`.
     pub id: String,
+    // `<idl_file>_<instruction>_<suffix>`, for people reading reports.
+    pub display_name: String,
     pub idl_file: String,
     pub program_id: Address,
     pub instruction: InstructionSpec,
     pub mutation: Mutation,
     pub expectation: Expectation,
+    // Initializer instructions run (in order, same SVM) before the case itself.
+    pub setup_chain: Vec<String>,
 }
 
 // Mutation category applied to a base instruction.
@@ -70,26 +207,464 @@ pub enum Mutation {
     WrongProgramId,
     TruncateData,
     WrongPda { account: String },
+    // `account` re-derived with only seed `seed_index` corrupted (see `corrupt_seed`).
+    CorruptSeed { account: String, seed_index: usize },
+    // `account` at its seeds with a valid bump below the canonical one.
+    NonCanonicalBump { account: String },
+    // `account` derived with the right seeds, but for an unrelated user in place of the signers.
+    ForeignPda { account: String },
+    WrongOwner { account: String },
+    // `account` is derived correctly but the state the setup chain created there is erased.
+    Uninitialized { account: String },
+    // The initializer is sent once unmutated; the case's transaction is the second attempt.
+    DoubleInit,
+    RemoveAccount { index: usize, account: String },
+    OptionSome,
+    // Every integer arg encoded at its type's maximum value.
+    MaxArgs,
+    // Amount-like integer args (`amount`, `lamports`, ...) at their maximum, aimed at the
+    // program's declared errors (e.g. InsufficientFunds).
+    HugeAmount,
+    ExtraAccount,
+    // `account`, declared writable, sent as a readonly meta.
+    NotWritable { account: String },
+    // `account`, declared readonly, sent as a writable meta.
+    UnexpectedWritable { account: String },
+    CorruptDiscriminator,
+    EmptyData,
+    MissingSigner { account: String },
+    WrongSigner { account: String },
+    // The state `account` owns stays derived for it, but an unrelated keypair signs in its place.
+    WrongAuthority { account: String },
+    // `account` is given the same pubkey as `source`.
+    DuplicateAccount { account: String, source: String },
+    // One integer arg at a boundary value; every other arg stays zero.
+    Boundary { arg: String, value: Boundary },
+    // `test --fuzz`: every arg replaced by a random Borsh value drawn from `seed`.
+    RandomArgs { seed: u64, args: Vec<Vec<u8>> },
+    // Seeded random perturbation from the `fuzz` command; `test` never generates these.
+    Fuzz(FuzzPerturbation),
+}
+
+// One random perturbation drawn by `fuzz`; everything random is drawn upfront from the seeded
+// RNG so an iteration is fully described by its perturbation.
+#[derive(Debug, Clone)]
+pub enum FuzzPerturbation {
+    // Overwrite instruction data bytes at (offset, value); the discriminator is fair game.
+    DataBytes { writes: Vec<(usize, u8)> },
+    // Args replaced by these random Borsh values (`encode_arg_random`).
+    ArgValues { values: Vec<(String, Vec<u8>)> },
+    // Account metas reordered: position i gets the IDL's account `order[i]`.
+    AccountOrder { order: Vec<usize> },
+    // One seed of a PDA account has its first byte XORed before derivation.
+    PdaSeed {
+        account: String,
+        seed_index: usize,
+        xor: u8,
+    },
+}
+
+impl FuzzPerturbation {
+    pub fn label(&self) -> String {
+        match self {
+            FuzzPerturbation::DataBytes { .. } => "data_bytes".to_string(),
+            FuzzPerturbation::ArgValues { .. } => "arg_values".to_string(),
+            FuzzPerturbation::AccountOrder { .. } => "account_order".to_string(),
+            FuzzPerturbation::PdaSeed { account, .. } => format!("pda_seed:{}", account),
+        }
+    }
+}
+
+// Boundary value of an integer type used by `boundary` cases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    One,
+    Max,
+    // Signed types only; an unsigned minimum is the zero the base case already covers.
+    Min,
+}
+
+impl Boundary {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Boundary::One => "one",
+            Boundary::Max => "max",
+            Boundary::Min => "min",
+        }
+    }
+}
+
+// Every mutation kind, i.e. the label without its `:<account>` suffix.
+pub const MUTATION_KINDS: &[&str] = &[
+    "none",
+    "wrong_program_id",
+    "truncate_data",
+    "wrong_pda",
+    "corrupt_seed",
+    "non_canonical_bump",
+    "foreign_pda",
+    "wrong_owner",
+    "uninitialized",
+    "double_init",
+    "remove_account",
+    "option_some",
+    "max_args",
+    "huge_amount",
+    "extra_account",
+    "not_writable",
+    "unexpected_writable",
+    "corrupt_discriminator",
+    "empty_data",
+    "missing_signer",
+    "wrong_signer",
+    "wrong_authority",
+    "duplicate_account",
+    "boundary",
+    "random_args",
+];
+
+impl Mutation {
+    // Label without the account suffix (`wrong_pda:vault` -> `wrong_pda`).
+    pub fn kind(&self) -> String {
+        let label = self.label();
+        match label.split_once(':') {
+            Some((kind, _)) => kind.to_string(),
+            None => label,
+        }
+    }
+
+    // Stable string form used in reports and classifier rules.
+    pub fn label(&self) -> String {
+        match self {
+            Mutation::None => "none".to_string(),
+            Mutation::WrongProgramId => "wrong_program_id".to_string(),
+            Mutation::TruncateData => "truncate_data".to_string(),
+            Mutation::WrongPda { account } => format!("wrong_pda:{}", account),
+            Mutation::CorruptSeed {
+                account,
+                seed_index,
+            } => format!("corrupt_seed:{}[{}]", account, seed_index),
+            Mutation::NonCanonicalBump { account } => format!("non_canonical_bump:{}", account),
+            Mutation::ForeignPda { account } => format!("foreign_pda:{}", account),
+            Mutation::WrongOwner { account } => format!("wrong_owner:{}", account),
+            Mutation::Uninitialized { account } => format!("uninitialized:{}", account),
+            Mutation::DoubleInit => "double_init".to_string(),
+            Mutation::RemoveAccount { account, .. } => format!("remove_account:{}", account),
+            Mutation::OptionSome => "option_some".to_string(),
+            Mutation::MaxArgs => "max_args".to_string(),
+            Mutation::HugeAmount => "huge_amount".to_string(),
+            Mutation::ExtraAccount => "extra_account".to_string(),
+            Mutation::NotWritable { account } => format!("not_writable:{}", account),
+            Mutation::UnexpectedWritable { account } => format!("unexpected_writable:{}", account),
+            Mutation::CorruptDiscriminator => "corrupt_discriminator".to_string(),
+            Mutation::EmptyData => "empty_data".to_string(),
+            Mutation::MissingSigner { account } => format!("missing_signer:{}", account),
+            Mutation::WrongSigner { account } => format!("wrong_signer:{}", account),
+            Mutation::WrongAuthority { account } => format!("wrong_authority:{}", account),
+            Mutation::DuplicateAccount { account, source } => {
+                format!("duplicate_account:{}={}", account, source)
+            }
+            Mutation::Boundary { arg, value } => format!("boundary:{}={}", arg, value.label()),
+            Mutation::RandomArgs { .. } => "random_args".to_string(),
+            Mutation::Fuzz(p) => format!("fuzz:{}", p.label()),
+        }
+    }
 }
 
 // Expected outcome for a case.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Expectation {
     MustFail,
+    // Must fail with a specific error; every part that is set must match.
+    MustFailWith {
+        anchor_code: Option<u32>,
+        contains: Option<String>,
+    },
+    MustSucceed,
     Any,
+    // Passes only if a log line of the case's transaction contains `substring`.
+    LogContains { substring: String },
+    // Must succeed and leave every asserted account slice equal to its expected bytes.
+    AccountState { assertions: Vec<AccountAssertion> },
+}
+
+// `--assertions` entry: after `instruction`'s base case, `account`'s data at `offset` must equal
+// `expected` (the slice is `expected.len()` bytes long).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountAssertion {
+    pub instruction: String,
+    pub account: String,
+    pub offset: usize,
+    pub expected: Vec<u8>,
+}
+
+// An assertion as checked after a case: the bytes actually found at its offset, None when the
+// account doesn't exist, its data is too short, or the transaction failed.
+#[derive(Debug, Clone)]
+pub struct StateCheck {
+    pub assertion: AccountAssertion,
+    pub actual: Option<Vec<u8>>,
+}
+
+impl StateCheck {
+    pub fn matched(&self) -> bool {
+        self.actual.as_deref() == Some(self.assertion.expected.as_slice())
+    }
+}
+
+impl Expectation {
+    // Stable string form used in classifier rules; MustFailWith cases are still `must_fail`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Expectation::MustFail | Expectation::MustFailWith { .. } => "must_fail",
+            Expectation::MustSucceed => "must_succeed",
+            Expectation::Any => "any",
+            Expectation::LogContains { .. } => "log_contains",
+            Expectation::AccountState { .. } => "account_state",
+        }
+    }
+
+    pub fn expected_success(&self) -> Option<bool> {
+        match self {
+            Expectation::Any | Expectation::LogContains { .. } => None,
+            Expectation::MustFail | Expectation::MustFailWith { .. } => Some(false),
+            Expectation::MustSucceed | Expectation::AccountState { .. } => Some(true),
+        }
+    }
 }
 
 // Result of executing one mutation case.
 #[derive(Debug)]
 pub struct ExecutedCase {
     pub id: String,
+    pub display_name: String,
     pub idl_file: String,
     pub instruction: String,
     pub mutation: String,
     pub expected_success: Option<bool>,
     pub actual_success: bool,
     pub passed: bool,
+    pub rule: String,
+    pub error: Option<String>,
+    pub timings: Option<HarnessTimings>,
+    // Set for cases compared against their instruction's base case (extra_account,
+    // unexpected_writable).
+    pub base_case_id: Option<String>,
+    pub same_as_base: Option<bool>,
+    // double_init cases: whether the initializer accepted the second call on the state the first
+    // one created (a reinitialization vulnerability).
+    pub reinitialization_allowed: Option<bool>,
+    pub unresolved_seeds: Vec<UnresolvedSeed>,
+    // IDL files of the programs deployed into the case's SVM (program under test first).
+    pub loaded_programs: Vec<String>,
+    pub setup: Vec<SetupStep>,
+    // Program logs (capped) and compute units; empty/None when no transaction was sent.
+    pub logs: Vec<String>,
+    pub compute_units: Option<u64>,
+    // MustFailWith expectation next to the program error code the failure actually carried.
+    pub expected_error_code: Option<u32>,
+    pub expected_error_contains: Option<String>,
+    pub error_code: Option<u32>,
+    // Anchor or IDL name for `error_code`.
+    pub error_name: Option<String>,
+    // LogContains substring and whether any log line of the case contained it.
+    pub expected_log: Option<String>,
+    pub log_matched: Option<bool>,
+    // AccountState assertions with the bytes found, and whether all of them matched.
+    pub state_checks: Vec<StateCheck>,
+    pub state_matched: Option<bool>,
+    // PDAs the harness fabricated (discriminator, zeroed fields) because nothing created them.
+    pub fabricated_accounts: Vec<String>,
+    // Fields of those PDAs filled from the IDL layout (owner pubkeys, balances).
+    pub fabricated_fields: Vec<FabricatedField>,
+    // The PDA a `foreign_pda` case passed in place of the one its signers derive to.
+    pub foreign_pda: Option<ForeignPda>,
+    // The address a `non_canonical_bump` case passed instead of the canonical PDA.
+    pub non_canonical_bump: Option<BumpSwap>,
+    // The legitimate authority of a `wrong_authority` case and the key that signed instead.
+    pub wrong_authority: Option<AuthoritySwap>,
+    // Decimal value of each integer arg as sent, in declaration order.
+    pub arg_values: Vec<(String, String)>,
+}
+
+// Why a generated case has no result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    // Its program binary failed validation, so it was never deployed.
+    ProgramBinaryInvalid,
+    // A non_canonical_bump case whose seeds have no valid bump below the canonical one.
+    NoNonCanonicalBump,
+    // `--fail-fast` stopped the run first.
+    FailFast,
+}
+
+impl SkipReason {
+    // Stable string form used as `skipped_reason` in the report.
+    pub fn label(self) -> &'static str {
+        match self {
+            SkipReason::ProgramBinaryInvalid => "program_binary_invalid",
+            SkipReason::NoNonCanonicalBump => "no_non_canonical_bump",
+            SkipReason::FailFast => "fail_fast",
+        }
+    }
+}
+
+// One setup-chain instruction executed before a case.
+#[derive(Debug, Clone)]
+pub struct SetupStep {
+    pub instruction: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+// A field of a fabricated PDA the harness filled in, with the value it wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FabricatedField {
+    pub account: String,
+    pub field: String,
+    pub value: String,
+}
+
+// `foreign_pda` substitution: `expected` is what the case's signers derive `account` to,
+// `substituted` what the same seeds give for `owner`, an unrelated user.
+#[derive(Debug, Clone)]
+pub struct ForeignPda {
+    pub account: String,
+    pub expected: Address,
+    pub substituted: Address,
+    pub owner: Address,
+}
+
+// `non_canonical_bump` substitution: `substituted` is `account`'s seeds with `bump`, a valid
+// bump below `canonical_bump`, the one `find_program_address` returns for `expected`.
+#[derive(Debug, Clone)]
+pub struct BumpSwap {
+    pub account: String,
+    pub expected: Address,
+    pub canonical_bump: u8,
+    pub substituted: Address,
+    pub bump: u8,
+}
+
+// `wrong_authority` substitution: PDAs stay derived from `authority`, `signer` signed as
+// `account`.
+#[derive(Debug, Clone)]
+pub struct AuthoritySwap {
+    pub account: String,
+    pub authority: Address,
+    pub signer: Address,
+}
+
+// One `--sequence` step, run on the state the previous steps left behind.
+#[derive(Debug, Clone)]
+pub struct SequenceStep {
+    pub idl_file: String,
+    pub instruction: String,
+    pub success: bool,
     pub error: Option<String>,
+    pub logs: Vec<String>,
+    pub compute_units: Option<u64>,
+}
+
+// A PDA whose seeds couldn't be resolved for a case; the account got a random key instead.
+#[derive(Debug, Clone)]
+pub struct UnresolvedSeed {
+    pub account: String,
+    pub seed: String,
+    pub reason: String,
+}
+
+// Per-phase wall time for one case (only collected with --debug-harness).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HarnessTimings {
+    pub add_program: Duration,
+    pub airdrop: Duration,
+    pub build_accounts: Duration,
+    pub send_ix: Duration,
+}
+
+// CLI options that tune the `test` flow.
+#[derive(Debug, Default)]
+pub struct TestOptions {
+    pub rules_file: Option<PathBuf>,
+    pub fail_on_unsupported_types: bool,
+    pub offline: bool,
+    pub non_empty_strings: bool,
+    // `--fund-writable`: create writable non-signer, non-PDA accounts rent-exempt before sending.
+    pub fund_writable: bool,
+    // `--fabricated-balance`: value written to u64 balance fields of fabricated PDAs.
+    pub fabricated_balance: u64,
+    // `--fixtures`: JSON file of accounts to seed by name before each case.
+    pub fixtures: Option<PathBuf>,
+    // `--assertions`: JSON file of account-state checks for base cases.
+    pub assertions: Option<PathBuf>,
+    // `--idl-dir` / `--deploy-dir` in place of `target/idl` / `target/deploy`.
+    pub idl_dir: Option<PathBuf>,
+    pub deploy_dir: Option<PathBuf>,
+    // `--idl` files loaded instead of scanning the IDL directory.
+    pub idl_files: Vec<PathBuf>,
+    pub debug_harness: bool,
+    pub warnings_as_errors: bool,
+    pub error_on: Vec<String>,
+    pub spec_file: Option<PathBuf>,
+    pub duplicate_accounts_must_fail: bool,
+    pub extra_accounts_must_fail: bool,
+    // `--expect-log <instruction>=<substring>`: base cases that must log `substring`.
+    pub expect_logs: Vec<String>,
+    // `--max-cu [<instruction>=]<units>`: compute-unit ceilings for base cases.
+    pub max_cu: Vec<String>,
+    // `--fuzz <n>`: random-args cases per instruction, drawn from `fuzz_seed`.
+    pub fuzz: Option<usize>,
+    pub fuzz_seed: Option<u64>,
+    // `--case-id`: replay this one case verbosely instead of running the suite.
+    pub case_id: Option<String>,
+    // Only run cases for these instruction names (empty = all).
+    pub instructions: Vec<String>,
+    // `--filter`: only instructions whose name matches this regex.
+    pub instruction_regex: Option<String>,
+    pub list_instructions: bool,
+    // Only run cases of these mutation kinds (`--mutation` + `--mutations`; empty = all).
+    pub mutations: Vec<String>,
+    // `--sequence`: instructions run in order on one persistent LiteSVM after the cases.
+    pub sequence: Vec<String>,
+    // `--program`: only test the program with this IDL name, file stem or address.
+    pub program: Option<String>,
+    // `--program-id <name>:<pubkey>` overrides, checked before the IDL address.
+    pub program_ids: Vec<String>,
+    // `--map <idl_stem>=<so_filename>` pairings, checked before the name heuristic.
+    pub so_map: Vec<String>,
+    // Worker threads for case execution (None = one per CPU).
+    pub jobs: Option<NonZeroUsize>,
+    // Stop executing after the first case that doesn't match its expectation.
+    pub fail_fast: bool,
+    // Fresh LiteSVM (and program deployment) per case instead of one reused per worker.
+    pub isolate: bool,
+    pub junit: Option<PathBuf>,
+    // `--output`: report.json destination; `-` streams it to stdout.
+    pub output: Option<PathBuf>,
+    // `--update-baseline`: where to copy report.json after a successful run.
+    pub update_baseline: Option<PathBuf>,
+    // Plain PASS/FAIL console labels even on a terminal.
+    pub no_color: bool,
+    // Also write report.md (`--format md`).
+    pub markdown: bool,
+    // Also write report.html (`--format html`).
+    pub html: bool,
+    // Also write report.tap (`--format tap`).
+    pub tap: bool,
+    // Also write report.sarif (`--format sarif`).
+    pub sarif: bool,
+    // `--summary-markdown`: where to write the compact PR-comment summary.
+    pub summary_markdown: Option<PathBuf>,
+}
+
+// Static analysis finding; `code` is stable so tooling can filter on it.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub code: &'static str,
+    pub message: String,
+    pub program: String,
+    pub location: Option<String>,
 }
 
 // Preflight and pipeline check results (used in report).
@@ -122,3 +697,43 @@ impl CheckResult {
         }
     }
 }
+
+// Parsed `--max-cu` values: a run-wide ceiling plus per-instruction ones that take precedence.
+#[derive(Debug, Default)]
+pub struct ComputeBudget {
+    pub default: Option<u64>,
+    pub per_instruction: Vec<(String, u64)>,
+}
+
+impl ComputeBudget {
+    pub fn is_active(&self) -> bool {
+        self.default.is_some() || !self.per_instruction.is_empty()
+    }
+
+    pub fn limit(&self, instruction: &str) -> Option<u64> {
+        self.per_instruction
+            .iter()
+            .find(|(ix, _)| ix == instruction)
+            .map(|(_, units)| *units)
+            .or(self.default)
+    }
+}
+
+// One `--fixtures` entry: the account a case's key for that name holds before it is sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountFixture {
+    pub lamports: u64,
+    pub owner: Address,
+    pub data: Vec<u8>,
+}
+
+// `--fixtures` entries keyed by IDL account name.
+pub type AccountFixtures = BTreeMap<String, AccountFixture>;
+
+// Runtime options for the `fuzz` command.
+#[derive(Debug, Default)]
+pub struct FuzzOptions {
+    pub iterations: usize,
+    // RNG seed; None picks one from the clock (and prints it).
+    pub seed: Option<u64>,
+}
@@ -0,0 +1,140 @@
+use crate::cases::{account_space_for, encode_arg_zero, ArgStyle};
+use crate::types::{ProgramSpec, SeedSpec, UnsupportedArg, Warning};
+use anyhow::{bail, Result};
+
+// Every warning code the suite can emit; `--error-on` only accepts these.
+pub const WARNING_CODES: &[&str] = &[
+    "unsupported_arg_type",
+    "dangling_seed",
+    "oversized_seed",
+    "no_signer_instruction",
+    "undetermined_account_space",
+    "unresolved_seed",
+];
+
+// Solana's per-seed length limit (`MAX_SEED_LEN`).
+const MAX_SEED_LEN: usize = 32;
+
+// Static IDL analysis: everything here is derived from the loaded specs, no execution needed.
+pub fn collect_warnings(programs: &[ProgramSpec], unsupported: &[UnsupportedArg]) -> Vec<Warning> {
+    let mut out = Vec::new();
+
+    for u in unsupported {
+        out.push(Warning {
+            code: "unsupported_arg_type",
+            message: format!("arg type {} not encodable: {}", u.ty, u.reason),
+            program: u.idl_file.clone(),
+            location: Some(format!("{}.{}", u.instruction, u.field)),
+        });
+    }
+
+    for p in programs {
+        for ix in &p.instructions {
+            if !ix.accounts.iter().any(|a| a.signer) {
+                out.push(Warning {
+                    code: "no_signer_instruction",
+                    message: "instruction declares no signer accounts".to_string(),
+                    program: p.idl_file.clone(),
+                    location: Some(ix.name.clone()),
+                });
+            }
+
+            for acc in &ix.accounts {
+                // Accounts whose size can't be derived are seeded with a fallback length.
+                if let Some(Err(reason)) =
+                    account_space_for(p, ix, &acc.name, ArgStyle::default())
+                {
+                    out.push(Warning {
+                        code: "undetermined_account_space",
+                        message: format!("account space could not be determined: {}", reason),
+                        program: p.idl_file.clone(),
+                        location: Some(format!("{}.{}", ix.name, acc.name)),
+                    });
+                }
+
+                for (i, seed) in acc.pda_seeds.iter().enumerate() {
+                    let location = Some(format!("{}.{}.seeds[{}]", ix.name, acc.name, i));
+                    match seed {
+                        SeedSpec::Const(bytes) if bytes.len() > MAX_SEED_LEN => {
+                            out.push(Warning {
+                                code: "oversized_seed",
+                                message: format!(
+                                    "const seed is {} bytes (max {})",
+                                    bytes.len(),
+                                    MAX_SEED_LEN
+                                ),
+                                program: p.idl_file.clone(),
+                                location,
+                            });
+                        }
+                        // `vault.authority` refers to a field of the `vault` account.
+                        SeedSpec::Account(path)
+                            if !ix.accounts.iter().any(|a| a.name == seed_base(path)) =>
+                        {
+                            out.push(Warning {
+                                code: "dangling_seed",
+                                message: format!(
+                                    "seed references `{}`, which is not an account of this instruction",
+                                    path
+                                ),
+                                program: p.idl_file.clone(),
+                                location,
+                            });
+                        }
+                        SeedSpec::Arg(path) => match ix.args.iter().find(|a| a.name == *path) {
+                            None => out.push(Warning {
+                                code: "dangling_seed",
+                                message: format!(
+                                    "seed references `{}`, which is not an arg of this instruction",
+                                    path
+                                ),
+                                program: p.idl_file.clone(),
+                                location,
+                            }),
+                            // The PDA can't be derived, so cases fall back to a random key.
+                            Some(arg) => {
+                                if let Err(reason) = encode_arg_zero(&arg.ty, &p.types) {
+                                    out.push(Warning {
+                                        code: "unresolved_seed",
+                                        message: format!(
+                                            "arg seed `{}` can't be encoded: {}",
+                                            path, reason
+                                        ),
+                                        program: p.idl_file.clone(),
+                                        location,
+                                    });
+                                }
+                            }
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn seed_base(path: &str) -> &str {
+    path.split_once('.').map_or(path, |(base, _)| base)
+}
+
+// Reject `--error-on` codes that no analysis can produce.
+pub fn validate_codes(codes: &[String]) -> Result<()> {
+    for c in codes {
+        if !WARNING_CODES.contains(&c.as_str()) {
+            bail!(
+                "Unknown warning code `{}`. Valid codes: {}",
+                c,
+                WARNING_CODES.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+// Whether a warning should fail the run under `--warnings-as-errors` / `--error-on`.
+pub fn is_promoted(w: &Warning, all: bool, codes: &[String]) -> bool {
+    all || codes.iter().any(|c| c == w.code)
+}
@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 
+// Default id used by the suite and its tests; `localnet-id` swaps in the Anchor.toml localnet key.
+#[cfg(not(feature = "localnet-id"))]
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+#[cfg(feature = "localnet-id")]
+declare_id!("GLnH5huAyPLjEY9rNkWceL5mi9zYwwua5apJcZm1hC51");
 
 #[program]
 pub mod test_vault {
@@ -12,7 +16,14 @@ pub mod test_vault {
         vault.authority = ctx.accounts.user.key();
         vault.balance = 0;
         vault.last_update = Clock::get()?.unix_timestamp;
+        vault.withdrawal_limit = u64::MAX;
         msg!("Vault initialized for {}", vault.authority);
+        emit!(VaultInitialized {
+            authority: vault.authority,
+            amount: 0,
+            new_balance: vault.balance,
+            timestamp: vault.last_update,
+        });
         Ok(())
     }
 
@@ -28,35 +39,110 @@ pub mod test_vault {
         system_program::transfer(cpi_ctx, amount)?;
 
         let vault = &mut ctx.accounts.vault;
-        vault.balance += amount;
+        vault.balance = vault
+            .balance
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
         vault.last_update = Clock::get()?.unix_timestamp;
         msg!("Deposited {} lamports. New balance: {}", amount, vault.balance);
+        emit!(Deposited {
+            authority: vault.authority,
+            amount,
+            new_balance: vault.balance,
+            timestamp: vault.last_update,
+        });
         Ok(())
     }
 
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         require!(ctx.accounts.vault.balance >= amount, VaultError::InsufficientFunds);
         require!(ctx.accounts.vault.authority == ctx.accounts.user.key(), VaultError::Unauthorized);
+        require!(amount <= ctx.accounts.vault.withdrawal_limit, VaultError::ExceedsLimit);
+        // The vault must stay rent-exempt, or its lamports and `balance` could drift apart.
+        let rent_minimum = Rent::get()?.minimum_balance(Vault::SPACE);
+        let remaining = ctx.accounts.vault.to_account_info().lamports().checked_sub(amount);
+        require!(
+            remaining.is_some_and(|left| left >= rent_minimum),
+            VaultError::WouldBreakRentExemption
+        );
         
-        let user_key = ctx.accounts.user.key();
-        let seeds = &[
-            b"vault".as_ref(),
-            user_key.as_ref(),
-            &[ctx.bumps.vault],
-        ];
-        let signer = &[&seeds[..]];
+        // The vault holds data, so the system program won't debit it; as the vault's owner the
+        // program moves the lamports itself.
+        ctx.accounts.vault.sub_lamports(amount)?;
+        ctx.accounts.user.add_lamports(amount)?;
         
-        let cpi_accounts = anchor_lang::system_program::Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.user.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.system_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        anchor_lang::system_program::transfer(cpi_ctx, amount)?;
-        
-        ctx.accounts.vault.balance -= amount;
+        ctx.accounts.vault.balance = ctx
+            .accounts
+            .vault
+            .balance
+            .checked_sub(amount)
+            .ok_or(VaultError::MathOverflow)?;
         ctx.accounts.vault.last_update = Clock::get()?.unix_timestamp;
         msg!("Withdrew {}. New balance: {}", amount, ctx.accounts.vault.balance);
+        emit!(Withdrawn {
+            authority: ctx.accounts.vault.authority,
+            amount,
+            new_balance: ctx.accounts.vault.balance,
+            timestamp: ctx.accounts.vault.last_update,
+        });
+        Ok(())
+    }
+
+    // Caps what a single `withdraw` may take; new vaults start without a cap (u64::MAX).
+    pub fn set_limit(ctx: Context<SetLimit>, limit: u64) -> Result<()> {
+        require!(ctx.accounts.vault.authority == ctx.accounts.user.key(), VaultError::Unauthorized);
+        ctx.accounts.vault.withdrawal_limit = limit;
+        ctx.accounts.vault.last_update = Clock::get()?.unix_timestamp;
+        msg!("Withdrawal limit set to {}", limit);
+        Ok(())
+    }
+
+    // Moves lamports and recorded balance from the signer's vault to `recipient`'s. Both vaults
+    // are program-owned, so lamports move directly instead of through the system program.
+    pub fn transfer(ctx: Context<Transfer>, amount: u64) -> Result<()> {
+        require_keys_neq!(
+            ctx.accounts.vault.key(),
+            ctx.accounts.destination_vault.key(),
+            VaultError::SameVault
+        );
+        require!(ctx.accounts.vault.balance >= amount, VaultError::InsufficientFunds);
+        require!(ctx.accounts.vault.authority == ctx.accounts.user.key(), VaultError::Unauthorized);
+        let rent_minimum = Rent::get()?.minimum_balance(Vault::SPACE);
+        let remaining = ctx.accounts.vault.get_lamports().checked_sub(amount);
+        require!(
+            remaining.is_some_and(|left| left >= rent_minimum),
+            VaultError::WouldBreakRentExemption
+        );
+
+        ctx.accounts.vault.sub_lamports(amount)?;
+        ctx.accounts.destination_vault.add_lamports(amount)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let source = &mut ctx.accounts.vault;
+        source.balance = source
+            .balance
+            .checked_sub(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        source.last_update = now;
+        let destination = &mut ctx.accounts.destination_vault;
+        destination.balance = destination
+            .balance
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        destination.last_update = now;
+        msg!(
+            "Transferred {} from {} to {}",
+            amount,
+            ctx.accounts.vault.authority,
+            ctx.accounts.destination_vault.authority
+        );
+        Ok(())
+    }
+
+    // `close = user` hands the vault's lamports back to the authority and zeroes the account.
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        require!(ctx.accounts.vault.authority == ctx.accounts.user.key(), VaultError::Unauthorized);
+        msg!("Vault closed for {}", ctx.accounts.vault.authority);
         Ok(())
     }
 }
@@ -66,7 +152,7 @@ pub struct InitializeVault<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + 32 + 8 + 8,
+        space = Vault::SPACE,
         seeds = [b"vault", user.key().as_ref()],
         bump
     )]
@@ -102,11 +188,86 @@ pub struct Withdraw<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetLimit<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Transfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", user.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"vault", recipient.key().as_ref()],
+        bump
+    )]
+    pub destination_vault: Account<'info, Vault>,
+    /// CHECK: only seeds `destination_vault`; never read or written.
+    pub recipient: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", user.key().as_ref()],
+        bump,
+        close = user
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
 #[account]
 pub struct Vault {
     pub authority: Pubkey,
     pub balance: u64,
     pub last_update: i64,
+    pub withdrawal_limit: u64,
+}
+
+impl Vault {
+    // Discriminator + authority + balance + last_update + withdrawal_limit.
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8;
+}
+
+// Structured records of each balance change for indexers; the `msg!` lines stay for existing
+// log readers.
+#[event]
+pub struct VaultInitialized {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Deposited {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+    pub timestamp: i64,
 }
 
 #[error_code]
@@ -115,4 +276,12 @@ pub enum VaultError {
     InsufficientFunds,
     #[msg("Unauthorized withdrawal")]
     Unauthorized,
+    #[msg("Vault balance overflow")]
+    MathOverflow,
+    #[msg("Withdrawal would leave the vault below rent exemption")]
+    WouldBreakRentExemption,
+    #[msg("Source and destination vault are the same account")]
+    SameVault,
+    #[msg("Withdrawal exceeds the vault's limit")]
+    ExceedsLimit,
 }
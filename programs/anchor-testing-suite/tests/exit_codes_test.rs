@@ -0,0 +1,104 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+// Fresh, empty project directory for one test.
+fn project_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("anchor-suite-exit-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn exit_code(args: &[&str]) -> i32 {
+    let output = Command::new(env!("CARGO_BIN_EXE_pda-scanner"))
+        .args(args)
+        .output()
+        .expect("run pda-scanner");
+    output.status.code().expect("exited with a code")
+}
+
+// `test` without the smoke run, writing its report into the project instead of the fixtures.
+fn run_test(project: &Path) -> i32 {
+    let report = project.join("report.json");
+    exit_code(&[
+        "test",
+        "--offline",
+        "--project-dir",
+        project.to_str().unwrap(),
+        "--output",
+        report.to_str().unwrap(),
+    ])
+}
+
+#[test]
+fn missing_build_artifacts_exit_with_preflight_code() {
+    let project = project_dir("unbuilt");
+    assert_eq!(run_test(&project), 2);
+    // The minimal report is still written.
+    assert!(project.join("report.json").exists());
+}
+
+#[test]
+fn no_testable_program_exits_with_its_own_code() {
+    let project = project_dir("empty-idl");
+    fs::create_dir_all(project.join("target/idl")).unwrap();
+    fs::create_dir_all(project.join("target/deploy")).unwrap();
+    assert_eq!(run_test(&project), 3);
+}
+
+#[test]
+fn regressed_cases_exit_with_case_failure_code() {
+    let baseline = format!("{}/reports/baseline.json", FIXTURES);
+    let regressed = format!("{}/reports/regressed.json", FIXTURES);
+    assert_eq!(
+        exit_code(&["diff", "--baseline", &baseline, "--current", &regressed]),
+        4
+    );
+    assert_eq!(
+        exit_code(&["diff", "--baseline", &baseline, "--current", &baseline]),
+        0
+    );
+}
+
+#[test]
+fn io_and_usage_errors_exit_with_tool_error_code() {
+    let missing = format!("{}/reports/missing.json", FIXTURES);
+    assert_eq!(exit_code(&["diff", "--baseline", &missing]), 1);
+    assert_eq!(exit_code(&["test", "--jobs", "0"]), 1);
+}
+
+#[test]
+fn scan_of_a_built_idl_exits_zero() {
+    let project = project_dir("scan");
+    fs::create_dir_all(project.join("target/idl")).unwrap();
+    fs::copy(
+        format!("{}/idl/test_vault.modern.json", FIXTURES),
+        project.join("target/idl/test_vault.json"),
+    )
+    .unwrap();
+    assert_eq!(
+        exit_code(&["scan", "--project-dir", project.to_str().unwrap()]),
+        0
+    );
+}
+
+#[test]
+fn help_documents_the_exit_codes() {
+    for args in [&["--help"][..], &["test", "--help"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_pda-scanner"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        let help = String::from_utf8_lossy(&output.stdout);
+        assert!(help.contains("Exit codes:"), "{}", help);
+        assert!(help.contains("4  the suite ran and a check or generated case failed"));
+        assert!(help.contains("5  the optional cargo smoke test failed"));
+    }
+}
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+use std::str::FromStr;
+
+#[cfg(not(feature = "localnet-id"))]
+#[test]
+fn test_default_program_id() {
+    // Default build keeps the id the suite and smoke test deploy under.
+    let expected = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
+    assert_eq!(anchor_testing_suite::ID, expected);
+}
+
+#[cfg(feature = "localnet-id")]
+#[test]
+fn test_localnet_program_id() {
+    // `--features localnet-id` builds against the Anchor.toml localnet key.
+    let expected = Pubkey::from_str("GLnH5huAyPLjEY9rNkWceL5mi9zYwwua5apJcZm1hC51").unwrap();
+    assert_eq!(anchor_testing_suite::ID, expected);
+}
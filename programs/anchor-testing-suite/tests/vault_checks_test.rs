@@ -0,0 +1,257 @@
+#![cfg(feature = "cli")]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData};
+use anchor_testing_suite::{instruction, Vault, VaultError};
+use litesvm::LiteSVM;
+use solana_account::Account;
+use solana_address::Address;
+use solana_instruction::{account_meta::AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_message::Message;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::path::PathBuf;
+
+const SYSTEM_PROGRAM: Address = Address::new_from_array([0; 32]);
+
+// Lamports the user starts with, and the fee it pays as the only signer (LiteSVM's default).
+const USER_LAMPORTS: u64 = 10_000_000_000;
+const FEE: u64 = 5_000;
+
+// The bundled vault program, built by `anchor build` at the workspace root.
+fn vault_so() -> PathBuf {
+    let workspace_so = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../target/deploy/anchor_testing_suite.so");
+    let local_so = PathBuf::from("target/deploy/anchor_testing_suite.so");
+    if workspace_so.exists() {
+        workspace_so
+    } else {
+        local_so
+    }
+}
+
+// What the vault holds before the instruction runs.
+struct VaultState {
+    // Recorded authority; the signing user when None.
+    authority: Option<Pubkey>,
+    balance: u64,
+    withdrawal_limit: u64,
+    // Lamports on top of the rent-exempt minimum.
+    spare: u64,
+}
+
+impl Default for VaultState {
+    fn default() -> Self {
+        Self {
+            authority: None,
+            balance: 0,
+            withdrawal_limit: u64::MAX,
+            spare: 0,
+        }
+    }
+}
+
+// The SVM after one instruction ran against a vault, for checking the state it left.
+struct Sent {
+    svm: LiteSVM,
+    user: Address,
+    vault: Address,
+    succeeded: bool,
+    // Custom error code the transaction failed with, if any.
+    error: Option<u32>,
+}
+
+impl Sent {
+    fn lamports(&self, key: &Address) -> u64 {
+        self.svm.get_account(key).map_or(0, |a| a.lamports)
+    }
+
+    fn vault_state(&self) -> Vault {
+        let account = self.svm.get_account(&self.vault).unwrap();
+        Vault::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+}
+
+// Custom error code `data` fails with against a vault in `state`, if any.
+fn send_to_vault(state: VaultState, data: Vec<u8>) -> Option<u32> {
+    send(state, data).error
+}
+
+// Send `data` as the vault's user against a vault in `state`.
+fn send(state: VaultState, data: Vec<u8>) -> Sent {
+    let program_id = Address::new_from_array(anchor_testing_suite::ID.to_bytes());
+    let so = vault_so();
+    let bytes =
+        std::fs::read(&so).unwrap_or_else(|e| panic!("failed to read {}: {}", so.display(), e));
+    let mut svm = LiteSVM::new();
+    svm.add_program(program_id, &bytes).unwrap();
+
+    let user = Keypair::new();
+    svm.airdrop(&user.pubkey(), USER_LAMPORTS).unwrap();
+    let (vault_key, _) =
+        Address::find_program_address(&[b"vault", user.pubkey().as_ref()], &program_id);
+    let vault = Vault {
+        authority: state
+            .authority
+            .unwrap_or(Pubkey::new_from_array(user.pubkey().to_bytes())),
+        balance: state.balance,
+        last_update: 0,
+        withdrawal_limit: state.withdrawal_limit,
+    };
+    let mut vault_data = Vec::new();
+    vault.try_serialize(&mut vault_data).unwrap();
+    let account = Account {
+        lamports: svm.minimum_balance_for_rent_exemption(Vault::SPACE) + state.spare,
+        data: vault_data,
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(vault_key, account).unwrap();
+
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(vault_key, false),
+            AccountMeta::new(user.pubkey(), true),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+        ],
+        data,
+    };
+    let blockhash = svm.latest_blockhash();
+    let msg = Message::new_with_blockhash(&[ix], Some(&user.pubkey()), &blockhash);
+    let tx = Transaction::new(&[&user], msg, blockhash);
+    let err = svm.send_transaction(tx).err().map(|e| format!("{:?}", e.err));
+    Sent {
+        svm,
+        user: user.pubkey(),
+        vault: vault_key,
+        succeeded: err.is_none(),
+        error: err.as_deref().and_then(custom_code),
+    }
+}
+
+fn custom_code(error: &str) -> Option<u32> {
+    let start = error.find("Custom(")? + "Custom(".len();
+    error[start..].split(')').next()?.parse().ok()
+}
+
+fn deposit(amount: u64) -> Vec<u8> {
+    instruction::Deposit { amount }.data()
+}
+
+fn withdraw(amount: u64) -> Vec<u8> {
+    instruction::Withdraw { amount }.data()
+}
+
+fn near_max() -> VaultState {
+    VaultState {
+        balance: u64::MAX - 10,
+        ..VaultState::default()
+    }
+}
+
+fn someone_elses() -> VaultState {
+    VaultState {
+        authority: Some(Pubkey::new_unique()),
+        ..VaultState::default()
+    }
+}
+
+#[test]
+fn deposit_past_u64_max_fails_with_math_overflow() {
+    assert_eq!(
+        send_to_vault(near_max(), deposit(11)),
+        Some(u32::from(VaultError::MathOverflow))
+    );
+}
+
+#[test]
+fn deposit_up_to_u64_max_still_succeeds() {
+    assert_eq!(send_to_vault(near_max(), deposit(10)), None);
+}
+
+// The lamports leave the vault PDA for the user and the recorded balance follows.
+#[test]
+fn withdraw_moves_lamports_to_the_user() {
+    let state = VaultState {
+        balance: 1_000_000,
+        spare: 1_000_000,
+        ..VaultState::default()
+    };
+    let sent = send(state, withdraw(400_000));
+    assert!(sent.succeeded);
+    let rent_minimum = sent.svm.minimum_balance_for_rent_exemption(Vault::SPACE);
+    assert_eq!(sent.lamports(&sent.vault), rent_minimum + 600_000);
+    assert_eq!(sent.lamports(&sent.user), USER_LAMPORTS - FEE + 400_000);
+    assert_eq!(sent.vault_state().balance, 600_000);
+}
+
+// The recorded balance covers the amount, but the lamports above rent exemption don't.
+#[test]
+fn withdraw_below_rent_exemption_is_rejected() {
+    let state = VaultState {
+        balance: 1_000_000,
+        spare: 1_000,
+        ..VaultState::default()
+    };
+    assert_eq!(
+        send_to_vault(state, withdraw(1_001)),
+        Some(u32::from(VaultError::WouldBreakRentExemption))
+    );
+}
+
+// Draining every lamport above rent exemption is still allowed.
+#[test]
+fn withdraw_down_to_the_rent_minimum_succeeds() {
+    let state = VaultState {
+        balance: 1_000_000,
+        spare: 1_000,
+        ..VaultState::default()
+    };
+    let sent = send(state, withdraw(1_000));
+    assert!(sent.succeeded);
+    let rent_minimum = sent.svm.minimum_balance_for_rent_exemption(Vault::SPACE);
+    assert_eq!(sent.lamports(&sent.vault), rent_minimum);
+    assert_eq!(sent.vault_state().balance, 999_000);
+}
+
+#[test]
+fn authority_can_close_its_vault() {
+    let close = instruction::CloseVault {}.data();
+    assert_eq!(send_to_vault(VaultState::default(), close), None);
+}
+
+#[test]
+fn closing_a_vault_recorded_to_someone_else_is_unauthorized() {
+    let close = instruction::CloseVault {}.data();
+    assert_eq!(
+        send_to_vault(someone_elses(), close),
+        Some(u32::from(VaultError::Unauthorized))
+    );
+}
+
+#[test]
+fn withdraw_above_the_limit_is_rejected() {
+    let state = VaultState {
+        balance: 1_000_000,
+        withdrawal_limit: 500,
+        spare: 1_000_000,
+        ..VaultState::default()
+    };
+    assert_eq!(
+        send_to_vault(state, withdraw(501)),
+        Some(u32::from(VaultError::ExceedsLimit))
+    );
+}
+
+#[test]
+fn only_the_authority_sets_the_limit() {
+    let set_limit = || instruction::SetLimit { limit: 500 }.data();
+    assert_eq!(send_to_vault(VaultState::default(), set_limit()), None);
+    assert_eq!(
+        send_to_vault(someone_elses(), set_limit()),
+        Some(u32::from(VaultError::Unauthorized))
+    );
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+// The bundled vault program, built by `anchor build` at the workspace root.
+fn vault_so() -> PathBuf {
+    let workspace_so = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../target/deploy/anchor_testing_suite.so");
+    let local_so = PathBuf::from("target/deploy/anchor_testing_suite.so");
+    if workspace_so.exists() {
+        workspace_so
+    } else {
+        local_so
+    }
+}
+
+// The substituted vault exists and looks real, so every wrong_pda case must be rejected by the
+// seeds constraint (2006) rather than by a missing account.
+#[test]
+fn wrong_pda_cases_fail_on_the_seeds_constraint() {
+    let project =
+        std::env::temp_dir().join(format!("anchor-suite-wrong-pda-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&project);
+    fs::create_dir_all(project.join("target/idl")).unwrap();
+    fs::create_dir_all(project.join("target/deploy")).unwrap();
+    fs::copy(
+        format!("{}/idl/test_vault.modern.json", FIXTURES),
+        project.join("target/idl/test_vault.json"),
+    )
+    .unwrap();
+    let so = vault_so();
+    fs::copy(&so, project.join("target/deploy/test_vault.so"))
+        .unwrap_or_else(|e| panic!("failed to copy {}: {}", so.display(), e));
+
+    let report = project.join("report.json");
+    let status = Command::new(env!("CARGO_BIN_EXE_pda-scanner"))
+        .args([
+            "test",
+            "--offline",
+            "--mutation",
+            "wrong_pda",
+            "--project-dir",
+        ])
+        .arg(&project)
+        .arg("--output")
+        .arg(&report)
+        .status()
+        .expect("run pda-scanner");
+    assert_eq!(status.code(), Some(0));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    let cases = report["executed_cases"].as_array().unwrap();
+    // Every instruction takes the vault PDA; transfer also takes the destination vault.
+    assert_eq!(cases.len(), 7);
+    for case in cases {
+        assert_eq!(case["passed"], true, "{}", case);
+        assert_eq!(case["error_code"], 2006, "{}", case);
+    }
+
+    let _ = fs::remove_dir_all(&project);
+}